@@ -0,0 +1,329 @@
+use std::collections::HashMap;
+use std::io::Read;
+use std::time::{Duration, Instant};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{self, json, Value};
+use tauri::async_runtime::block_on;
+use tiny_http::{Header, Method, Request, Response, Server};
+use url::Url;
+
+use crate::data::app_settings::write_app_settings;
+use crate::data::groups::{create_group, delete_group, move_group, rename_group, set_group_collapsed, set_group_metadata, validate_group_metadata, validate_group_name};
+use crate::data::todo_data::{get_data_dir, read_group_store, read_todo_store};
+use crate::data::{add_todo, delete_todo, set_todo_completed, update_todo};
+use crate::models::{AppSettings, GroupDeleteStrategy, TodoGroup, TodoPatch};
+use crate::system::app_lock::ensure_unlocked;
+use super::{generate_token, token_matches};
+
+// 检查设置变化（启用、停用或更换端口）的间隔
+const SETTINGS_CHECK_INTERVAL: Duration = Duration::from_secs(5);
+
+// 请求体大小上限
+const MAX_BODY_BYTES: u64 = 1024 * 1024;
+
+// 接口错误：HTTP状态码和错误信息
+type ApiError = (u16, String);
+
+// 根据命令返回的错误信息确定状态码
+fn error_status(message: String) -> ApiError {
+    let status = if message.starts_with("未找到") {
+        404
+    } else if message.contains("应用已锁定") || message.contains("数据已加密") {
+        423
+    } else {
+        400
+    };
+    (status, message)
+}
+
+fn parse_body<T: DeserializeOwned>(body: &str) -> Result<T, ApiError> {
+    serde_json::from_str(body).map_err(|e| (400, format!("解析请求JSON失败: {}", e)))
+}
+
+fn to_value<T: Serialize>(value: &T) -> Result<Value, ApiError> {
+    serde_json::to_value(value).map_err(|e| (500, format!("序列化数据失败: {}", e)))
+}
+
+#[derive(Deserialize)]
+struct NewTodo {
+    text: String,
+    #[serde(default)]
+    group_id: Option<String>,
+    #[serde(default)]
+    priority: Option<i32>,
+    #[serde(default)]
+    deadline: Option<i64>,
+    #[serde(default)]
+    tags: Option<Vec<String>>,
+}
+
+#[derive(Deserialize)]
+struct NewGroup {
+    name: String,
+    #[serde(default)]
+    parent_id: Option<String>,
+}
+
+// 读取分组列表（不经过 load_group_data，避免改变前端的保存基准）
+fn list_groups(app: &tauri::AppHandle) -> Result<Vec<TodoGroup>, ApiError> {
+    ensure_unlocked().map_err(error_status)?;
+    let data_dir = get_data_dir(app).map_err(error_status)?;
+    let mut groups = read_group_store(&data_dir).map_err(error_status)?.groups;
+    groups.sort_by_key(|group| group.order);
+    Ok(groups)
+}
+
+fn find_group(app: &tauri::AppHandle, group_id: &str) -> Result<TodoGroup, ApiError> {
+    list_groups(app)?.into_iter()
+        .find(|group| group.id == group_id)
+        .ok_or_else(|| (404, format!("未找到指定的分组: id='{}'", group_id)))
+}
+
+// 修改分组：name、collapsed、parent_id 以及颜色等分组设置，未传入的字段保持不变
+fn patch_group(app: &tauri::AppHandle, group_id: &str, patch: serde_json::Map<String, Value>) -> Result<TodoGroup, ApiError> {
+    let group = find_group(app, group_id)?;
+    let group_id = group_id.to_string();
+
+    // 先校验所有字段，避免部分字段无效时只修改了一部分
+    let name = match patch.get("name") {
+        Some(name) => {
+            let name = name.as_str().ok_or((400, "name 必须是字符串".to_string()))?;
+            Some(validate_group_name(name).map_err(error_status)?)
+        }
+        None => None,
+    };
+    let collapsed = match patch.get("collapsed") {
+        Some(collapsed) => Some(collapsed.as_bool().ok_or((400, "collapsed 必须是布尔值".to_string()))?),
+        None => None,
+    };
+    let parent_id: Option<Option<String>> = match patch.get("parent_id") {
+        Some(parent_id) => Some(serde_json::from_value(parent_id.clone())
+            .map_err(|_| (400, "parent_id 必须是字符串或 null".to_string()))?),
+        None => None,
+    };
+
+    let metadata_fields = ["color", "icon", "default_priority", "default_deadline_offset"];
+    let metadata = if metadata_fields.iter().any(|field| patch.contains_key(*field)) {
+        // 未传入的分组设置沿用当前值
+        let mut metadata = json!({
+            "color": group.color,
+            "icon": group.icon,
+            "default_priority": group.default_priority,
+            "default_deadline_offset": group.default_deadline_offset,
+        });
+        for field in metadata_fields {
+            if let Some(value) = patch.get(field) {
+                metadata[field] = value.clone();
+            }
+        }
+        fn field<T: DeserializeOwned>(metadata: &Value, name: &str) -> Result<T, ApiError> {
+            serde_json::from_value(metadata[name].clone()).map_err(|_| (400, format!("{} 的类型无效", name)))
+        }
+        let color: Option<String> = field(&metadata, "color")?;
+        let icon: Option<String> = field(&metadata, "icon")?;
+        let default_priority: i32 = field(&metadata, "default_priority")?;
+        let default_deadline_offset: Option<String> = field(&metadata, "default_deadline_offset")?;
+        validate_group_metadata(color.as_deref(), default_priority, default_deadline_offset.as_deref())
+            .map_err(error_status)?;
+        Some((color, icon, default_priority, default_deadline_offset))
+    } else {
+        None
+    };
+
+    // 移动分组还需检查上级分组，放在最前面，失败时其他字段都还没有修改
+    if let Some(parent_id) = parent_id {
+        block_on(move_group(app.clone(), group_id.clone(), parent_id)).map_err(error_status)?;
+    }
+    if let Some(name) = name {
+        block_on(rename_group(app.clone(), group_id.clone(), name)).map_err(error_status)?;
+    }
+    if let Some(collapsed) = collapsed {
+        block_on(set_group_collapsed(app.clone(), group_id.clone(), collapsed)).map_err(error_status)?;
+    }
+    if let Some((color, icon, default_priority, default_deadline_offset)) = metadata {
+        block_on(set_group_metadata(app.clone(), group_id.clone(), color, icon, default_priority, default_deadline_offset))
+            .map_err(error_status)?;
+    }
+
+    find_group(app, &group_id)
+}
+
+// 处理一个请求，返回状态码和响应内容（None 表示没有响应内容）
+fn route(app: &tauri::AppHandle, method: &Method, url: &Url, body: &str) -> Result<(u16, Option<Value>), ApiError> {
+    let segments: Vec<&str> = url.path_segments()
+        .map(|segments| segments.filter(|segment| !segment.is_empty()).collect())
+        .unwrap_or_default();
+    let query: HashMap<String, String> = url.query_pairs().into_owned().collect();
+
+    match (method, segments.as_slice()) {
+        // 任务列表，可按 group_id（包括下级分组）和 completed 筛选
+        (Method::Get, ["todos"]) => {
+            ensure_unlocked().map_err(error_status)?;
+            let data_dir = get_data_dir(app).map_err(error_status)?;
+            let group_ids = crate::data::groups::group_filter(&data_dir, query.get("group_id").map(String::as_str))
+                .map_err(error_status)?;
+            let completed = match query.get("completed").map(String::as_str) {
+                None => None,
+                Some("true") => Some(true),
+                Some("false") => Some(false),
+                Some(other) => return Err((400, format!("无效的 completed 参数: {}", other))),
+            };
+            let mut todos = read_todo_store(&data_dir).map_err(error_status)?.todos;
+            todos.retain(|todo| {
+                group_ids.as_ref().map_or(true, |ids| ids.contains(&todo.group_id))
+                    && completed.map_or(true, |completed| todo.completed == completed)
+            });
+            Ok((200, Some(to_value(&todos)?)))
+        }
+        (Method::Post, ["todos"]) => {
+            let new_todo: NewTodo = parse_body(body)?;
+            let todo = block_on(add_todo(app.clone(), new_todo.text, new_todo.group_id, new_todo.priority, new_todo.deadline, new_todo.tags))
+                .map_err(error_status)?;
+            Ok((201, Some(to_value(&todo)?)))
+        }
+        (Method::Get, ["todos", todo_id]) => {
+            ensure_unlocked().map_err(error_status)?;
+            let data_dir = get_data_dir(app).map_err(error_status)?;
+            let todo = read_todo_store(&data_dir).map_err(error_status)?.todos.into_iter()
+                .find(|todo| todo.id == *todo_id)
+                .ok_or_else(|| (404, format!("未找到指定的todo项: id='{}'", todo_id)))?;
+            Ok((200, Some(to_value(&todo)?)))
+        }
+        (Method::Patch, ["todos", todo_id]) => {
+            let patch: TodoPatch = parse_body(body)?;
+            let todo = block_on(update_todo(app.clone(), todo_id.to_string(), patch)).map_err(error_status)?;
+            Ok((200, Some(to_value(&todo)?)))
+        }
+        (Method::Post, ["todos", todo_id, action @ ("complete" | "uncomplete")]) => {
+            let todo = block_on(set_todo_completed(app.clone(), todo_id.to_string(), *action == "complete"))
+                .map_err(error_status)?;
+            Ok((200, Some(to_value(&todo)?)))
+        }
+        (Method::Delete, ["todos", todo_id]) => {
+            block_on(delete_todo(app.clone(), todo_id.to_string())).map_err(error_status)?;
+            Ok((204, None))
+        }
+
+        (Method::Get, ["groups"]) => Ok((200, Some(to_value(&list_groups(app)?)?))),
+        (Method::Post, ["groups"]) => {
+            let new_group: NewGroup = parse_body(body)?;
+            let group = block_on(create_group(app.clone(), new_group.name, new_group.parent_id)).map_err(error_status)?;
+            Ok((201, Some(to_value(&group)?)))
+        }
+        (Method::Get, ["groups", group_id]) => Ok((200, Some(to_value(&find_group(app, group_id)?)?))),
+        (Method::Patch, ["groups", group_id]) => {
+            let group = patch_group(app, group_id, parse_body(body)?)?;
+            Ok((200, Some(to_value(&group)?)))
+        }
+        // 删除分组：?strategy=move_to_default（默认）| move_to_group&target_group_id=... | delete_todos
+        (Method::Delete, ["groups", group_id]) => {
+            let strategy: GroupDeleteStrategy = serde_json::from_value(json!({
+                "type": query.get("strategy").map(String::as_str).unwrap_or("move_to_default"),
+                "target_group_id": query.get("target_group_id"),
+            })).map_err(|e| (400, format!("无效的删除方式: {}", e)))?;
+            block_on(delete_group(app.clone(), group_id.to_string(), strategy)).map_err(error_status)?;
+            Ok((204, None))
+        }
+
+        _ => Err((404, format!("未找到请求的接口: {} {}", method, url.path()))),
+    }
+}
+
+fn json_header() -> Header {
+    Header::from_bytes(&b"Content-Type"[..], &b"application/json; charset=utf-8"[..])
+        .expect("固定的响应头总是有效的")
+}
+
+// 校验访问令牌并处理请求
+fn handle_request(app: &tauri::AppHandle, mut request: Request, token: &str) {
+    let authorized = request.headers().iter()
+        .find(|header| header.field.equiv("Authorization"))
+        .and_then(|header| header.value.as_str().strip_prefix("Bearer "))
+        .is_some_and(|provided| token_matches(token, provided.trim()));
+
+    let result = if !authorized {
+        Err((401, "访问令牌无效".to_string()))
+    } else {
+        let mut body = String::new();
+        match request.as_reader().take(MAX_BODY_BYTES).read_to_string(&mut body) {
+            Ok(_) => match Url::parse(&format!("http://127.0.0.1{}", request.url())) {
+                Ok(url) => route(app, request.method(), &url, &body),
+                Err(e) => Err((400, format!("无效的请求地址: {}", e))),
+            },
+            Err(e) => Err((400, format!("读取请求内容失败: {}", e))),
+        }
+    };
+
+    let (status, body) = match result {
+        Ok((status, body)) => (status, body),
+        Err((status, message)) => (status, Some(json!({ "error": message }))),
+    };
+    let response = Response::from_string(body.map(|body| body.to_string()).unwrap_or_default())
+        .with_status_code(status)
+        .with_header(json_header());
+    if let Err(e) = request.respond(response) {
+        log::error!("发送本机接口响应失败: {}", e);
+    }
+}
+
+// 当前设置下需要的服务端口和访问令牌，未启用时返回 None
+// 启用时还没有访问令牌则自动生成
+fn wanted_server(app: &tauri::AppHandle) -> Option<(u16, String)> {
+    let mut settings: AppSettings = block_on(crate::data::load_app_settings(app.clone())).ok()?;
+    if !settings.api_enabled {
+        return None;
+    }
+    if settings.api_token.is_empty() {
+        settings.api_token = generate_token();
+        if let Err(e) = write_app_settings(app, &settings) {
+            log::error!("保存本机接口访问令牌失败: {}", e);
+            return None;
+        }
+    }
+    Some((settings.api_port, settings.api_token))
+}
+
+// 启动本机HTTP接口，只监听 127.0.0.1，按设置启用、停用或更换端口
+pub fn start_api_server(app: tauri::AppHandle) {
+    std::thread::spawn(move || {
+        let mut server: Option<(u16, Server)> = None;
+        loop {
+            let wanted = wanted_server(&app);
+
+            let port = wanted.as_ref().map(|(port, _)| *port);
+            if server.as_ref().map(|(port, _)| *port) != port {
+                if server.take().is_some() {
+                    log::info!("本机HTTP接口已停止");
+                }
+                if let Some(port) = port {
+                    match Server::http(("127.0.0.1", port)) {
+                        Ok(new_server) => {
+                            log::info!("本机HTTP接口已启动: http://127.0.0.1:{}", port);
+                            server = Some((port, new_server));
+                        }
+                        Err(e) => log::error!("启动本机HTTP接口失败（端口 {}）: {}", port, e),
+                    }
+                }
+            }
+
+            let (Some((_, active)), Some((_, token))) = (&server, &wanted) else {
+                std::thread::sleep(SETTINGS_CHECK_INTERVAL);
+                continue;
+            };
+
+            // 处理请求，直到需要重新检查设置
+            let deadline = Instant::now() + SETTINGS_CHECK_INTERVAL;
+            while let Some(remaining) = deadline.checked_duration_since(Instant::now()) {
+                match active.recv_timeout(remaining) {
+                    Ok(Some(request)) => handle_request(&app, request, token),
+                    Ok(None) => break,
+                    Err(e) => {
+                        log::error!("接收本机接口请求失败: {}", e);
+                        break;
+                    }
+                }
+            }
+        }
+    });
+}
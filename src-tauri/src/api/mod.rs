@@ -0,0 +1,31 @@
+pub mod http;
+pub mod rpc;
+
+use argon2::password_hash::rand_core::{OsRng, RngCore};
+
+use crate::data::app_settings::write_app_settings;
+use crate::system::app_lock::ensure_unlocked;
+
+// 生成随机访问令牌（32字节，十六进制）
+pub(crate) fn generate_token() -> String {
+    let mut bytes = [0u8; 32];
+    OsRng.fill_bytes(&mut bytes);
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+// 校验访问令牌，比较耗时与令牌内容无关
+pub(crate) fn token_matches(expected: &str, provided: &str) -> bool {
+    !expected.is_empty()
+        && expected.len() == provided.len()
+        && expected.bytes().zip(provided.bytes()).fold(0, |diff, (a, b)| diff | (a ^ b)) == 0
+}
+
+// Tauri 命令：重新生成本机接口的访问令牌，旧令牌立即失效
+#[tauri::command]
+pub async fn regenerate_api_token(app: tauri::AppHandle) -> Result<String, String> {
+    ensure_unlocked()?;
+    let mut settings = crate::data::load_app_settings(app.clone()).await?;
+    settings.api_token = generate_token();
+    write_app_settings(&app, &settings)?;
+    Ok(settings.api_token)
+}
@@ -0,0 +1,528 @@
+use std::collections::HashMap;
+use std::io::{self, BufRead, BufReader, ErrorKind, Write};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use interprocess::local_socket::prelude::*;
+use interprocess::local_socket::{ListenerNonblockingMode, ListenerOptions, Name, SendHalf};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{self, json, Value};
+use tauri::async_runtime::block_on;
+use tauri::{Emitter, EventId, Listener};
+
+use crate::data::todo_data::{get_data_dir, read_group_store, read_todo_store, save_group_store, save_todo_store};
+use crate::models::{AppSettings, GroupDeleteStrategy, TimelineOptions, TimelineRange, Todo, TodoPatch};
+use crate::sync::merge::fill_missing_fields;
+use crate::system::app_lock::ensure_unlocked;
+use crate::{calendar, data, stats, sync, system, window};
+
+// 检查设置变化（启用或停用）的间隔
+const SETTINGS_CHECK_INTERVAL: Duration = Duration::from_secs(5);
+
+// 没有新连接时的等待间隔
+const ACCEPT_POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+// 订阅时未指定事件则订阅这些事件
+const DEFAULT_EVENTS: [&str; 11] = [
+    "todo-data-changed",
+    "group-data-changed",
+    "app-settings-changed",
+    "data-unlocked",
+    "app-locked",
+    "app-unlocked",
+    "webdav-sync-status",
+    "webdav-sync-conflict",
+    "caldav-sync-status",
+    "caldav-sync-conflict",
+    "data-file-invalid",
+];
+
+static NEXT_SUBSCRIPTION_ID: AtomicU64 = AtomicU64::new(1);
+
+// JSON-RPC 错误
+struct RpcError {
+    code: i64,
+    message: String,
+}
+
+impl RpcError {
+    fn new(code: i64, message: impl Into<String>) -> Self {
+        RpcError { code, message: message.into() }
+    }
+
+    fn invalid_params(message: impl Into<String>) -> Self {
+        RpcError::new(-32602, message)
+    }
+
+    // 命令本身返回的错误
+    fn command(message: String) -> Self {
+        RpcError::new(-32000, message)
+    }
+}
+
+// 控制接口的名称：Unix 上为当前用户的套接字文件，Windows 上为当前用户的命名管道
+pub fn socket_name() -> io::Result<Name<'static>> {
+    user_socket_name("deskhive")
+}
+
+// 标记应用正在运行的名称，不论是否启用控制接口都会监听
+fn instance_socket_name() -> io::Result<Name<'static>> {
+    user_socket_name("deskhive-instance")
+}
+
+fn user_socket_name(prefix: &str) -> io::Result<Name<'static>> {
+    #[cfg(unix)]
+    {
+        use interprocess::local_socket::GenericFilePath;
+        let dir = std::env::var_os("XDG_RUNTIME_DIR")
+            .map(std::path::PathBuf::from)
+            .unwrap_or_else(std::env::temp_dir);
+        let user = std::env::var("USER").unwrap_or_default();
+        dir.join(format!("{}-{}.sock", prefix, user))
+            .to_fs_name::<GenericFilePath>()
+            .map(|name| name.into_owned())
+    }
+    #[cfg(windows)]
+    {
+        use interprocess::local_socket::GenericNamespaced;
+        let user = std::env::var("USERNAME").unwrap_or_default();
+        format!("{}-{}", prefix, user)
+            .to_ns_name::<GenericNamespaced>()
+            .map(|name| name.into_owned())
+    }
+}
+
+// 连接方是否为当前用户：Unix 上比较有效用户ID，Windows 上比较连接进程所属用户的SID
+fn peer_is_current_user(stream: &LocalSocketStream) -> io::Result<bool> {
+    let creds = stream.peer_creds()?;
+    #[cfg(unix)]
+    {
+        // SAFETY: geteuid 没有失败的情况
+        Ok(creds.euid() == Some(unsafe { libc::geteuid() }))
+    }
+    #[cfg(windows)]
+    {
+        let pid = creds.pid().ok_or_else(|| io::Error::other("无法获取连接进程的ID"))?;
+        Ok(process_user_sid(Some(pid))? == process_user_sid(None)?)
+    }
+}
+
+// 读取进程所属用户的SID，pid 为 None 时读取当前进程
+#[cfg(windows)]
+fn process_user_sid(pid: Option<u32>) -> io::Result<Vec<u8>> {
+    use windows::Win32::Foundation::{CloseHandle, HANDLE};
+    use windows::Win32::Security::{GetLengthSid, GetTokenInformation, TokenUser, TOKEN_QUERY, TOKEN_USER};
+    use windows::Win32::System::Threading::{GetCurrentProcess, OpenProcess, OpenProcessToken, PROCESS_QUERY_LIMITED_INFORMATION};
+
+    unsafe {
+        let process = match pid {
+            Some(pid) => OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, false, pid).map_err(io::Error::other)?,
+            None => GetCurrentProcess(),
+        };
+        let mut token = HANDLE::default();
+        let opened = OpenProcessToken(process, TOKEN_QUERY, &mut token).as_bool();
+        if pid.is_some() {
+            let _ = CloseHandle(process);
+        }
+        if !opened {
+            return Err(io::Error::last_os_error());
+        }
+
+        // TOKEN_USER 后紧跟SID，按指针对齐分配缓冲区
+        let mut buffer = [0u64; 64];
+        let mut length = 0u32;
+        let queried = GetTokenInformation(token, TokenUser, Some(buffer.as_mut_ptr().cast()), std::mem::size_of_val(&buffer) as u32, &mut length).as_bool();
+        let _ = CloseHandle(token);
+        if !queried {
+            return Err(io::Error::last_os_error());
+        }
+
+        let sid = (*buffer.as_ptr().cast::<TOKEN_USER>()).User.Sid;
+        Ok(std::slice::from_raw_parts(sid.0 as *const u8, GetLengthSid(sid) as usize).to_vec())
+    }
+}
+
+// 通过控制接口读取设置时隐藏接口访问令牌、PIN哈希和旧版本留下的明文密码
+fn redact_settings(mut settings: AppSettings) -> AppSettings {
+    settings.api_token.clear();
+    settings.app_lock_pin_hash.clear();
+    settings.webdav_password.clear();
+    settings.caldav_password.clear();
+    settings
+}
+
+// 解析命名参数，未传入参数时视为空对象
+fn parse_params<T: DeserializeOwned>(params: Value) -> Result<T, RpcError> {
+    let params = match params {
+        Value::Null => json!({}),
+        Value::Object(_) => params,
+        _ => return Err(RpcError::invalid_params("params 必须是以参数名为键的对象")),
+    };
+    serde_json::from_value(params).map_err(|e| RpcError::invalid_params(format!("参数无效: {}", e)))
+}
+
+fn to_result<T: Serialize>(result: Result<T, String>) -> Result<Value, RpcError> {
+    let value = result.map_err(RpcError::command)?;
+    serde_json::to_value(value).map_err(|e| RpcError::new(-32603, format!("序列化结果失败: {}", e)))
+}
+
+// 按参数名解析参数并调用 Tauri 命令，第一个参数为 app 的命令传入 AppHandle
+macro_rules! call {
+    ($params:ident, $app:ident, $command:path, app $(, $arg:ident : $ty:ty)*) => {{
+        #[derive(Deserialize)]
+        struct Params { $($arg: $ty),* }
+        let Params { $($arg),* } = parse_params::<Params>($params)?;
+        to_result(block_on($command($app.clone() $(, $arg)*)))
+    }};
+    ($params:ident, $app:ident, $command:path $(, $arg:ident : $ty:ty)*) => {{
+        #[derive(Deserialize)]
+        struct Params { $($arg: $ty),* }
+        let Params { $($arg),* } = parse_params::<Params>($params)?;
+        to_result(block_on($command($($arg),*)))
+    }};
+}
+
+// 调用与 Tauri 命令同名的方法
+fn dispatch(app: &tauri::AppHandle, method: &str, params: Value) -> Result<Value, RpcError> {
+    match method {
+        // 数据相关命令
+        "save_todo_data" => call!(params, app, data::save_todo_data, app, pending_todos: Vec<Todo>, completed_todos: Vec<Todo>),
+        "load_todo_data" => call!(params, app, data::load_todo_data, app),
+        "set_todo_deadline" => call!(params, app, data::set_todo_deadline, app, todo_id: String, is_completed: bool, deadline: Option<i64>),
+        "update_todo_text" => call!(params, app, data::update_todo_text, app, todo_id: String, is_completed: bool, new_text: String),
+        // 整体读取和保存不经过前端使用的命令，避免改变前端的保存基准；保存的列表即为完整数据
+        "save_todo_data_with_groups" => {
+            #[derive(Deserialize)]
+            struct Params { todos: Vec<Value> }
+            let Params { todos } = parse_params::<Params>(params)?;
+            to_result(ensure_unlocked()
+                .and_then(|_| {
+                    // 调用方没有传入的字段沿用磁盘上的值
+                    let data_dir = get_data_dir(app)?;
+                    let todos = fill_missing_fields(todos, &read_todo_store(&data_dir)?.todos)?;
+                    save_todo_store(&data_dir, todos, None)
+                })
+                .map(|_| { let _ = app.emit("todo-data-changed", ()); }))
+        }
+        "load_todo_data_with_groups" => to_result(ensure_unlocked().and_then(|_| read_todo_store(&get_data_dir(app)?))),
+        "save_group_data" => {
+            #[derive(Deserialize)]
+            struct Params { groups: Vec<Value> }
+            let Params { groups } = parse_params::<Params>(params)?;
+            to_result(ensure_unlocked()
+                .and_then(|_| {
+                    // 调用方没有传入的字段沿用磁盘上的值
+                    let data_dir = get_data_dir(app)?;
+                    let groups = fill_missing_fields(groups, &read_group_store(&data_dir)?.groups)?;
+                    save_group_store(&data_dir, groups, None)
+                })
+                .map(|_| { let _ = app.emit("group-data-changed", ()); }))
+        }
+        "load_group_data" => to_result(ensure_unlocked().and_then(|_| read_group_store(&get_data_dir(app)?))),
+        "add_todo" => call!(params, app, data::add_todo, app, text: String, group_id: Option<String>, priority: Option<i32>, deadline: Option<i64>, tags: Option<Vec<String>>),
+        "update_todo" => call!(params, app, data::update_todo, app, todo_id: String, patch: TodoPatch),
+        "set_todo_completed" => call!(params, app, data::set_todo_completed, app, todo_id: String, completed: bool),
+        "delete_todo" => call!(params, app, data::delete_todo, app, todo_id: String),
+        "export_todos" => call!(params, app, data::export_todos, app, plaintext: Option<bool>),
+        "save_app_settings" => call!(params, app, data::save_app_settings, app, settings: Value),
+        "load_app_settings" => to_result(block_on(data::load_app_settings(app.clone())).map(redact_settings)),
+        "apply_opacity" => call!(params, app, data::apply_opacity, app, opacity: f64),
+        "save_window_position" => call!(params, app, data::save_window_position, app, x: i32, y: i32),
+        "load_window_position" => call!(params, app, data::load_window_position, app),
+        "archive_completed_todos" => call!(params, app, data::archive::archive_completed_todos, app),
+        "list_archive_months" => call!(params, app, data::archive::list_archive_months, app),
+        "search_archive" => call!(params, app, data::archive::search_archive, app, query: String, month: Option<String>, group_id: Option<String>),
+        "export_archive" => call!(params, app, data::archive::export_archive, app, file_path: String, month: Option<String>, plaintext: Option<bool>, group_id: Option<String>),
+        "restore_archived_todos" => call!(params, app, data::archive::restore_archived_todos, app, todo_ids: Vec<String>),
+        "create_group" => call!(params, app, data::groups::create_group, app, name: String, parent_id: Option<String>),
+        "rename_group" => call!(params, app, data::groups::rename_group, app, group_id: String, name: String),
+        "set_group_metadata" => call!(params, app, data::groups::set_group_metadata, app, group_id: String, color: Option<String>, icon: Option<String>, default_priority: i32, default_deadline_offset: Option<String>),
+        "move_group" => call!(params, app, data::groups::move_group, app, group_id: String, parent_id: Option<String>),
+        "delete_group" => call!(params, app, data::groups::delete_group, app, group_id: String, strategy: GroupDeleteStrategy),
+        "reorder_groups" => call!(params, app, data::groups::reorder_groups, app, group_ids: Vec<String>),
+        "set_group_collapsed" => call!(params, app, data::groups::set_group_collapsed, app, group_id: String, collapsed: bool),
+        "get_group_counts" => call!(params, app, data::groups::get_group_counts, app),
+        "check_data_integrity" => call!(params, app, data::integrity::check_data_integrity, app, fix: bool),
+        "regenerate_api_token" => call!(params, app, super::regenerate_api_token, app),
+        "get_todo_history" => call!(params, app, data::history::get_todo_history, app, todo_id: String),
+        "restore_data_from_history" => call!(params, app, data::history::restore_data_from_history, app, commit: String),
+        "get_encryption_status" => call!(params, app, data::encryption::get_encryption_status),
+        "enable_encryption" => call!(params, app, data::encryption::enable_encryption, app, passphrase: String),
+        "unlock_data" => call!(params, app, data::encryption::unlock_data, app, passphrase: String),
+        "change_encryption_passphrase" => call!(params, app, data::encryption::change_encryption_passphrase, app, old_passphrase: String, new_passphrase: String),
+        "disable_encryption" => call!(params, app, data::encryption::disable_encryption, app, passphrase: String),
+        "merge_sync_conflicts" => call!(params, app, sync::folder::merge_sync_conflicts, app),
+        "sync_webdav_now" => call!(params, app, sync::webdav::sync_webdav_now, app),
+        "get_webdav_sync_status" => call!(params, app, sync::webdav::get_webdav_sync_status),
+        "sync_caldav_now" => call!(params, app, sync::caldav::sync_caldav_now, app),
+        "get_caldav_sync_status" => call!(params, app, sync::caldav::get_caldav_sync_status),
+
+        // 窗口管理命令
+        "toggle_main_window" => call!(params, app, window::management::toggle_main_window, app),
+        "show_main_window" => call!(params, app, window::management::show_main_window, app),
+        "minimize_to_tray" => call!(params, app, window::management::minimize_to_tray, app),
+        "restore_from_tray" => call!(params, app, window::management::restore_from_tray, app),
+        "open_settings_window" => call!(params, app, window::management::open_settings_window, app),
+        "close_settings_window" => call!(params, app, window::management::close_settings_window, app),
+        "reset_window_position" => call!(params, app, window::management::reset_window_position, app),
+        "open_quick_add_window" => call!(params, app, window::quick_add::open_quick_add_window, app),
+        "close_quick_add_window" => call!(params, app, window::quick_add::close_quick_add_window, app),
+        "get_quick_add_groups" => call!(params, app, window::quick_add::get_quick_add_groups, app),
+        "submit_quick_add" => call!(params, app, window::quick_add::submit_quick_add, app, text: String, group_id: Option<String>),
+
+        // 系统相关命令
+        "get_current_date" => call!(params, app, system::date_info::get_current_date),
+        "get_date_info" => call!(params, app, system::date_info::get_date_info, date: String),
+        "is_workday" => call!(params, app, data::holidays::is_workday, date: String),
+        "next_workday" => call!(params, app, data::holidays::next_workday, date: String),
+        "import_holiday_calendar" => call!(params, app, data::holidays::import_holiday_calendar, app, path: String),
+        "clear_holiday_overrides" => call!(params, app, data::holidays::clear_holiday_overrides, app),
+        "get_app_lock_status" => call!(params, app, system::app_lock::get_app_lock_status, app),
+        "lock_app" => call!(params, app, system::app_lock::lock_app, app),
+        "unlock_app" => call!(params, app, system::app_lock::unlock_app, app, pin: String),
+        "set_app_lock_pin" => call!(params, app, system::app_lock::set_app_lock_pin, app, current_pin: Option<String>, new_pin: Option<String>),
+        "get_pending_deep_link_adds" => call!(params, app, system::deep_link::get_pending_deep_link_adds),
+        "confirm_deep_link_add" => call!(params, app, system::deep_link::confirm_deep_link_add, app, request_id: String, confirmed: bool),
+        "get_app_version" => call!(params, app, crate::get_app_version, app),
+        "is_dev_mode" => call!(params, app, crate::is_dev_mode),
+        "quit_app" => call!(params, app, crate::quit_app, app),
+        "emit_theme_changed" => call!(params, app, crate::emit_theme_changed, app, theme: String),
+        "emit_priority_color_changed" => call!(params, app, crate::emit_priority_color_changed, app, color: String),
+        "test_notification" => call!(params, app, crate::test_notification, app),
+
+        // 统计相关命令
+        "get_statistics" => call!(params, app, stats::get_statistics, app, range: String, group_id: Option<String>),
+
+        // 日历相关命令
+        "get_calendar_month" => call!(params, app, calendar::get_calendar_month, app, year: i32, month: u32, group_id: Option<String>),
+        "get_timeline" => call!(params, app, calendar::timeline::get_timeline, app, range: Option<TimelineRange>, options: Option<TimelineOptions>),
+
+        _ => Err(RpcError::new(-32601, format!("未知的方法: {}", method))),
+    }
+}
+
+// 一个连接的状态：发送端和订阅
+struct Connection {
+    app: tauri::AppHandle,
+    writer: Arc<Mutex<SendHalf>>,
+    subscriptions: HashMap<u64, Vec<EventId>>,
+}
+
+fn write_message(writer: &Mutex<SendHalf>, message: &Value) -> io::Result<()> {
+    let mut line = message.to_string();
+    line.push('\n');
+    let mut writer = writer.lock().map_err(|_| io::Error::other("连接已失效"))?;
+    writer.write_all(line.as_bytes())
+}
+
+impl Connection {
+    // 订阅事件，事件发生时发送 method 为 "event" 的通知
+    fn subscribe(&mut self, params: Value) -> Result<Value, RpcError> {
+        #[derive(Deserialize)]
+        struct Params { events: Option<Vec<String>> }
+        let Params { events } = parse_params::<Params>(params)?;
+        let events = events.unwrap_or_else(|| DEFAULT_EVENTS.iter().map(|event| event.to_string()).collect());
+        if events.is_empty() {
+            return Err(RpcError::invalid_params("events 不能为空"));
+        }
+
+        let subscription = NEXT_SUBSCRIPTION_ID.fetch_add(1, Ordering::SeqCst);
+        let listeners = events.into_iter()
+            .map(|event| {
+                let writer = self.writer.clone();
+                let name = event.clone();
+                self.app.listen(event, move |event| {
+                    let payload = serde_json::from_str(event.payload()).unwrap_or(Value::Null);
+                    let _ = write_message(&writer, &json!({
+                        "jsonrpc": "2.0",
+                        "method": "event",
+                        "params": { "subscription": subscription, "event": name, "payload": payload },
+                    }));
+                })
+            })
+            .collect();
+        self.subscriptions.insert(subscription, listeners);
+        Ok(json!(subscription))
+    }
+
+    fn unsubscribe(&mut self, params: Value) -> Result<Value, RpcError> {
+        #[derive(Deserialize)]
+        struct Params { subscription: u64 }
+        let Params { subscription } = parse_params::<Params>(params)?;
+        let listeners = self.subscriptions.remove(&subscription).unwrap_or_default();
+        let found = !listeners.is_empty();
+        for listener in listeners {
+            self.app.unlisten(listener);
+        }
+        Ok(json!(found))
+    }
+
+    // 处理单个请求，通知（没有 id 的请求）不返回响应
+    fn handle_call(&mut self, call: Value) -> Option<Value> {
+        let id = call.get("id").cloned();
+        let result = match (call.get("jsonrpc").and_then(Value::as_str), call.get("method").and_then(Value::as_str)) {
+            (Some("2.0"), Some(method)) => {
+                let params = call.get("params").cloned().unwrap_or(Value::Null);
+                match method {
+                    "subscribe" => self.subscribe(params),
+                    "unsubscribe" => self.unsubscribe(params),
+                    _ => dispatch(&self.app, method, params),
+                }
+            }
+            _ => Err(RpcError::new(-32600, "无效的JSON-RPC请求")),
+        };
+
+        let id = id?;
+        Some(match result {
+            Ok(result) => json!({ "jsonrpc": "2.0", "result": result, "id": id }),
+            Err(e) => json!({ "jsonrpc": "2.0", "error": { "code": e.code, "message": e.message }, "id": id }),
+        })
+    }
+
+    // 处理一行消息（单个请求或批量请求）
+    fn handle_message(&mut self, message: &str) -> Option<Value> {
+        let error = |code: i64, message: &str| Some(json!({
+            "jsonrpc": "2.0",
+            "error": { "code": code, "message": message },
+            "id": null,
+        }));
+
+        match serde_json::from_str::<Value>(message) {
+            Ok(Value::Array(calls)) if calls.is_empty() => error(-32600, "批量请求不能为空"),
+            Ok(Value::Array(calls)) => {
+                let responses: Vec<Value> = calls.into_iter()
+                    .filter_map(|call| self.handle_call(call))
+                    .collect();
+                (!responses.is_empty()).then_some(Value::Array(responses))
+            }
+            Ok(call) => self.handle_call(call),
+            Err(e) => error(-32700, &format!("解析JSON失败: {}", e)),
+        }
+    }
+}
+
+// 处理一个连接：每行一条JSON-RPC消息，响应和事件通知同样每行一条
+fn handle_connection(app: tauri::AppHandle, stream: LocalSocketStream) {
+    let (reader, writer) = stream.split();
+    let mut connection = Connection {
+        app,
+        writer: Arc::new(Mutex::new(writer)),
+        subscriptions: HashMap::new(),
+    };
+
+    let mut reader = BufReader::new(reader);
+    let mut line = String::new();
+    loop {
+        line.clear();
+        match reader.read_line(&mut line) {
+            Ok(0) | Err(_) => break,
+            Ok(_) => {}
+        }
+        if line.trim().is_empty() {
+            continue;
+        }
+        if let Some(response) = connection.handle_message(line.trim()) {
+            if write_message(&connection.writer, &response).is_err() {
+                break;
+            }
+        }
+    }
+
+    // 连接断开后取消订阅
+    for listener in connection.subscriptions.into_values().flatten() {
+        connection.app.unlisten(listener);
+    }
+}
+
+// 创建监听，名称已被占用（例如上次异常退出留下的套接字文件）时接管
+fn create_listener(name: Name<'static>, nonblocking: ListenerNonblockingMode) -> io::Result<LocalSocketListener> {
+    let options = ListenerOptions::new()
+        .name(name)
+        .nonblocking(nonblocking)
+        .try_overwrite(true);
+    #[cfg(unix)]
+    let options = {
+        use interprocess::os::unix::local_socket::ListenerOptionsExt;
+        // 只允许当前用户连接
+        options.mode(0o600)
+    };
+    options.create_sync()
+}
+
+// 应用运行期间一直监听实例标记，命令行据此判断应用是否正在运行，
+// 避免在控制接口停用时绕过应用直接写数据文件
+pub fn hold_instance_marker() {
+    std::thread::spawn(|| {
+        let listener = match instance_socket_name().and_then(|name| create_listener(name, ListenerNonblockingMode::Neither)) {
+            Ok(listener) => listener,
+            Err(e) => {
+                log::error!("创建实例标记失败: {}", e);
+                return;
+            }
+        };
+        // 连接只用于探测，接受后立即断开
+        for stream in listener.incoming() {
+            drop(stream);
+        }
+    });
+}
+
+// 是否有正在运行的应用实例
+pub fn instance_running() -> bool {
+    instance_socket_name()
+        .and_then(LocalSocketStream::connect)
+        .is_ok()
+}
+
+fn rpc_enabled(app: &tauri::AppHandle) -> bool {
+    block_on(crate::data::load_app_settings(app.clone()))
+        .is_ok_and(|settings| settings.rpc_enabled)
+}
+
+// 启动本地控制接口，按设置启用或停用
+pub fn start_rpc_server(app: tauri::AppHandle) {
+    std::thread::spawn(move || {
+        let mut listener: Option<LocalSocketListener> = None;
+        loop {
+            let enabled = rpc_enabled(&app);
+            if !enabled && listener.take().is_some() {
+                log::info!("本地控制接口已停止");
+            }
+            if enabled && listener.is_none() {
+                match socket_name().and_then(|name| create_listener(name, ListenerNonblockingMode::Accept)) {
+                    Ok(new_listener) => {
+                        log::info!("本地控制接口已启动");
+                        listener = Some(new_listener);
+                    }
+                    Err(e) => log::error!("启动本地控制接口失败: {}", e),
+                }
+            }
+
+            let Some(active) = &listener else {
+                std::thread::sleep(SETTINGS_CHECK_INTERVAL);
+                continue;
+            };
+
+            // 接受连接，直到需要重新检查设置
+            let deadline = Instant::now() + SETTINGS_CHECK_INTERVAL;
+            while Instant::now() < deadline {
+                match active.accept() {
+                    Ok(stream) => match peer_is_current_user(&stream) {
+                        Ok(true) => {
+                            let app = app.clone();
+                            std::thread::spawn(move || handle_connection(app, stream));
+                        }
+                        Ok(false) => log::warn!("拒绝了其他用户的本地控制接口连接"),
+                        Err(e) => log::error!("检查本地控制接口连接方失败: {}", e),
+                    },
+                    Err(e) if e.kind() == ErrorKind::WouldBlock => std::thread::sleep(ACCEPT_POLL_INTERVAL),
+                    Err(e) => {
+                        log::error!("接受本地控制接口连接失败: {}", e);
+                        std::thread::sleep(ACCEPT_POLL_INTERVAL);
+                    }
+                }
+            }
+        }
+    });
+}
@@ -0,0 +1,132 @@
+pub mod timeline;
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, Datelike, Local, NaiveDate, TimeZone};
+
+use crate::data::holidays::{day_kind, DayKind};
+use crate::models::{CalendarDay, CalendarMonth, Todo};
+use crate::system::app_lock::ensure_unlocked;
+use crate::system::festivals::festivals_on;
+use crate::system::lunar::LunarDate;
+use crate::system::solar_terms::solar_terms_of_year;
+
+// 将Unix时间戳（秒）转换为本地日期
+pub(crate) fn local_date(timestamp: i64) -> Option<NaiveDate> {
+    Local.timestamp_opt(timestamp, 0)
+        .single()
+        .map(|dt| dt.date_naive())
+}
+
+// 日历格子中显示的农历文字：节日 > 节气 > 农历初一显示月份 > 农历日期
+fn lunar_label(lunar: Option<&LunarDate>, festivals: &[String], solar_term: Option<&String>) -> String {
+    if let Some(festival) = festivals.first() {
+        return festival.clone();
+    }
+    if let Some(solar_term) = solar_term {
+        return solar_term.clone();
+    }
+    match lunar {
+        Some(lunar) if lunar.day == 1 => lunar.month_name(),
+        Some(lunar) => lunar.day_name(),
+        None => String::new(),
+    }
+}
+
+// 计算某个月的日历数据
+// 未完成的任务：deadline_priority 为 true 且有截止时间时放在截止日期（due），否则放在创建日期（scheduled），与时间轴视图一致
+// 已完成的任务放在完成日期（completed）
+pub fn compute_calendar_month(
+    todos: &[Todo],
+    year: i32,
+    month: u32,
+    deadline_priority: bool,
+    now: DateTime<Local>,
+) -> Result<CalendarMonth, String> {
+    let first = NaiveDate::from_ymd_opt(year, month, 1)
+        .ok_or_else(|| format!("无效的月份: {}年{}月", year, month))?;
+    let today = now.date_naive();
+    let solar_terms = solar_terms_of_year(year);
+
+    let mut days: Vec<CalendarDay> = first.iter_days()
+        .take_while(|date| date.month() == month)
+        .map(|date| {
+            let lunar = LunarDate::from_solar(date);
+            let festivals: Vec<String> = festivals_on(date).into_iter().map(String::from).collect();
+            let solar_term = solar_terms.iter()
+                .find(|(term_date, _)| *term_date == date)
+                .map(|(_, name)| name.to_string());
+            let kind = day_kind(date);
+            let holiday_name = match &kind {
+                DayKind::Holiday(name) | DayKind::AdjustedWorkday(name) => Some(name.clone()),
+                DayKind::Workday | DayKind::Weekend => None,
+            };
+
+            CalendarDay {
+                date: date.format("%Y-%m-%d").to_string(),
+                day: date.day(),
+                weekday: date.weekday().num_days_from_monday(),
+                is_today: date == today,
+                lunar_month: lunar.map(|lunar| lunar.month_name()).unwrap_or_default(),
+                lunar_day: lunar.map(|lunar| lunar.day_name()).unwrap_or_default(),
+                lunar_label: lunar_label(lunar.as_ref(), &festivals, solar_term.as_ref()),
+                festivals,
+                solar_term,
+                is_workday: kind.is_workday(),
+                is_holiday: matches!(kind, DayKind::Holiday(_)),
+                is_adjusted_workday: matches!(kind, DayKind::AdjustedWorkday(_)),
+                holiday_name,
+                due: Vec::new(),
+                scheduled: Vec::new(),
+                completed: Vec::new(),
+            }
+        })
+        .collect();
+
+    // 日期在 days 中的位置
+    let index: HashMap<NaiveDate, usize> = first.iter_days()
+        .take_while(|date| date.month() == month)
+        .enumerate()
+        .map(|(index, date)| (date, index))
+        .collect();
+
+    for todo in todos {
+        if todo.completed {
+            let Some(day) = todo.completed_at.and_then(local_date).and_then(|date| index.get(&date)) else { continue };
+            days[*day].completed.push(todo.clone());
+        } else if let Some(deadline) = todo.deadline.filter(|_| deadline_priority) {
+            let Some(day) = local_date(deadline).and_then(|date| index.get(&date)) else { continue };
+            days[*day].due.push(todo.clone());
+        } else {
+            let Some(day) = local_date(todo.created_at).and_then(|date| index.get(&date)) else { continue };
+            days[*day].scheduled.push(todo.clone());
+        }
+    }
+
+    for day in &mut days {
+        day.due.sort_by_key(|todo| (todo.deadline, -todo.priority));
+        day.scheduled.sort_by_key(|todo| (todo.created_at, -todo.priority));
+        day.completed.sort_by_key(|todo| todo.completed_at);
+    }
+
+    Ok(CalendarMonth { year, month, deadline_priority, days })
+}
+
+// Tauri 命令：获取日历视图某个月（month 为 1-12）的数据，指定 group_id 时只包含该分组及其下级分组的任务
+#[tauri::command]
+pub async fn get_calendar_month(
+    app: tauri::AppHandle,
+    year: i32,
+    month: u32,
+    group_id: Option<String>,
+) -> Result<CalendarMonth, String> {
+    ensure_unlocked()?;
+    let data_dir = crate::data::todo_data::get_data_dir(&app)?;
+    let mut todo_data = crate::data::todo_data::read_todo_store(&data_dir)?;
+    if let Some(group_ids) = crate::data::groups::group_filter(&data_dir, group_id.as_deref())? {
+        todo_data.todos.retain(|todo| group_ids.contains(&todo.group_id));
+    }
+    let settings = crate::data::app_settings::read_app_settings(&data_dir);
+
+    compute_calendar_month(&todo_data.todos, year, month, settings.timeline_deadline_priority, Local::now())
+}
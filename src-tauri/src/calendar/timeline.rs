@@ -0,0 +1,267 @@
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Datelike, Local, NaiveDate};
+
+use crate::models::{Timeline, TimelineBucket, TimelineItem, TimelineLocale, TimelineOptions, TimelineRange, TimelineStatus, Todo};
+use crate::system::app_lock::ensure_unlocked;
+use super::local_date;
+
+const WEEKDAY_NAMES: [&str; 7] = ["周一", "周二", "周三", "周四", "周五", "周六", "周日"];
+const WEEKDAY_NAMES_EN: [&str; 7] = ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"];
+
+fn parse_range_date(date: Option<&str>) -> Result<Option<NaiveDate>, String> {
+    date.map(|date| {
+        NaiveDate::parse_from_str(date.trim(), "%Y-%m-%d")
+            .map_err(|e| format!("无效的日期 {}，格式应为 YYYY-MM-DD: {}", date, e))
+    }).transpose()
+}
+
+// 日期分组的名称：前天到后天（英文为昨天到明天）用相对说法，今年的日期省略年份
+fn day_label(date: NaiveDate, today: NaiveDate, locale: TimelineLocale) -> String {
+    let days = (date - today).num_days();
+    match locale {
+        TimelineLocale::ZhCn => match days {
+            -2 => "前天".to_string(),
+            -1 => "昨天".to_string(),
+            0 => "今天".to_string(),
+            1 => "明天".to_string(),
+            2 => "后天".to_string(),
+            _ if date.year() == today.year() => format!("{}月{}日", date.month(), date.day()),
+            _ => format!("{}年{}月{}日", date.year(), date.month(), date.day()),
+        },
+        TimelineLocale::En => match days {
+            -1 => "Yesterday".to_string(),
+            0 => "Today".to_string(),
+            1 => "Tomorrow".to_string(),
+            _ if date.year() == today.year() => date.format("%b %-d").to_string(),
+            _ => date.format("%b %-d, %Y").to_string(),
+        },
+    }
+}
+
+fn weekday_name(date: NaiveDate, locale: TimelineLocale) -> String {
+    let names = match locale {
+        TimelineLocale::ZhCn => WEEKDAY_NAMES,
+        TimelineLocale::En => WEEKDAY_NAMES_EN,
+    };
+    names[date.weekday().num_days_from_monday() as usize].to_string()
+}
+
+// "已逾期"和"已完成"分组的名称
+fn status_label(status: TimelineStatus, locale: TimelineLocale) -> String {
+    let label = match (status, locale) {
+        (TimelineStatus::Overdue, TimelineLocale::ZhCn) => "已逾期",
+        (TimelineStatus::Overdue, TimelineLocale::En) => "Overdue",
+        (_, TimelineLocale::ZhCn) => "已完成",
+        (_, TimelineLocale::En) => "Completed",
+    };
+    label.to_string()
+}
+
+fn item(todo: &Todo, display_time: i64, status: TimelineStatus) -> TimelineItem {
+    TimelineItem { todo: todo.clone(), display_time, status }
+}
+
+// 计算时间轴
+// 未完成的任务：deadline_priority 为 true 且有截止时间时按截止时间排列，否则按创建时间，与日历视图一致
+// 已过截止时间的未完成任务放在最前面的"已逾期"分组中，不受日期范围限制（与截止提醒使用同一判断）
+// 已完成的任务按完成时间放在最后的"已完成"分组中，最近完成的在前
+pub fn compute_timeline(
+    todos: &[Todo],
+    range: &TimelineRange,
+    include_completed: bool,
+    deadline_priority: bool,
+    locale: TimelineLocale,
+    now: DateTime<Local>,
+) -> Result<Timeline, String> {
+    let start = parse_range_date(range.start.as_deref())?;
+    let end = parse_range_date(range.end.as_deref())?;
+    let in_range = |timestamp: i64| {
+        local_date(timestamp).is_some_and(|date| {
+            start.map_or(true, |start| date >= start) && end.map_or(true, |end| date <= end)
+        })
+    };
+    let today = now.date_naive();
+    let now = now.timestamp();
+
+    let mut overdue = Vec::new();
+    let mut days: BTreeMap<NaiveDate, Vec<TimelineItem>> = BTreeMap::new();
+    let mut completed = Vec::new();
+    for todo in todos {
+        if todo.completed {
+            let completed_at = todo.completed_at.unwrap_or(todo.created_at);
+            if include_completed && in_range(completed_at) {
+                completed.push(item(todo, completed_at, TimelineStatus::Completed));
+            }
+            continue;
+        }
+
+        if let Some(deadline) = todo.deadline.filter(|deadline| *deadline < now) {
+            overdue.push(item(todo, deadline, TimelineStatus::Overdue));
+            continue;
+        }
+        let display_time = match todo.deadline {
+            Some(deadline) if deadline_priority => deadline,
+            _ => todo.created_at,
+        };
+        if let Some(date) = local_date(display_time).filter(|_| in_range(display_time)) {
+            days.entry(date).or_default().push(item(todo, display_time, TimelineStatus::Pending));
+        }
+    }
+
+    let pending_count = days.values().map(|items| items.len() as u32).sum();
+    let overdue_count = overdue.len() as u32;
+    let completed_count = completed.len() as u32;
+
+    let mut buckets = Vec::new();
+    if !overdue.is_empty() {
+        overdue.sort_by_key(|item| (item.display_time, -item.todo.priority));
+        buckets.push(TimelineBucket { key: "overdue".to_string(), label: status_label(TimelineStatus::Overdue, locale), weekday: None, items: overdue });
+    }
+    for (date, mut items) in days {
+        items.sort_by_key(|item| (item.display_time, -item.todo.priority));
+        buckets.push(TimelineBucket {
+            key: date.format("%Y-%m-%d").to_string(),
+            label: day_label(date, today, locale),
+            weekday: Some(weekday_name(date, locale)),
+            items,
+        });
+    }
+    if !completed.is_empty() {
+        completed.sort_by_key(|item| std::cmp::Reverse(item.display_time));
+        buckets.push(TimelineBucket { key: "completed".to_string(), label: status_label(TimelineStatus::Completed, locale), weekday: None, items: completed });
+    }
+
+    Ok(Timeline { deadline_priority, buckets, pending_count, overdue_count, completed_count })
+}
+
+// Tauri 命令：获取分好组的时间轴数据
+#[tauri::command]
+pub async fn get_timeline(
+    app: tauri::AppHandle,
+    range: Option<TimelineRange>,
+    options: Option<TimelineOptions>,
+) -> Result<Timeline, String> {
+    ensure_unlocked()?;
+    let range = range.unwrap_or_default();
+    let options = options.unwrap_or_default();
+    let data_dir = crate::data::todo_data::get_data_dir(&app)?;
+    let mut todo_data = crate::data::todo_data::read_todo_store(&data_dir)?;
+    if let Some(group_ids) = crate::data::groups::group_filter(&data_dir, options.group_id.as_deref())? {
+        todo_data.todos.retain(|todo| group_ids.contains(&todo.group_id));
+    }
+    let deadline_priority = options.deadline_priority
+        .unwrap_or_else(|| crate::data::app_settings::read_app_settings(&data_dir).timeline_deadline_priority);
+
+    compute_timeline(&todo_data.todos, &range, options.include_completed, deadline_priority, options.locale, Local::now())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use crate::models::test_todo;
+
+    // 2024-09-04 是星期三
+    fn now() -> DateTime<Local> {
+        Local.with_ymd_and_hms(2024, 9, 4, 12, 0, 0).unwrap()
+    }
+
+    fn at(month: u32, day: u32, hour: u32) -> i64 {
+        Local.with_ymd_and_hms(2024, month, day, hour, 0, 0).unwrap().timestamp()
+    }
+
+    fn todos() -> Vec<Todo> {
+        vec![
+            test_todo("overdue", at(8, 1, 9), Some(at(9, 3, 18)), None),
+            test_todo("due-later", at(9, 2, 9), Some(at(9, 6, 18)), None),
+            test_todo("no-deadline", at(9, 4, 9), None, None),
+            test_todo("done", at(8, 20, 9), None, Some(at(9, 3, 10))),
+            test_todo("done-earlier", at(8, 20, 9), Some(at(9, 1, 10)), Some(at(8, 30, 10))),
+        ]
+    }
+
+    fn range(start: Option<&str>, end: Option<&str>) -> TimelineRange {
+        TimelineRange { start: start.map(str::to_string), end: end.map(str::to_string) }
+    }
+
+    // 每个分组的 key 和其中任务的ID
+    fn layout(timeline: &Timeline) -> Vec<(String, Vec<String>)> {
+        timeline.buckets.iter()
+            .map(|bucket| (bucket.key.clone(), bucket.items.iter().map(|item| item.todo.id.clone()).collect()))
+            .collect()
+    }
+
+    fn bucket(key: &str, ids: &[&str]) -> (String, Vec<String>) {
+        (key.to_string(), ids.iter().map(|id| id.to_string()).collect())
+    }
+
+    #[test]
+    fn classifies_overdue_pending_and_completed() {
+        let timeline = compute_timeline(&todos(), &range(None, None), true, true, TimelineLocale::ZhCn, now()).unwrap();
+        assert_eq!(layout(&timeline), vec![
+            bucket("overdue", &["overdue"]),
+            bucket("2024-09-04", &["no-deadline"]),
+            bucket("2024-09-06", &["due-later"]),
+            bucket("completed", &["done", "done-earlier"]),
+        ]);
+        assert_eq!((timeline.pending_count, timeline.overdue_count, timeline.completed_count), (2, 1, 2));
+        assert!(timeline.buckets[0].items.iter().all(|item| item.status == TimelineStatus::Overdue));
+        assert!(timeline.buckets[3].items.iter().all(|item| item.status == TimelineStatus::Completed));
+
+        let labels: Vec<(&str, Option<&str>)> = timeline.buckets.iter()
+            .map(|bucket| (bucket.label.as_str(), bucket.weekday.as_deref()))
+            .collect();
+        assert_eq!(labels, vec![("已逾期", None), ("今天", Some("周三")), ("后天", Some("周五")), ("已完成", None)]);
+
+        let timeline = compute_timeline(&todos(), &range(None, None), false, true, TimelineLocale::ZhCn, now()).unwrap();
+        assert_eq!(timeline.completed_count, 0);
+        assert!(timeline.buckets.iter().all(|bucket| bucket.key != "completed"));
+    }
+
+    #[test]
+    fn deadline_priority_switches_the_display_time() {
+        let timeline = compute_timeline(&todos(), &range(None, None), false, false, TimelineLocale::ZhCn, now()).unwrap();
+        assert_eq!(layout(&timeline), vec![
+            bucket("overdue", &["overdue"]),
+            bucket("2024-09-02", &["due-later"]),
+            bucket("2024-09-04", &["no-deadline"]),
+        ]);
+        assert_eq!(timeline.buckets[1].items[0].display_time, at(9, 2, 9));
+        assert!(!timeline.deadline_priority);
+    }
+
+    #[test]
+    fn range_bounds_are_inclusive_and_skip_overdue() {
+        let timeline = compute_timeline(&todos(), &range(Some("2024-09-04"), Some("2024-09-05")), true, true, TimelineLocale::ZhCn, now()).unwrap();
+        assert_eq!(layout(&timeline), vec![
+            bucket("overdue", &["overdue"]),
+            bucket("2024-09-04", &["no-deadline"]),
+        ]);
+
+        let timeline = compute_timeline(&todos(), &range(Some("2024-09-03"), Some("2024-09-06")), true, true, TimelineLocale::ZhCn, now()).unwrap();
+        assert_eq!(layout(&timeline), vec![
+            bucket("overdue", &["overdue"]),
+            bucket("2024-09-04", &["no-deadline"]),
+            bucket("2024-09-06", &["due-later"]),
+            bucket("completed", &["done"]),
+        ]);
+
+        assert!(compute_timeline(&todos(), &range(Some("2024/09/03"), None), true, true, TimelineLocale::ZhCn, now()).is_err());
+    }
+
+    #[test]
+    fn english_labels() {
+        let timeline = compute_timeline(&todos(), &range(None, None), true, true, TimelineLocale::En, now()).unwrap();
+        let labels: Vec<(&str, Option<&str>)> = timeline.buckets.iter()
+            .map(|bucket| (bucket.label.as_str(), bucket.weekday.as_deref()))
+            .collect();
+        assert_eq!(labels, vec![("Overdue", None), ("Today", Some("Wed")), ("Sep 6", Some("Fri")), ("Completed", None)]);
+
+        let today = now().date_naive();
+        let next_year = NaiveDate::from_ymd_opt(2025, 1, 3).unwrap();
+        assert_eq!(day_label(next_year, today, TimelineLocale::En), "Jan 3, 2025");
+        assert_eq!(day_label(next_year, today, TimelineLocale::ZhCn), "2025年1月3日");
+        assert_eq!(day_label(today.pred_opt().unwrap(), today, TimelineLocale::En), "Yesterday");
+    }
+}
@@ -0,0 +1,434 @@
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use chrono::{Local, TimeZone};
+use interprocess::local_socket::prelude::*;
+use serde::de::DeserializeOwned;
+use serde_json::{json, Value};
+use tauri::async_runtime::block_on;
+
+use crate::data::groups::{parse_deadline, resolve_group, subtree_ids};
+use crate::utils::task_parser::parse_task;
+use crate::data::todo_data::{
+    add_todo_to_store, export_store, get_data_dir, read_group_store, read_todo_store, set_todo_completed_in_store, DEFAULT_GROUP_ID,
+};
+use crate::data::{add_todo, export_todos, set_todo_completed};
+use crate::models::{GroupData, Todo, TodoDataWithGroups, TodoGroup};
+use crate::system::app_lock::ensure_unlocked;
+
+const USAGE: &str = "用法:
+  deskhive add <内容> [--due <截止时间>] [--group <分组>] [--priority high|normal]
+  deskhive list [--json] [--all] [--group <分组>]
+  deskhive done <任务ID>
+  deskhive export [--output <文件>] [--plaintext]
+  deskhive [--settings | --toggle | --hide]   启动应用，应用已运行时打开设置、切换或隐藏主窗口
+
+启用数据加密时导出的内容同样加密，指定 --plaintext 时导出明文
+DeskHive 正在运行但未启用本地控制接口时，add 和 done 命令转发给应用执行，结果记录在应用日志中
+
+截止时间可以是 \"tomorrow 17:00\"、\"今天 18:00\"、\"2024-09-01\"、\"+2d\"、\"+3h\" 或 \"17:00\"
+分组可以是分组名称或ID，任务ID可以只写开头几位
+内容中也可以直接写时间、#分组、#标签和 !，例如 deskhive add \"明天下午3点 交周报 #工作 !\"";
+
+// 命令行参数有误
+struct UsageError(String);
+
+// 解析后的命令
+enum Command {
+    Add { text: String, due: Option<String>, group: Option<String>, priority: Option<String> },
+    List { json: bool, all: bool, group: Option<String> },
+    Done { id: String },
+    Export { output: Option<PathBuf>, plaintext: bool },
+    Help,
+}
+
+impl Command {
+    // 是否修改数据，应用运行时修改数据的命令不能直接写数据文件
+    fn modifies_data(&self) -> bool {
+        matches!(self, Command::Add { .. } | Command::Done { .. })
+    }
+}
+
+// 命令的执行方式：通过本地控制接口交给正在运行的实例，直接读写数据目录，
+// 或在应用中执行再次启动时转发来的命令
+enum Backend {
+    Remote(RpcClient),
+    Local(PathBuf),
+    App(tauri::AppHandle),
+}
+
+// 连接正在运行的实例的本地控制接口
+struct RpcClient {
+    reader: BufReader<LocalSocketStream>,
+    next_id: u64,
+}
+
+impl RpcClient {
+    fn connect() -> Option<Self> {
+        let name = crate::api::rpc::socket_name().ok()?;
+        let stream = LocalSocketStream::connect(name).ok()?;
+        Some(RpcClient { reader: BufReader::new(stream), next_id: 1 })
+    }
+
+    fn call<T: DeserializeOwned>(&mut self, method: &str, params: Value) -> Result<T, String> {
+        let id = self.next_id;
+        self.next_id += 1;
+        let request = json!({ "jsonrpc": "2.0", "method": method, "params": params, "id": id });
+        let stream = self.reader.get_mut();
+        stream.write_all(format!("{}\n", request).as_bytes())
+            .map_err(|e| format!("发送请求失败: {}", e))?;
+
+        // 跳过事件通知等不属于本次请求的消息
+        let mut line = String::new();
+        loop {
+            line.clear();
+            let read = self.reader.read_line(&mut line)
+                .map_err(|e| format!("读取响应失败: {}", e))?;
+            if read == 0 {
+                return Err("DeskHive 已断开连接".to_string());
+            }
+            let response: Value = serde_json::from_str(&line)
+                .map_err(|e| format!("解析响应失败: {}", e))?;
+            if response.get("id").and_then(Value::as_u64) != Some(id) {
+                continue;
+            }
+            if let Some(error) = response.get("error") {
+                let message = error.get("message").and_then(Value::as_str).unwrap_or("未知错误");
+                return Err(message.to_string());
+            }
+            return serde_json::from_value(response.get("result").cloned().unwrap_or(Value::Null))
+                .map_err(|e| format!("解析响应失败: {}", e));
+        }
+    }
+}
+
+impl Backend {
+    // 返回 None 表示需要通过单实例转发给正在运行的应用执行
+    fn connect(modifies_data: bool) -> Result<Option<Self>, String> {
+        if let Some(client) = RpcClient::connect() {
+            return Ok(Some(Backend::Remote(client)));
+        }
+        // 应用正在运行时直接写数据文件会与应用的修改互相覆盖，只读的命令仍可直接读取
+        if modifies_data && crate::api::rpc::instance_running() {
+            return Ok(None);
+        }
+
+        // 没有正在运行的实例时直接读写数据目录，与应用使用相同的位置
+        let data_dir = dirs::document_dir()
+            .ok_or_else(|| "获取用户文档目录失败".to_string())?
+            .join("DeskHive")
+            .join("data");
+        std::fs::create_dir_all(&data_dir)
+            .map_err(|e| format!("创建data目录失败: {}", e))?;
+        ensure_accessible(&data_dir)?;
+        Ok(Some(Backend::Local(data_dir)))
+    }
+
+    fn groups(&mut self) -> Result<Vec<TodoGroup>, String> {
+        let group_data: GroupData = match self {
+            Backend::Remote(client) => client.call("load_group_data", json!({}))?,
+            Backend::Local(data_dir) => read_group_store(data_dir)?,
+            Backend::App(app) => {
+                ensure_unlocked()?;
+                read_group_store(&get_data_dir(app)?)?
+            }
+        };
+        Ok(group_data.groups)
+    }
+
+    fn todos(&mut self) -> Result<Vec<Todo>, String> {
+        let todo_data: TodoDataWithGroups = match self {
+            Backend::Remote(client) => client.call("load_todo_data_with_groups", json!({}))?,
+            Backend::Local(data_dir) => read_todo_store(data_dir)?,
+            Backend::App(app) => {
+                ensure_unlocked()?;
+                read_todo_store(&get_data_dir(app)?)?
+            }
+        };
+        Ok(todo_data.todos)
+    }
+
+    fn add_todo(
+        &mut self,
+        text: &str,
+        group_id: Option<&str>,
+        priority: Option<i32>,
+        deadline: Option<i64>,
+        tags: Vec<String>,
+    ) -> Result<Todo, String> {
+        match self {
+            Backend::Remote(client) => client.call("add_todo", json!({
+                "text": text,
+                "group_id": group_id,
+                "priority": priority,
+                "deadline": deadline,
+                "tags": tags,
+            })),
+            Backend::Local(data_dir) => add_todo_to_store(data_dir, text, group_id, priority, deadline, tags),
+            Backend::App(app) => block_on(add_todo(app.clone(), text.to_string(), group_id.map(str::to_string), priority, deadline, Some(tags))),
+        }
+    }
+
+    fn complete_todo(&mut self, todo_id: &str) -> Result<Todo, String> {
+        match self {
+            Backend::Remote(client) => client.call("set_todo_completed", json!({ "todo_id": todo_id, "completed": true })),
+            Backend::Local(data_dir) => set_todo_completed_in_store(data_dir, todo_id, true),
+            Backend::App(app) => block_on(set_todo_completed(app.clone(), todo_id.to_string(), true)),
+        }
+    }
+
+    // 加密需要已解锁的密钥，因此由应用导出；直接读取数据目录时数据不会是加密的
+    fn export(&mut self, plaintext: bool) -> Result<String, String> {
+        match self {
+            Backend::Remote(client) => client.call("export_todos", json!({ "plaintext": plaintext })),
+            Backend::Local(data_dir) => export_store(data_dir, plaintext),
+            Backend::App(app) => block_on(export_todos(app.clone(), Some(plaintext))),
+        }
+    }
+}
+
+// 应用未运行时无法解锁，加密的数据或设置了PIN时拒绝直接访问
+fn ensure_accessible(data_dir: &Path) -> Result<(), String> {
+    crate::data::encryption::init_encryption(data_dir);
+    if crate::data::encryption::is_locked() {
+        return Err("数据已加密，请先启动 DeskHive 并解锁".to_string());
+    }
+
+    let pin_set = std::fs::read_to_string(data_dir.join("app_settings.json")).ok()
+        .and_then(|contents| serde_json::from_str::<Value>(&contents).ok())
+        .and_then(|settings| settings.get("app_lock_pin_hash").and_then(Value::as_str).map(|hash| !hash.is_empty()))
+        .unwrap_or(false);
+    if pin_set {
+        return Err("应用设置了PIN锁，请先启动 DeskHive 并解锁".to_string());
+    }
+    Ok(())
+}
+
+fn parse_args(args: &[String]) -> Result<Command, UsageError> {
+    let (name, rest) = args.split_first().ok_or_else(|| UsageError("缺少命令".to_string()))?;
+
+    let mut positional = Vec::new();
+    let mut options: Vec<(&str, Option<String>)> = Vec::new();
+    let mut iter = rest.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--json" | "--all" | "--plaintext" => options.push((arg, None)),
+            "--due" | "--group" | "--priority" | "--output" => {
+                let value = iter.next().ok_or_else(|| UsageError(format!("{} 缺少参数值", arg)))?;
+                options.push((arg, Some(value.clone())));
+            }
+            _ if arg.starts_with("--") => return Err(UsageError(format!("未知的选项: {}", arg))),
+            _ => positional.push(arg.clone()),
+        }
+    }
+
+    let allowed: &[&str] = match name.as_str() {
+        "add" => &["--due", "--group", "--priority"],
+        "list" => &["--json", "--all", "--group"],
+        "export" => &["--output", "--plaintext"],
+        _ => &[],
+    };
+    if let Some((option, _)) = options.iter().find(|(option, _)| !allowed.contains(option)) {
+        return Err(UsageError(format!("{} 命令不支持选项 {}", name, option)));
+    }
+    let value = |option: &str| options.iter().find(|(name, _)| *name == option).and_then(|(_, value)| value.clone());
+    let flag = |option: &str| options.iter().any(|(name, _)| *name == option);
+
+    match (name.as_str(), positional.len()) {
+        ("add", 0) => Err(UsageError("缺少任务内容".to_string())),
+        ("add", _) => Ok(Command::Add {
+            text: positional.join(" "),
+            due: value("--due"),
+            group: value("--group"),
+            priority: value("--priority"),
+        }),
+        ("list", 0) => Ok(Command::List { json: flag("--json"), all: flag("--all"), group: value("--group") }),
+        ("done", 1) => Ok(Command::Done { id: positional.remove(0) }),
+        ("done", _) => Err(UsageError("需要一个任务ID".to_string())),
+        ("export", 0) => Ok(Command::Export { output: value("--output").map(PathBuf::from), plaintext: flag("--plaintext") }),
+        ("help" | "--help" | "-h", _) => Ok(Command::Help),
+        ("list" | "export", _) => Err(UsageError(format!("多余的参数: {}", positional.join(" ")))),
+        _ => Err(UsageError(format!("未知的命令: {}", name))),
+    }
+}
+
+fn parse_priority(priority: &str) -> Result<i32, String> {
+    match priority.to_lowercase().as_str() {
+        "high" | "高" | "1" => Ok(1),
+        "normal" | "low" | "普通" | "0" => Ok(0),
+        _ => Err(format!("无效的优先级: {}（可选 high、normal）", priority)),
+    }
+}
+
+// 按完整ID或唯一的ID前缀查找任务
+fn resolve_todo<'a>(todos: &'a [Todo], id: &str) -> Result<&'a Todo, String> {
+    if let Some(todo) = todos.iter().find(|todo| todo.id == id) {
+        return Ok(todo);
+    }
+    let matches: Vec<&Todo> = todos.iter().filter(|todo| todo.id.starts_with(id)).collect();
+    match matches.as_slice() {
+        [todo] => Ok(todo),
+        [] => Err(format!("未找到任务: {}", id)),
+        _ => Err(format!("有多个任务的ID以 {} 开头，请输入更长的ID", id)),
+    }
+}
+
+fn format_deadline(deadline: i64) -> String {
+    Local.timestamp_opt(deadline, 0).single()
+        .map(|deadline| deadline.format("%Y-%m-%d %H:%M").to_string())
+        .unwrap_or_default()
+}
+
+fn format_todo(todo: &Todo, groups: &[TodoGroup]) -> String {
+    let group_name = groups.iter().find(|group| group.id == todo.group_id)
+        .map(|group| group.name.as_str())
+        .unwrap_or(todo.group_id.as_str());
+    let mut line = format!(
+        "{} [{}] {}{}  #{}",
+        &todo.id[..todo.id.len().min(8)],
+        if todo.completed { "x" } else { " " },
+        if todo.priority > 0 { "! " } else { "" },
+        todo.text,
+        group_name,
+    );
+    if let Some(deadline) = todo.deadline {
+        line.push_str(&format!("  截止 {}", format_deadline(deadline)));
+    }
+    line
+}
+
+// 执行命令，返回要输出的内容
+fn execute(command: Command, backend: &mut Backend) -> Result<String, String> {
+    let output = match command {
+        Command::Add { text, due, group, priority } => {
+            // 内容中的时间、#分组、#标签和 ! 会被识别，命令行参数优先
+            let groups = backend.groups()?;
+            let parsed = parse_task(&text, Local::now(), &groups);
+            let group_id = match group {
+                Some(group) => Some(resolve_group(&groups, &group)?),
+                None => parsed.group_id,
+            };
+            let priority = priority.as_deref().map(parse_priority).transpose()?.or(parsed.priority);
+            let deadline = due.as_deref().map(|due| parse_deadline(due, Local::now())).transpose()?.or(parsed.deadline);
+            let todo = backend.add_todo(&parsed.text, group_id.as_deref(), priority, deadline, parsed.tags)?;
+            format!("已添加任务 {}", todo.id)
+        }
+        Command::List { json, all, group } => {
+            let groups = backend.groups()?;
+            let group_filter = match group {
+                Some(group) => {
+                    let root = resolve_group(&groups, &group)?;
+                    Some(subtree_ids(&groups, &root))
+                }
+                None => None,
+            };
+            let mut todos: Vec<Todo> = backend.todos()?.into_iter()
+                .filter(|todo| all || !todo.completed)
+                .filter(|todo| group_filter.as_ref().map_or(true, |ids| ids.contains(&todo.group_id)))
+                .collect();
+            todos.sort_by_key(|todo| (todo.completed, todo.group_id != DEFAULT_GROUP_ID, todo.order));
+
+            if json {
+                serde_json::to_string_pretty(&todos)
+                    .map_err(|e| format!("序列化数据失败: {}", e))?
+            } else if todos.is_empty() {
+                "没有任务".to_string()
+            } else {
+                todos.iter().map(|todo| format_todo(todo, &groups)).collect::<Vec<_>>().join("\n")
+            }
+        }
+        Command::Done { id } => {
+            let todos = backend.todos()?;
+            let todo_id = resolve_todo(&todos, &id)?.id.clone();
+            let todo = backend.complete_todo(&todo_id)?;
+            format!("已完成任务: {}", todo.text)
+        }
+        Command::Export { output, plaintext } => {
+            let count = backend.todos()?.len();
+            let json_data = backend.export(plaintext)?;
+            match output {
+                Some(path) => {
+                    std::fs::write(&path, json_data)
+                        .map_err(|e| format!("写入导出文件失败: {}", e))?;
+                    format!("已导出 {} 个任务到 {}", count, path.display())
+                }
+                None => json_data,
+            }
+        }
+        Command::Help => USAGE.to_string(),
+    };
+    Ok(output)
+}
+
+// Windows 发布版本没有控制台窗口，从终端运行时把输出连接到终端
+#[cfg(target_os = "windows")]
+fn attach_console() {
+    use windows::Win32::System::Console::{AttachConsole, ATTACH_PARENT_PROCESS};
+    unsafe {
+        let _ = AttachConsole(ATTACH_PARENT_PROCESS);
+    }
+}
+
+// 第一个参数是否为命令行命令
+pub fn is_command(args: &[String]) -> bool {
+    args.first().is_some_and(|arg| {
+        matches!(arg.as_str(), "add" | "list" | "done" | "export" | "help" | "--help" | "-h")
+    })
+}
+
+// 处理命令行参数：第一个参数是命令时执行命令并返回退出码，否则返回 None 正常启动应用
+// 应用正在运行但未启用本地控制接口时同样返回 None，由单实例插件把命令转发给正在运行的应用
+pub fn run_from_args() -> Option<i32> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if !is_command(&args) {
+        return None;
+    }
+
+    #[cfg(target_os = "windows")]
+    attach_console();
+
+    let result = parse_args(&args).map(|command| match command {
+        Command::Help => Ok(Some(USAGE.to_string())),
+        command => Backend::connect(command.modifies_data())?
+            .map(|mut backend| execute(command, &mut backend))
+            .transpose(),
+    });
+    let code = match result {
+        Ok(Ok(Some(output))) => {
+            println!("{}", output);
+            0
+        }
+        Ok(Ok(None)) => {
+            println!("DeskHive 正在运行但未启用本地控制接口，命令已转发给应用执行，结果记录在应用日志中");
+            return None;
+        }
+        Ok(Err(e)) => {
+            eprintln!("错误: {}", e);
+            1
+        }
+        Err(UsageError(message)) => {
+            eprintln!("{}\n\n{}", message, USAGE);
+            2
+        }
+    };
+    Some(code)
+}
+
+// 在应用中执行再次启动时转发来的命令，与命令行使用相同的解析和执行逻辑，结果写入日志
+pub fn run_forwarded(app: &tauri::AppHandle, args: &[String], cwd: &str) {
+    let result = parse_args(args)
+        .map_err(|UsageError(message)| message)
+        .and_then(|command| {
+            // 相对路径以执行命令时的工作目录为准
+            let command = match command {
+                Command::Export { output: Some(path), plaintext } if path.is_relative() => {
+                    Command::Export { output: Some(Path::new(cwd).join(path)), plaintext }
+                }
+                command => command,
+            };
+            execute(command, &mut Backend::App(app.clone()))
+        });
+    match result {
+        Ok(output) => log::info!("已执行转发的命令 {}:\n{}", args.join(" "), output),
+        Err(e) => log::error!("执行转发的命令 {} 失败: {}", args.join(" "), e),
+    }
+}
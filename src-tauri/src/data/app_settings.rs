@@ -1,10 +1,11 @@
-use std::fs;
 use serde_json;
 use tauri::{Manager, Emitter};
 
 use crate::models::AppSettings;
 use crate::system::auto_start::set_auto_start;
+use crate::system::credentials::{move_password_to_keyring, save_password, CALDAV_ACCOUNT, WEBDAV_ACCOUNT};
 use crate::window::opacity::set_window_opacity;
+use super::storage::write_data_file;
 
 // 获取数据目录路径
 fn get_data_dir(app: &tauri::AppHandle) -> Result<std::path::PathBuf, String> {
@@ -24,14 +25,62 @@ fn get_data_dir(app: &tauri::AppHandle) -> Result<std::path::PathBuf, String> {
     Ok(data_dir)
 }
 
+// 写入设置文件
+pub(crate) fn write_app_settings(app: &tauri::AppHandle, settings: &AppSettings) -> Result<(), String> {
+    let data_dir = get_data_dir(app)?;
+    let file_path = data_dir.join("app_settings.json");
+    
+    // 旧版本以明文保存的密码移入系统钥匙串，钥匙串不可用时暂时保留在设置文件中
+    let mut settings = settings.clone();
+    if let Err(e) = move_password_to_keyring(WEBDAV_ACCOUNT, &mut settings.webdav_password) {
+        log::warn!("WebDAV密码未能移入系统钥匙串: {}", e);
+    }
+    if let Err(e) = move_password_to_keyring(CALDAV_ACCOUNT, &mut settings.caldav_password) {
+        log::warn!("CalDAV密码未能移入系统钥匙串: {}", e);
+    }
+    
+    let json_data = serde_json::to_string_pretty(&settings)
+        .map_err(|e| format!("序列化设置失败: {}", e))?;
+    
+    write_data_file(&file_path, &json_data)
+        .map_err(|e| format!("写入设置文件失败: {}", e))
+}
+
 // Tauri 命令：保存应用设置
+// 前端只传入它管理的设置项，未传入的设置项保留已保存的值
 #[tauri::command]
-pub async fn save_app_settings(app: tauri::AppHandle, settings: AppSettings) -> Result<(), String> {
-    let data_dir = get_data_dir(&app)?;
-    let file_path = data_dir.join("app_settings.json");
+pub async fn save_app_settings(app: tauri::AppHandle, settings: serde_json::Value) -> Result<(), String> {
+    let old_settings = crate::data::load_app_settings(app.clone()).await;
+    
+    let mut merged = match &old_settings {
+        Ok(old_settings) => serde_json::to_value(old_settings)
+            .map_err(|e| format!("序列化设置失败: {}", e))?,
+        Err(_) => serde_json::json!({}),
+    };
+    if let (Some(merged), Some(fields)) = (merged.as_object_mut(), settings.as_object()) {
+        for (key, value) in fields {
+            // PIN只能通过 set_app_lock_pin 修改，接口访问令牌只能通过 regenerate_api_token 修改
+            if key == "app_lock_pin_hash" || key == "api_token" {
+                continue;
+            }
+            // 密码保存在系统钥匙串中，传入空字符串表示删除已保存的密码
+            let account = match key.as_str() {
+                "webdav_password" => Some(WEBDAV_ACCOUNT),
+                "caldav_password" => Some(CALDAV_ACCOUNT),
+                _ => None,
+            };
+            if let Some(account) = account {
+                save_password(account, value.as_str().unwrap_or(""))?;
+                continue;
+            }
+            merged.insert(key.clone(), value.clone());
+        }
+    }
+    let settings: AppSettings = serde_json::from_value(merged)
+        .map_err(|e| format!("解析设置失败: {}", e))?;
     
     // 处理开机自启动设置
-    if let Ok(old_settings) = crate::data::load_app_settings(app.clone()).await {
+    if let Ok(old_settings) = &old_settings {
         // 如果开机自启动设置发生了变化，则更新系统设置
         if old_settings.auto_start != settings.auto_start {
             set_auto_start(&app, settings.auto_start)?;
@@ -41,11 +90,13 @@ pub async fn save_app_settings(app: tauri::AppHandle, settings: AppSettings) ->
         set_auto_start(&app, settings.auto_start)?;
     }
     
-    let json_data = serde_json::to_string_pretty(&settings)
-        .map_err(|e| format!("序列化设置失败: {}", e))?;
+    // 快速添加快捷键发生变化时重新注册，快捷键无效或被占用时不保存
+    let old_shortcut = old_settings.as_ref().map(|old_settings| old_settings.quick_add_shortcut.as_str()).unwrap_or("");
+    if old_shortcut != settings.quick_add_shortcut {
+        crate::window::quick_add::apply_quick_add_shortcut(&app, old_shortcut, &settings.quick_add_shortcut)?;
+    }
     
-    fs::write(&file_path, json_data)
-        .map_err(|e| format!("写入设置文件失败: {}", e))?;
+    write_app_settings(&app, &settings)?;
     
     // 应用设置到主窗口（设置窗口保持不透明）
     if let Some(main_window) = app.get_webview_window("main") {
@@ -95,6 +146,13 @@ pub async fn save_app_settings(app: tauri::AppHandle, settings: AppSettings) ->
     Ok(())
 }
 
+// 直接从数据目录读取设置（命令行等没有 AppHandle 时使用），文件不存在或无法解析时使用默认设置
+pub(crate) fn read_app_settings(data_dir: &std::path::Path) -> AppSettings {
+    std::fs::read_to_string(data_dir.join("app_settings.json")).ok()
+        .and_then(|json_data| serde_json::from_str(&json_data).ok())
+        .unwrap_or_default()
+}
+
 // Tauri 命令：加载应用设置
 #[tauri::command]
 pub async fn load_app_settings(app: tauri::AppHandle) -> Result<AppSettings, String> {
@@ -116,6 +174,27 @@ pub async fn load_app_settings(app: tauri::AppHandle) -> Result<AppSettings, Str
             timeline_deadline_priority: true,
             enable_deadline_notification: false,
             notification_minutes_before: 30,
+            archive_after_days: 0,
+            webdav_enabled: false,
+            webdav_url: "".to_string(),
+            webdav_username: "".to_string(),
+            webdav_password: "".to_string(),
+            webdav_sync_interval_minutes: 15,
+            caldav_enabled: false,
+            caldav_url: "".to_string(),
+            caldav_username: "".to_string(),
+            caldav_password: "".to_string(),
+            caldav_sync_interval_minutes: 15,
+            git_history_enabled: false,
+            app_lock_pin_hash: "".to_string(),
+            auto_lock_minutes: 5,
+            api_enabled: false,
+            api_port: 27121,
+            api_token: "".to_string(),
+            rpc_enabled: false,
+            quick_add_shortcut: "CommandOrControl+Alt+N".to_string(),
+            skip_non_workdays_for_deadlines: false,
+            skip_non_workdays_for_reminders: false,
         });
     }
     
@@ -125,6 +204,17 @@ pub async fn load_app_settings(app: tauri::AppHandle) -> Result<AppSettings, Str
     let settings: AppSettings = serde_json::from_str(&json_data)
         .map_err(|e| format!("解析设置JSON失败: {}", e))?;
     
+    // 设置文件中还有旧版本保存的明文密码时迁移到系统钥匙串
+    if !settings.webdav_password.is_empty() || !settings.caldav_password.is_empty() {
+        let mut migrated = settings.clone();
+        let moved = move_password_to_keyring(WEBDAV_ACCOUNT, &mut migrated.webdav_password)
+            .and_then(|_| move_password_to_keyring(CALDAV_ACCOUNT, &mut migrated.caldav_password));
+        match moved {
+            Ok(()) => write_app_settings(&app, &migrated)?,
+            Err(e) => log::warn!("同步密码未能移入系统钥匙串: {}", e),
+        }
+    }
+    
     Ok(settings)
 }
 
@@ -0,0 +1,387 @@
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard};
+use chrono::{Local, NaiveDate, TimeZone};
+use serde_json;
+use tauri::Emitter;
+
+use crate::models::{Todo, TodoDataWithGroups, TodoExport};
+use crate::system::app_lock::ensure_unlocked;
+use super::encryption::encrypt_contents;
+use super::groups::group_filter;
+use super::storage::{read_data_file, write_data_file};
+use super::todo_data::{get_data_dir, read_group_store, read_todo_store, update_todo_store};
+
+// 归档文件的读改写锁，与数据锁同时持有时先取数据锁
+static ARCHIVE_LOCK: Mutex<()> = Mutex::new(());
+
+fn lock_archive() -> MutexGuard<'static, ()> {
+    ARCHIVE_LOCK.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+// 获取数据目录中的归档目录路径，不存在时创建
+fn ensure_archive_dir(data_dir: &Path) -> Result<PathBuf, String> {
+    let archive_dir = data_dir.join("archive");
+
+    if !archive_dir.exists() {
+        fs::create_dir_all(&archive_dir)
+            .map_err(|e| format!("创建archive目录失败: {}", e))?;
+    }
+
+    Ok(archive_dir)
+}
+
+// 获取归档目录路径
+fn get_archive_dir(app: &tauri::AppHandle) -> Result<PathBuf, String> {
+    ensure_archive_dir(&get_data_dir(app)?)
+}
+
+// 校验归档月份格式（YYYY-MM），防止拼接出任意路径
+fn validate_month(month: &str) -> Result<(), String> {
+    NaiveDate::parse_from_str(&format!("{}-01", month), "%Y-%m-%d")
+        .map(|_| ())
+        .map_err(|_| format!("无效的归档月份: {}", month))
+}
+
+// 根据完成时间计算归档月份
+fn archive_month(completed_at: i64) -> String {
+    Local.timestamp_opt(completed_at, 0)
+        .single()
+        .map(|dt| dt.format("%Y-%m").to_string())
+        .unwrap_or_else(|| "1970-01".to_string())
+}
+
+// 读取单个月份的归档文件
+fn read_archive_file(archive_dir: &Path, month: &str) -> Result<Vec<Todo>, String> {
+    let file_path = archive_dir.join(format!("{}.json", month));
+
+    if !file_path.exists() {
+        return Ok(Vec::new());
+    }
+
+    let json_data = read_data_file(&file_path)
+        .map_err(|e| format!("读取归档文件失败: {}", e))?;
+
+    let archive: TodoDataWithGroups = serde_json::from_str(&json_data)
+        .map_err(|e| format!("解析归档JSON失败: {}", e))?;
+
+    Ok(archive.todos)
+}
+
+// 写入单个月份的归档文件，归档为空时删除文件
+fn write_archive_file(archive_dir: &Path, month: &str, todos: Vec<Todo>) -> Result<(), String> {
+    let file_path = archive_dir.join(format!("{}.json", month));
+
+    if todos.is_empty() {
+        if file_path.exists() {
+            fs::remove_file(&file_path)
+                .map_err(|e| format!("删除归档文件失败: {}", e))?;
+        }
+        return Ok(());
+    }
+
+    let json_data = serde_json::to_string_pretty(&TodoDataWithGroups { todos, ..Default::default() })
+        .map_err(|e| format!("序列化归档数据失败: {}", e))?;
+
+    write_data_file(&file_path, &json_data)
+        .map_err(|e| format!("写入归档文件失败: {}", e))?;
+
+    Ok(())
+}
+
+// 列出所有归档月份（升序）
+fn list_months(archive_dir: &Path) -> Result<Vec<String>, String> {
+    let entries = fs::read_dir(archive_dir)
+        .map_err(|e| format!("读取archive目录失败: {}", e))?;
+
+    let mut months: Vec<String> = entries
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().to_string();
+            let month = name.strip_suffix(".json")?.to_string();
+            validate_month(&month).ok().map(|_| month)
+        })
+        .collect();
+    months.sort();
+
+    Ok(months)
+}
+
+// 读取指定月份（或全部月份）的归档任务
+fn read_archived_todos(archive_dir: &Path, month: Option<String>) -> Result<Vec<Todo>, String> {
+    let months = match month {
+        Some(month) => {
+            validate_month(&month)?;
+            vec![month]
+        }
+        None => list_months(archive_dir)?,
+    };
+
+    let mut todos = Vec::new();
+    for month in months {
+        todos.extend(read_archive_file(archive_dir, &month)?);
+    }
+
+    Ok(todos)
+}
+
+// 所有已归档任务的ID（同步时归档的任务不视为删除）
+pub(crate) fn archived_todo_ids(data_dir: &Path) -> Result<HashSet<String>, String> {
+    let archive_dir = data_dir.join("archive");
+    if !archive_dir.exists() {
+        return Ok(HashSet::new());
+    }
+
+    Ok(read_archived_todos(&archive_dir, None)?
+        .into_iter()
+        .map(|todo| todo.id)
+        .collect())
+}
+
+// 把任务写入按月归档文件，返回归档的任务数
+// 已完成的任务按完成月份归档，未完成的任务（例如随分组一起删除的任务）按归档时的月份归档
+// 调用方在归档文件写入成功后再从任务文件中移除这些任务，中途失败时最多产生重复而不会丢数据
+pub(crate) fn archive_todos(data_dir: &Path, todos: Vec<Todo>) -> Result<usize, String> {
+    let now = Local::now().timestamp();
+    let mut by_month: BTreeMap<String, Vec<Todo>> = BTreeMap::new();
+    for todo in todos {
+        let archived_at = todo.completed_at.filter(|_| todo.completed).unwrap_or(now);
+        by_month.entry(archive_month(archived_at)).or_default().push(todo);
+    }
+
+    let archive_dir = ensure_archive_dir(data_dir)?;
+    let _guard = lock_archive();
+    let mut archived_count = 0;
+    for (month, todos) in by_month {
+        let mut archived = read_archive_file(&archive_dir, &month)?;
+        archived.retain(|existing| !todos.iter().any(|todo| todo.id == existing.id));
+        archived_count += todos.len();
+        archived.extend(todos);
+        write_archive_file(&archive_dir, &month, archived)?;
+    }
+
+    Ok(archived_count)
+}
+
+// 将超过设定天数的已完成任务移动到按月归档文件中，返回归档的任务数
+pub async fn archive_old_todos(app: tauri::AppHandle) -> Result<usize, String> {
+    let settings = super::load_app_settings(app.clone()).await?;
+    if settings.archive_after_days == 0 {
+        return Ok(0);
+    }
+
+    let cutoff = Local::now().timestamp() - settings.archive_after_days as i64 * 24 * 60 * 60;
+    let is_old = |todo: &Todo| todo.completed && todo.completed_at.is_some_and(|completed_at| completed_at < cutoff);
+    let data_dir = get_data_dir(&app)?;
+    if !read_todo_store(&data_dir)?.todos.iter().any(is_old) {
+        return Ok(0);
+    }
+
+    // 在数据锁内取出要归档的任务，避免覆盖同时发生的修改
+    // 先写归档文件再更新任务文件，中途失败时最多产生重复而不会丢数据
+    let archived_count = update_todo_store(&data_dir, |todos, _| {
+        let (to_archive, remaining): (Vec<Todo>, Vec<Todo>) = std::mem::take(todos)
+            .into_iter()
+            .partition(is_old);
+        *todos = remaining;
+        archive_todos(&data_dir, to_archive)
+    })?;
+
+    log::info!("已归档 {} 个已完成任务", archived_count);
+    Ok(archived_count)
+}
+
+// Tauri 命令：立即归档旧的已完成任务
+#[tauri::command]
+pub async fn archive_completed_todos(app: tauri::AppHandle) -> Result<usize, String> {
+    ensure_unlocked()?;
+    let count = archive_old_todos(app.clone()).await?;
+
+    if count > 0 {
+        let _ = app.emit("todo-data-changed", ());
+    }
+
+    Ok(count)
+}
+
+// Tauri 命令：列出归档月份
+#[tauri::command]
+pub async fn list_archive_months(app: tauri::AppHandle) -> Result<Vec<String>, String> {
+    ensure_unlocked()?;
+    list_months(&get_archive_dir(&app)?)
+}
+
+// Tauri 命令：搜索归档任务（按文本，不区分大小写）
+// 指定 group_id 时只搜索该分组及其下级分组中的任务
+#[tauri::command]
+pub async fn search_archive(
+    app: tauri::AppHandle,
+    query: String,
+    month: Option<String>,
+    group_id: Option<String>
+) -> Result<Vec<Todo>, String> {
+    ensure_unlocked()?;
+    let archive_dir = get_archive_dir(&app)?;
+    let group_ids = group_filter(&get_data_dir(&app)?, group_id.as_deref())?;
+    let query = query.trim().to_lowercase();
+
+    let mut todos: Vec<Todo> = read_archived_todos(&archive_dir, month)?
+        .into_iter()
+        .filter(|todo| group_ids.as_ref().map_or(true, |ids| ids.contains(&todo.group_id)))
+        .filter(|todo| query.is_empty() || todo.text.to_lowercase().contains(&query))
+        .collect();
+
+    // 最近完成的排在前面
+    todos.sort_by_key(|todo| std::cmp::Reverse(todo.completed_at));
+
+    Ok(todos)
+}
+
+// Tauri 命令：导出归档任务到指定文件，返回导出的任务数
+// 启用数据加密时导出文件同样加密，plaintext 为 true 时才导出明文
+// 指定 group_id 时只导出该分组及其下级分组中的任务
+#[tauri::command]
+pub async fn export_archive(
+    app: tauri::AppHandle,
+    file_path: String,
+    month: Option<String>,
+    plaintext: Option<bool>,
+    group_id: Option<String>
+) -> Result<usize, String> {
+    ensure_unlocked()?;
+    let archive_dir = get_archive_dir(&app)?;
+    let group_ids = group_filter(&get_data_dir(&app)?, group_id.as_deref())?;
+    let mut todos = read_archived_todos(&archive_dir, month)?;
+    if let Some(group_ids) = &group_ids {
+        todos.retain(|todo| group_ids.contains(&todo.group_id));
+    }
+    let count = todos.len();
+
+    // 一并导出任务所属的分组（包括颜色和图标）
+    let groups = read_group_store(&get_data_dir(&app)?)?.groups.into_iter()
+        .filter(|group| todos.iter().any(|todo| todo.group_id == group.id))
+        .collect();
+
+    let mut json_data = serde_json::to_string_pretty(&TodoExport { todos, groups })
+        .map_err(|e| format!("序列化归档数据失败: {}", e))?;
+    if !plaintext.unwrap_or(false) {
+        json_data = encrypt_contents(&json_data)?;
+    }
+
+    fs::write(&file_path, json_data)
+        .map_err(|e| format!("写入导出文件失败: {}", e))?;
+
+    Ok(count)
+}
+
+// Tauri 命令：将归档任务恢复到任务列表，返回恢复的任务数
+#[tauri::command]
+pub async fn restore_archived_todos(app: tauri::AppHandle, todo_ids: Vec<String>) -> Result<usize, String> {
+    ensure_unlocked()?;
+    let count = restore_todos(&get_data_dir(&app)?, &todo_ids)?;
+
+    if count > 0 {
+        let _ = app.emit("todo-data-changed", ());
+    }
+    Ok(count)
+}
+
+fn restore_todos(data_dir: &Path, todo_ids: &[String]) -> Result<usize, String> {
+    let archive_dir = ensure_archive_dir(data_dir)?;
+
+    // 在数据锁内把归档中的任务加回任务列表
+    let restored_ids = update_todo_store(data_dir, |todos, _| {
+        let groups = read_group_store(data_dir)?.groups;
+        let restored: Vec<Todo> = read_archived_todos(&archive_dir, None)?
+            .into_iter()
+            .filter(|todo| todo_ids.contains(&todo.id))
+            .collect();
+
+        let mut restored_ids = HashSet::new();
+        for mut todo in restored {
+            // 原分组已被删除时恢复到默认分组
+            if !groups.iter().any(|group| group.id == todo.group_id) {
+                todo.group_id = "default".to_string();
+            }
+            // 重新记录修改时间，否则同步时会被归档时留下的删除记录再次删除
+            todo.updated_at = 0;
+            todo.field_updated_at.clear();
+            todos.retain(|existing| existing.id != todo.id);
+            restored_ids.insert(todo.id.clone());
+            todos.push(todo);
+        }
+        Ok(restored_ids)
+    })?;
+    if restored_ids.is_empty() {
+        return Ok(0);
+    }
+
+    // 任务文件写入成功后再从归档文件中移除，中途失败时最多产生重复而不会丢数据
+    {
+        let _guard = lock_archive();
+        for month in list_months(&archive_dir)? {
+            let archived = read_archive_file(&archive_dir, &month)?;
+            if archived.iter().any(|todo| restored_ids.contains(&todo.id)) {
+                let kept = archived.into_iter().filter(|todo| !restored_ids.contains(&todo.id)).collect();
+                write_archive_file(&archive_dir, &month, kept)?;
+            }
+        }
+    }
+
+    Ok(restored_ids.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::test_todo;
+
+    #[test]
+    fn archives_completed_and_pending_todos_by_month() {
+        let data_dir = std::env::temp_dir().join(format!("deskhive-archive-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&data_dir).unwrap();
+        let completed_at = Local.with_ymd_and_hms(2024, 3, 15, 12, 0, 0).unwrap().timestamp();
+
+        let count = archive_todos(&data_dir, vec![test_todo("done", 1_700_000_000, None, Some(completed_at)), test_todo("pending", 1_700_000_000, None, None)]).unwrap();
+        assert_eq!(count, 2);
+
+        let archive_dir = data_dir.join("archive");
+        let march: Vec<String> = read_archive_file(&archive_dir, "2024-03").unwrap().into_iter().map(|todo| todo.id).collect();
+        assert_eq!(march, ["done"]);
+        let current: Vec<String> = read_archive_file(&archive_dir, &archive_month(Local::now().timestamp()))
+            .unwrap().into_iter().map(|todo| todo.id).collect();
+        assert_eq!(current, ["pending"]);
+
+        // 再次归档同一任务时替换旧的归档记录
+        archive_todos(&data_dir, vec![test_todo("done", 1_700_000_000, None, Some(completed_at))]).unwrap();
+        assert_eq!(read_archive_file(&archive_dir, "2024-03").unwrap().len(), 1);
+        assert_eq!(archived_todo_ids(&data_dir).unwrap(), HashSet::from(["done".to_string(), "pending".to_string()]));
+    }
+
+    #[test]
+    fn restored_todos_outlive_the_archive_tombstone() {
+        let data_dir = std::env::temp_dir().join(format!("deskhive-archive-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&data_dir).unwrap();
+        let completed_at = Local.with_ymd_and_hms(2024, 3, 15, 12, 0, 0).unwrap().timestamp();
+        let mut archived = test_todo("done", 1_700_000_000, None, Some(completed_at));
+        archived.updated_at = completed_at;
+
+        update_todo_store(&data_dir, |todos, _| {
+            todos.push(archived);
+            Ok(())
+        }).unwrap();
+        update_todo_store(&data_dir, |todos, _| archive_todos(&data_dir, std::mem::take(todos))).unwrap();
+        let tombstones = read_todo_store(&data_dir).unwrap().tombstones;
+        assert_eq!(tombstones.len(), 1);
+
+        assert_eq!(restore_todos(&data_dir, &["done".to_string()]).unwrap(), 1);
+        let store = read_todo_store(&data_dir).unwrap();
+        assert_eq!(store.todos.len(), 1);
+        assert!(store.todos[0].updated_at >= tombstones[0].deleted_at);
+        assert!(store.tombstones.is_empty());
+        // 已从归档中移除
+        assert!(archived_todo_ids(&data_dir).unwrap().is_empty());
+        assert_eq!(restore_todos(&data_dir, &["done".to_string()]).unwrap(), 0);
+    }
+}
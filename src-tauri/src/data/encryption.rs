@@ -0,0 +1,486 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard};
+use argon2::{Algorithm, Argon2, Params, Version};
+use base64::Engine;
+use chacha20poly1305::aead::rand_core::RngCore;
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng};
+use chacha20poly1305::{Key, XChaCha20Poly1305, XNonce};
+use serde::{Deserialize, Serialize};
+use serde_json;
+use tauri::Emitter;
+
+use crate::models::EncryptionStatus;
+use crate::system::app_lock::ensure_unlocked;
+use super::history::purge_history;
+use super::storage::{lock_store, write_data_file};
+use super::todo_data::get_data_dir;
+
+// 加密配置文件（本身不加密），存在即表示启用了数据加密
+const CONFIG_FILE: &str = "encryption.json";
+
+// 需要加密的数据文件，archive 目录下的归档文件也会加密
+// 应用设置在解锁前就需要读取（窗口位置、透明度等），因此不加密
+const PROTECTED_FILES: &[&str] = &["todos_with_groups.json", "groups.json", "todo_list.json", "caldav_state.json"];
+const ARCHIVE_DIR: &str = "archive";
+
+const ENVELOPE_FORMAT: &str = "deskhive-encrypted";
+const VERIFIER_PLAINTEXT: &str = "DeskHive";
+
+// 锁定状态下读写加密数据时的错误信息
+const LOCKED_ERROR: &str = "数据已加密，请先输入密码解锁";
+
+// 密钥派生参数（Argon2id），随密文一起保存，其他设备只需密码即可解密
+#[derive(Serialize, Deserialize, Clone)]
+struct KdfParams {
+    algorithm: String,
+    m_cost: u32,
+    t_cost: u32,
+    p_cost: u32,
+    salt: String, // Base64
+}
+
+impl KdfParams {
+    fn generate() -> Self {
+        let mut salt = [0u8; 16];
+        OsRng.fill_bytes(&mut salt);
+        Self {
+            algorithm: "argon2id".to_string(),
+            m_cost: Params::DEFAULT_M_COST,
+            t_cost: Params::DEFAULT_T_COST,
+            p_cost: Params::DEFAULT_P_COST,
+            salt: base64::engine::general_purpose::STANDARD.encode(salt),
+        }
+    }
+
+    // 由密码派生256位密钥
+    fn derive_key(&self, passphrase: &str) -> Result<[u8; 32], String> {
+        if self.algorithm != "argon2id" {
+            return Err(format!("不支持的密钥派生算法: {}", self.algorithm));
+        }
+        let salt = base64::engine::general_purpose::STANDARD.decode(&self.salt)
+            .map_err(|e| format!("无效的加密参数: {}", e))?;
+        let params = Params::new(self.m_cost, self.t_cost, self.p_cost, Some(32))
+            .map_err(|e| format!("无效的加密参数: {}", e))?;
+
+        let mut key = [0u8; 32];
+        Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+            .hash_password_into(passphrase.as_bytes(), &salt, &mut key)
+            .map_err(|e| format!("派生密钥失败: {}", e))?;
+        Ok(key)
+    }
+}
+
+// 加密后的文件内容（XChaCha20-Poly1305），仍然是JSON格式，便于同步工具和git处理
+#[derive(Serialize, Deserialize)]
+struct Envelope {
+    format: String,
+    version: u32,
+    kdf: KdfParams,
+    nonce: String,      // Base64
+    ciphertext: String, // Base64
+}
+
+impl Envelope {
+    fn seal(kdf: &KdfParams, key: &[u8; 32], plaintext: &str) -> Result<Self, String> {
+        let cipher = XChaCha20Poly1305::new(Key::from_slice(key));
+        let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let ciphertext = cipher.encrypt(&nonce, plaintext.as_bytes())
+            .map_err(|_| "加密数据失败".to_string())?;
+
+        let engine = base64::engine::general_purpose::STANDARD;
+        Ok(Self {
+            format: ENVELOPE_FORMAT.to_string(),
+            version: 1,
+            kdf: kdf.clone(),
+            nonce: engine.encode(nonce),
+            ciphertext: engine.encode(ciphertext),
+        })
+    }
+
+    fn open(&self, key: &[u8; 32]) -> Result<String, String> {
+        let engine = base64::engine::general_purpose::STANDARD;
+        let nonce = engine.decode(&self.nonce).map_err(|e| format!("无效的加密数据: {}", e))?;
+        let ciphertext = engine.decode(&self.ciphertext).map_err(|e| format!("无效的加密数据: {}", e))?;
+        if nonce.len() != 24 {
+            return Err("无效的加密数据".to_string());
+        }
+
+        let cipher = XChaCha20Poly1305::new(Key::from_slice(key));
+        let plaintext = cipher.decrypt(XNonce::from_slice(&nonce), ciphertext.as_slice())
+            .map_err(|_| "解密失败：密码错误或数据已被篡改".to_string())?;
+        String::from_utf8(plaintext).map_err(|e| format!("解密后的数据无效: {}", e))
+    }
+
+    // 内容不是加密格式时返回 None
+    fn parse(contents: &str) -> Option<Self> {
+        if !contents.contains(ENVELOPE_FORMAT) {
+            return None;
+        }
+        serde_json::from_str::<Self>(contents)
+            .ok()
+            .filter(|envelope| envelope.format == ENVELOPE_FORMAT)
+    }
+}
+
+// 更换密码前使用的密钥，用当前密钥加密后保存在配置中
+#[derive(Serialize, Deserialize)]
+struct PreviousKey {
+    salt: String,
+    key: String, // Base64
+}
+
+// 加密配置：写入新数据使用的参数，以及用于校验密码的密文
+#[derive(Serialize, Deserialize)]
+struct EncryptionConfig {
+    kdf: KdfParams,
+    verifier: Envelope,
+    // 旧密钥：更换密码中途失败或其他设备尚未重新加密时，仍可读取旧密钥加密的数据
+    #[serde(default)]
+    previous_keys: Vec<Envelope>,
+}
+
+impl EncryptionConfig {
+    // 用当前密钥解开保存的旧密钥
+    fn previous_keys(&self, key: &[u8; 32]) -> Result<HashMap<String, [u8; 32]>, String> {
+        let engine = base64::engine::general_purpose::STANDARD;
+        let mut keys = HashMap::new();
+        for envelope in &self.previous_keys {
+            let previous: PreviousKey = serde_json::from_str(&envelope.open(key)?)
+                .map_err(|e| format!("解析旧密钥失败: {}", e))?;
+            let bytes = engine.decode(&previous.key).map_err(|e| format!("解析旧密钥失败: {}", e))?;
+            let previous_key: [u8; 32] = bytes.try_into().map_err(|_| "解析旧密钥失败".to_string())?;
+            keys.insert(previous.salt, previous_key);
+        }
+        Ok(keys)
+    }
+}
+
+#[derive(Default)]
+struct EncryptionState {
+    enabled: bool,
+    passphrase: Option<String>,          // 解锁后保存在内存中，用于解密其他设备写入的数据
+    current: Option<(KdfParams, [u8; 32])>, // 写入数据使用的参数和密钥
+    keys: HashMap<String, [u8; 32]>,     // 按盐缓存已派生的密钥
+}
+
+static STATE: Mutex<Option<EncryptionState>> = Mutex::new(None);
+
+fn state() -> MutexGuard<'static, Option<EncryptionState>> {
+    STATE.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+fn read_config(data_dir: &Path) -> Result<Option<EncryptionConfig>, String> {
+    match fs::read_to_string(data_dir.join(CONFIG_FILE)) {
+        Ok(contents) => serde_json::from_str(&contents)
+            .map(Some)
+            .map_err(|e| format!("解析加密配置失败: {}", e)),
+        Err(_) => Ok(None),
+    }
+}
+
+fn write_config(data_dir: &Path, config: &EncryptionConfig) -> Result<(), String> {
+    let json_data = serde_json::to_string_pretty(config)
+        .map_err(|e| format!("序列化加密配置失败: {}", e))?;
+    // 先写临时文件再替换，写入中途失败不会留下损坏的配置
+    let temp_path = data_dir.join(format!("{}.tmp", CONFIG_FILE));
+    fs::write(&temp_path, json_data)
+        .and_then(|_| fs::rename(&temp_path, data_dir.join(CONFIG_FILE)))
+        .map_err(|e| format!("写入加密配置失败: {}", e))
+}
+
+// 读取数据目录的加密配置，应用启动时调用
+pub fn init_encryption(data_dir: &Path) {
+    let enabled = data_dir.join(CONFIG_FILE).exists();
+    *state() = Some(EncryptionState { enabled, ..Default::default() });
+}
+
+// 是否启用了加密但尚未解锁
+pub fn is_locked() -> bool {
+    state().as_ref().is_some_and(|state| state.enabled && state.current.is_none())
+}
+
+// 判断文件是否需要加密
+fn is_protected(path: &Path) -> bool {
+    let file_name = path.file_name().map(|name| name.to_string_lossy().to_string()).unwrap_or_default();
+    let in_archive = path.parent()
+        .and_then(|parent| parent.file_name())
+        .is_some_and(|name| name == ARCHIVE_DIR);
+
+    PROTECTED_FILES.contains(&file_name.as_str()) || (in_archive && file_name.ends_with(".json"))
+}
+
+// 在启用加密时加密内容，未加锁的状态下无法写入
+pub fn encrypt_contents(plaintext: &str) -> Result<String, String> {
+    let guard = state();
+    let state = match guard.as_ref() {
+        Some(state) if state.enabled => state,
+        _ => return Ok(plaintext.to_string()),
+    };
+    let (kdf, key) = state.current.as_ref().ok_or_else(|| LOCKED_ERROR.to_string())?;
+
+    serde_json::to_string_pretty(&Envelope::seal(kdf, key, plaintext)?)
+        .map_err(|e| format!("序列化加密数据失败: {}", e))
+}
+
+// 按文件加密内容，不需要加密的文件原样返回
+pub fn encrypt_for(path: &Path, plaintext: &str) -> Result<String, String> {
+    if is_protected(path) {
+        encrypt_contents(plaintext)
+    } else {
+        Ok(plaintext.to_string())
+    }
+}
+
+// 解密文件内容，未加密的内容原样返回（兼容启用加密前的数据）
+pub fn decrypt_contents(contents: &str) -> Result<String, String> {
+    let envelope = match Envelope::parse(contents) {
+        Some(envelope) => envelope,
+        None => return Ok(contents.to_string()),
+    };
+
+    let mut guard = state();
+    let state = guard.as_mut().ok_or_else(|| LOCKED_ERROR.to_string())?;
+    let key = match state.keys.get(&envelope.kdf.salt) {
+        Some(key) => *key,
+        None => {
+            let passphrase = state.passphrase.as_deref().ok_or_else(|| LOCKED_ERROR.to_string())?;
+            let key = envelope.kdf.derive_key(passphrase)?;
+            state.keys.insert(envelope.kdf.salt.clone(), key);
+            key
+        }
+    };
+
+    envelope.open(&key)
+}
+
+// 校验密码，返回加密配置和派生的密钥
+fn verify_passphrase(data_dir: &Path, passphrase: &str) -> Result<(EncryptionConfig, [u8; 32]), String> {
+    let config = read_config(data_dir)?.ok_or_else(|| "数据未加密".to_string())?;
+    let key = config.kdf.derive_key(passphrase)?;
+    match config.verifier.open(&key) {
+        Ok(plaintext) if plaintext == VERIFIER_PLAINTEXT => Ok((config, key)),
+        _ => Err("密码错误".to_string()),
+    }
+}
+
+// 生成新的加密配置
+fn new_config(passphrase: &str) -> Result<(EncryptionConfig, [u8; 32]), String> {
+    if passphrase.chars().count() < 8 {
+        return Err("密码至少需要8个字符".to_string());
+    }
+    let kdf = KdfParams::generate();
+    let key = kdf.derive_key(passphrase)?;
+    let verifier = Envelope::seal(&kdf, &key, VERIFIER_PLAINTEXT)?;
+    Ok((EncryptionConfig { kdf, verifier, previous_keys: Vec::new() }, key))
+}
+
+// 把旧密钥用新密钥加密后保存到配置中
+fn keep_previous_keys(config: &mut EncryptionConfig, key: &[u8; 32], previous: &HashMap<String, [u8; 32]>) -> Result<(), String> {
+    let engine = base64::engine::general_purpose::STANDARD;
+    for (salt, previous_key) in previous {
+        if *salt == config.kdf.salt {
+            continue;
+        }
+        let plaintext = serde_json::to_string(&PreviousKey { salt: salt.clone(), key: engine.encode(previous_key) })
+            .map_err(|e| format!("序列化旧密钥失败: {}", e))?;
+        config.previous_keys.push(Envelope::seal(&config.kdf, key, &plaintext)?);
+    }
+    Ok(())
+}
+
+fn unlocked_state(config: &EncryptionConfig, key: [u8; 32], passphrase: &str) -> Result<EncryptionState, String> {
+    let mut keys = config.previous_keys(&key)?;
+    keys.insert(config.kdf.salt.clone(), key);
+    Ok(EncryptionState {
+        enabled: true,
+        passphrase: Some(passphrase.to_string()),
+        current: Some((config.kdf.clone(), key)),
+        keys,
+    })
+}
+
+// 列出数据目录中需要加密的文件
+fn protected_files(data_dir: &Path) -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = PROTECTED_FILES.iter()
+        .map(|name| data_dir.join(name))
+        .filter(|path| path.exists())
+        .collect();
+    if let Ok(entries) = fs::read_dir(data_dir.join(ARCHIVE_DIR)) {
+        files.extend(entries.filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| is_protected(path)));
+    }
+    files
+}
+
+// 用当前密钥读取所有需要加密的文件，切换加密状态后重新写入
+fn rewrite_protected_files(data_dir: &Path, next_state: EncryptionState) -> Result<(), String> {
+    let _guard = lock_store();
+    let mut contents = Vec::new();
+    for path in protected_files(data_dir) {
+        let raw = fs::read_to_string(&path)
+            .map_err(|e| format!("读取 {} 失败: {}", path.display(), e))?;
+        contents.push((path, decrypt_contents(&raw)?));
+    }
+
+    *state() = Some(next_state);
+
+    for (path, plaintext) in contents {
+        write_data_file(&path, &plaintext)
+            .map_err(|e| format!("写入 {} 失败: {}", path.display(), e))?;
+    }
+    Ok(())
+}
+
+// Tauri 命令：获取数据加密状态
+#[tauri::command]
+pub async fn get_encryption_status() -> Result<EncryptionStatus, String> {
+    let guard = state();
+    let enabled = guard.as_ref().is_some_and(|state| state.enabled);
+    Ok(EncryptionStatus {
+        enabled,
+        unlocked: enabled && guard.as_ref().is_some_and(|state| state.current.is_some()),
+    })
+}
+
+// 启用数据加密：加密现有的数据文件，并清除含有明文的修改历史
+fn enable(data_dir: &Path, passphrase: &str) -> Result<(), String> {
+    if read_config(data_dir)?.is_some() {
+        return Err("数据已经加密".to_string());
+    }
+
+    let (config, key) = new_config(passphrase)?;
+    // 先写入配置：中途失败时剩余的明文文件仍可读取，下次写入时加密
+    write_config(data_dir, &config)?;
+    rewrite_protected_files(data_dir, unlocked_state(&config, key, passphrase)?)?;
+
+    // 历史记录中保存的是加密前的明文，保留会让加密失去意义
+    if purge_history(data_dir)? {
+        log::warn!("启用加密后已清除明文的修改历史，之前的版本无法再恢复");
+    }
+    Ok(())
+}
+
+// 更换加密密码：新配置保存所有旧密钥后再重新加密数据文件，中途失败时仍可用新密码读取全部数据
+fn change_passphrase(data_dir: &Path, old_passphrase: &str, new_passphrase: &str) -> Result<(), String> {
+    let (old_config, old_key) = verify_passphrase(data_dir, old_passphrase)?;
+    let old_state = unlocked_state(&old_config, old_key, old_passphrase)?;
+    let previous = old_state.keys.clone();
+    // 确保能读取用旧密码加密的数据
+    *state() = Some(old_state);
+
+    let (mut config, key) = new_config(new_passphrase)?;
+    keep_previous_keys(&mut config, &key, &previous)?;
+    let next_state = unlocked_state(&config, key, new_passphrase)?;
+
+    write_config(data_dir, &config)?;
+    rewrite_protected_files(data_dir, next_state)
+}
+
+// Tauri 命令：启用数据加密，并加密现有的数据文件
+#[tauri::command]
+pub async fn enable_encryption(app: tauri::AppHandle, passphrase: String) -> Result<(), String> {
+    ensure_unlocked()?;
+    let data_dir = get_data_dir(&app)?;
+    enable(&data_dir, &passphrase)?;
+
+    log::info!("已启用数据加密");
+    Ok(())
+}
+
+// Tauri 命令：输入密码解锁加密的数据
+#[tauri::command]
+pub async fn unlock_data(app: tauri::AppHandle, passphrase: String) -> Result<(), String> {
+    let data_dir = get_data_dir(&app)?;
+    let (config, key) = verify_passphrase(&data_dir, &passphrase)?;
+    *state() = Some(unlocked_state(&config, key, &passphrase)?);
+
+    let _ = app.emit("data-unlocked", ());
+    let _ = app.emit("todo-data-changed", ());
+    let _ = app.emit("group-data-changed", ());
+    Ok(())
+}
+
+// Tauri 命令：更换加密密码，并用新密钥重新加密所有数据文件
+#[tauri::command]
+pub async fn change_encryption_passphrase(
+    app: tauri::AppHandle,
+    old_passphrase: String,
+    new_passphrase: String,
+) -> Result<(), String> {
+    ensure_unlocked()?;
+    let data_dir = get_data_dir(&app)?;
+    change_passphrase(&data_dir, &old_passphrase, &new_passphrase)?;
+
+    log::info!("已更换数据加密密码");
+    Ok(())
+}
+
+// Tauri 命令：关闭数据加密，将数据文件恢复为明文
+#[tauri::command]
+pub async fn disable_encryption(app: tauri::AppHandle, passphrase: String) -> Result<(), String> {
+    ensure_unlocked()?;
+    let data_dir = get_data_dir(&app)?;
+    let (config, key) = verify_passphrase(&data_dir, &passphrase)?;
+    *state() = Some(unlocked_state(&config, key, &passphrase)?);
+
+    rewrite_protected_files(&data_dir, EncryptionState::default())?;
+    fs::remove_file(data_dir.join(CONFIG_FILE))
+        .map_err(|e| format!("删除加密配置失败: {}", e))?;
+
+    log::info!("已关闭数据加密");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 测试不修改全局加密状态，避免其他并行测试写入的数据被加密
+    fn temp_dir() -> PathBuf {
+        let data_dir = std::env::temp_dir().join(format!("deskhive-encryption-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&data_dir).unwrap();
+        data_dir
+    }
+
+    #[test]
+    fn round_trips_contents_with_the_derived_key() {
+        let kdf = KdfParams::generate();
+        let key = kdf.derive_key("correct horse").unwrap();
+        let sealed = serde_json::to_string_pretty(&Envelope::seal(&kdf, &key, r#"{"todos":[]}"#).unwrap()).unwrap();
+
+        let envelope = Envelope::parse(&sealed).unwrap();
+        assert_eq!(envelope.open(&key).unwrap(), r#"{"todos":[]}"#);
+        assert!(envelope.open(&kdf.derive_key("wrong horse").unwrap()).is_err());
+        assert!(Envelope::parse(r#"{"todos":[]}"#).is_none());
+    }
+
+    #[test]
+    fn rekeyed_config_still_opens_data_written_with_old_keys() {
+        let data_dir = temp_dir();
+        let (first, first_key) = new_config("first passphrase").unwrap();
+        let old_data = Envelope::seal(&first.kdf, &first_key, "旧密码加密的数据").unwrap();
+        let first_keys = unlocked_state(&first, first_key, "first passphrase").unwrap().keys;
+
+        let (mut second, second_key) = new_config("second passphrase").unwrap();
+        keep_previous_keys(&mut second, &second_key, &first_keys).unwrap();
+        write_config(&data_dir, &second).unwrap();
+
+        // 配置写入后重新加密中途失败：只用新密码也能读取旧密钥加密的文件
+        assert_eq!(verify_passphrase(&data_dir, "first passphrase").err().as_deref(), Some("密码错误"));
+        let (config, key) = verify_passphrase(&data_dir, "second passphrase").unwrap();
+        let state = unlocked_state(&config, key, "second passphrase").unwrap();
+        assert_eq!(old_data.open(&state.keys[&first.kdf.salt]).unwrap(), "旧密码加密的数据");
+
+        // 再次更换密码时保留所有旧密钥
+        let (mut third, third_key) = new_config("third passphrase").unwrap();
+        keep_previous_keys(&mut third, &third_key, &state.keys).unwrap();
+        let keys = unlocked_state(&third, third_key, "third passphrase").unwrap().keys;
+        assert_eq!(keys.len(), 3);
+        assert!(keys.contains_key(&first.kdf.salt) && keys.contains_key(&second.kdf.salt));
+
+        fs::remove_dir_all(&data_dir).unwrap();
+    }
+}
@@ -0,0 +1,558 @@
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use chrono::{DateTime, Local, NaiveDate, NaiveTime, TimeDelta, TimeZone};
+use tauri::Emitter;
+use uuid::Uuid;
+
+use crate::models::{GroupDeleteStrategy, GroupTodoCounts, Todo, TodoGroup};
+use crate::system::app_lock::ensure_unlocked;
+use super::archive::archive_todos;
+use super::todo_data::{get_data_dir, read_group_store, read_todo_store, update_group_store, update_todo_store, DEFAULT_GROUP_ID};
+
+// 校验分组名称，返回去除首尾空白后的名称
+pub(crate) fn validate_group_name(name: &str) -> Result<String, String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("分组名称不能为空".to_string());
+    }
+    Ok(name.to_string())
+}
+
+// 在分组列表中查找分组
+fn find_group<'a>(groups: &'a mut [TodoGroup], group_id: &str) -> Result<&'a mut TodoGroup, String> {
+    groups.iter_mut()
+        .find(|group| group.id == group_id)
+        .ok_or_else(|| format!("未找到指定的分组: id='{}'", group_id))
+}
+
+// 分组及其所有下级分组的ID
+pub(crate) fn subtree_ids(groups: &[TodoGroup], root: &str) -> HashSet<String> {
+    let mut ids = HashSet::from([root.to_string()]);
+    loop {
+        let before = ids.len();
+        for group in groups {
+            if group.parent_id.as_ref().is_some_and(|parent| ids.contains(parent)) {
+                ids.insert(group.id.clone());
+            }
+        }
+        if ids.len() == before {
+            return ids;
+        }
+    }
+}
+
+// 按分组筛选时需要包含的分组ID（包括下级分组），不筛选时返回 None
+pub(crate) fn group_filter(data_dir: &Path, group_id: Option<&str>) -> Result<Option<HashSet<String>>, String> {
+    let group_id = match group_id {
+        Some(group_id) => group_id,
+        None => return Ok(None),
+    };
+    let groups = read_group_store(data_dir)?.groups;
+    if !groups.iter().any(|group| group.id == group_id) {
+        return Err(format!("未找到指定的分组: id='{}'", group_id));
+    }
+    Ok(Some(subtree_ids(&groups, group_id)))
+}
+
+// 从 start 沿上级分组向上查找，回到已经过的分组时返回形成循环的分组
+fn find_cycle(parents: &HashMap<String, String>, start: &str) -> Option<Vec<String>> {
+    let mut path = vec![start.to_string()];
+    let mut current = start;
+    while let Some(parent) = parents.get(current) {
+        if let Some(position) = path.iter().position(|id| id == parent) {
+            return Some(path.split_off(position));
+        }
+        path.push(parent.clone());
+        current = parent;
+    }
+    None
+}
+
+// 修复分组层级：默认分组始终是顶层分组，不能作为上级分组；上级分组不存在时改为顶层分组；
+// 形成循环时把循环中ID最小的分组改为顶层分组，保证各设备修复结果一致
+pub(crate) fn repair_group_tree(groups: &mut [TodoGroup]) {
+    let ids: HashSet<String> = groups.iter().map(|group| group.id.clone()).collect();
+    for group in groups.iter_mut() {
+        let invalid = group.id == DEFAULT_GROUP_ID || group.parent_id.as_ref()
+            .is_some_and(|parent| parent == DEFAULT_GROUP_ID || !ids.contains(parent));
+        if invalid {
+            group.parent_id = None;
+        }
+    }
+
+    loop {
+        let parents: HashMap<String, String> = groups.iter()
+            .filter_map(|group| Some((group.id.clone(), group.parent_id.clone()?)))
+            .collect();
+        let breaker = groups.iter()
+            .find_map(|group| find_cycle(&parents, &group.id))
+            .and_then(|cycle| cycle.into_iter().min());
+        match breaker.and_then(|breaker| groups.iter_mut().find(|group| group.id == breaker)) {
+            Some(group) => group.parent_id = None,
+            None => return,
+        }
+    }
+}
+
+// 校验可以作为上级分组的分组
+fn validate_parent(groups: &[TodoGroup], parent_id: &str) -> Result<(), String> {
+    if parent_id == DEFAULT_GROUP_ID {
+        return Err("默认分组不能包含下级分组".to_string());
+    }
+    if !groups.iter().any(|group| group.id == parent_id) {
+        return Err(format!("未找到指定的上级分组: id='{}'", parent_id));
+    }
+    Ok(())
+}
+
+// 同一上级分组下，排在最后的位置
+fn next_sibling_order(groups: &[TodoGroup], parent_id: Option<&str>) -> i32 {
+    groups.iter()
+        .filter(|group| group.parent_id.as_deref() == parent_id)
+        .map(|group| group.order)
+        .max()
+        .unwrap_or(0) + 1
+}
+
+// 校验分组颜色，格式：#RRGGBB
+fn validate_color(color: &str) -> Result<(), String> {
+    let valid = color.len() == 7
+        && color.starts_with('#')
+        && color[1..].chars().all(|c| c.is_ascii_hexdigit());
+    if !valid {
+        return Err(format!("无效的分组颜色: {}", color));
+    }
+    Ok(())
+}
+
+// 把分组颜色解析为 RGB
+pub(crate) fn parse_color(color: &str) -> Option<(u8, u8, u8)> {
+    validate_color(color).ok()?;
+    let channel = |i: usize| u8::from_str_radix(&color[i..i + 2], 16).ok();
+    Some((channel(1)?, channel(3)?, channel(5)?))
+}
+
+// 校验分组设置，颜色和默认截止时间为空时表示清除
+pub(crate) fn validate_group_metadata(color: Option<&str>, default_priority: i32, default_deadline_offset: Option<&str>) -> Result<(), String> {
+    if let Some(color) = color.map(str::trim).filter(|color| !color.is_empty()) {
+        validate_color(color)?;
+    }
+    if !(0..=1).contains(&default_priority) {
+        return Err(format!("无效的优先级: {}", default_priority));
+    }
+    if let Some(offset) = default_deadline_offset.map(str::trim).filter(|offset| !offset.is_empty()) {
+        resolve_deadline_offset(offset, Local::now())?;
+    }
+    Ok(())
+}
+
+// 默认截止时间最多推后的天数
+const MAX_DEADLINE_OFFSET_DAYS: i64 = 3650;
+
+// 根据默认截止时间计算截止时间
+// 格式："+2d 18:00"（2天后18:00）、"+1w"（1周后当天结束）、"+3h"（3小时后）、"18:00"（今天18:00，已过则为明天）
+pub(crate) fn resolve_deadline_offset(offset: &str, now: DateTime<Local>) -> Result<i64, String> {
+    let invalid = || format!("无效的默认截止时间: {}", offset);
+
+    let mut days = None;
+    let mut hours = None;
+    let mut time = None;
+    for part in offset.split_whitespace() {
+        if let Some(amount) = part.strip_prefix('+') {
+            if days.is_some() || hours.is_some() || amount.len() < 2 {
+                return Err(invalid());
+            }
+            let (number, unit) = amount.split_at(amount.len() - 1);
+            let number: i64 = number.parse().map_err(|_| invalid())?;
+            match unit {
+                "d" => days = Some(number),
+                "w" => days = Some(number.checked_mul(7).ok_or_else(invalid)?),
+                "h" => hours = Some(number),
+                _ => return Err(invalid()),
+            }
+            let limit = if unit == "h" { MAX_DEADLINE_OFFSET_DAYS * 24 } else { MAX_DEADLINE_OFFSET_DAYS };
+            if days.or(hours).is_some_and(|amount| !(0..=limit).contains(&amount)) {
+                return Err(format!("默认截止时间最多为{}天后: {}", MAX_DEADLINE_OFFSET_DAYS, offset));
+            }
+        } else if time.is_none() {
+            time = Some(NaiveTime::parse_from_str(part, "%H:%M").map_err(|_| invalid())?);
+        } else {
+            return Err(invalid());
+        }
+    }
+
+    let deadline = match (days, hours, time) {
+        (None, Some(hours), None) => TimeDelta::try_hours(hours)
+            .and_then(|delta| now.checked_add_signed(delta))
+            .ok_or_else(invalid)?,
+        (None, None, Some(time)) => {
+            let today = now.date_naive().and_time(time);
+            let deadline = Local.from_local_datetime(&today).earliest().ok_or_else(invalid)?;
+            if deadline <= now {
+                deadline.checked_add_signed(TimeDelta::days(1)).ok_or_else(invalid)?
+            } else {
+                deadline
+            }
+        }
+        (Some(days), None, time) => {
+            // 未指定时间时截止到当天结束
+            let time = time.unwrap_or_else(|| NaiveTime::from_hms_opt(23, 59, 0).unwrap_or_default());
+            let date = TimeDelta::try_days(days)
+                .and_then(|delta| now.date_naive().checked_add_signed(delta))
+                .ok_or_else(invalid)?
+                .and_time(time);
+            Local.from_local_datetime(&date).earliest().ok_or_else(invalid)?
+        }
+        _ => return Err(invalid()),
+    };
+    Ok(deadline.timestamp())
+}
+
+// 解析截止时间：日期（today、tomorrow、今天、明天、后天或 2024-09-01）加可选的时间，
+// 或分组默认截止时间使用的格式（"+2d 18:00"、"+3h"、"18:00"）
+pub(crate) fn parse_deadline(due: &str, now: DateTime<Local>) -> Result<i64, String> {
+    let invalid = || format!("无效的截止时间: {}", due);
+    let mut parts = due.split_whitespace();
+    let first = parts.next().ok_or_else(invalid)?;
+
+    let date = match first.to_lowercase().as_str() {
+        "today" | "今天" => Some(now.date_naive()),
+        "tomorrow" | "明天" => now.date_naive().succ_opt(),
+        "后天" => now.date_naive().checked_add_signed(TimeDelta::days(2)),
+        _ => NaiveDate::parse_from_str(first, "%Y-%m-%d").ok(),
+    };
+    let Some(date) = date else {
+        return resolve_deadline_offset(due, now).map_err(|_| invalid());
+    };
+
+    // 未指定时间时截止到当天结束
+    let time = match (parts.next(), parts.next()) {
+        (None, _) => NaiveTime::from_hms_opt(23, 59, 0).unwrap_or_default(),
+        (Some(time), None) => NaiveTime::parse_from_str(time, "%H:%M").map_err(|_| invalid())?,
+        _ => return Err(invalid()),
+    };
+    Local.from_local_datetime(&date.and_time(time)).earliest()
+        .map(|deadline| deadline.timestamp())
+        .ok_or_else(invalid)
+}
+
+// 按ID或名称查找分组，名称相同的分组有多个时要求使用ID
+pub(crate) fn resolve_group(groups: &[TodoGroup], group: &str) -> Result<String, String> {
+    if let Some(found) = groups.iter().find(|g| g.id == group) {
+        return Ok(found.id.clone());
+    }
+    let matches: Vec<&TodoGroup> = groups.iter().filter(|g| g.name == group).collect();
+    match matches.as_slice() {
+        [found] => Ok(found.id.clone()),
+        [] => Err(format!("未找到分组: {}", group)),
+        _ => Err(format!("有多个名为「{}」的分组，请使用分组ID", group)),
+    }
+}
+
+// 把所属分组不存在的任务移到默认分组，返回移动的任务数
+pub(crate) fn reassign_orphaned_todos(data_dir: &Path) -> Result<usize, String> {
+    let group_ids: HashSet<String> = read_group_store(data_dir)?.groups.into_iter()
+        .map(|group| group.id)
+        .collect();
+    let has_orphans = read_todo_store(data_dir)?.todos.iter()
+        .any(|todo| !group_ids.contains(&todo.group_id));
+    if !has_orphans {
+        return Ok(0);
+    }
+
+    update_todo_store(data_dir, |todos, _| {
+        let mut moved = 0;
+        for todo in todos.iter_mut().filter(|todo| !group_ids.contains(&todo.group_id)) {
+            todo.group_id = DEFAULT_GROUP_ID.to_string();
+            moved += 1;
+        }
+        Ok(moved)
+    })
+}
+
+// Tauri 命令：新建分组，排在同一上级分组下所有分组之后
+#[tauri::command]
+pub async fn create_group(app: tauri::AppHandle, name: String, parent_id: Option<String>) -> Result<TodoGroup, String> {
+    ensure_unlocked()?;
+    let data_dir = get_data_dir(&app)?;
+    let name = validate_group_name(&name)?;
+
+    let group = update_group_store(&data_dir, |groups, _| {
+        if let Some(parent_id) = &parent_id {
+            validate_parent(groups, parent_id)?;
+        }
+        let group = TodoGroup {
+            id: Uuid::new_v4().to_string(),
+            name,
+            order: next_sibling_order(groups, parent_id.as_deref()),
+            parent_id,
+            ..Default::default()
+        };
+        groups.push(group.clone());
+        Ok(group)
+    })?;
+
+    let _ = app.emit("group-data-changed", ());
+    Ok(group)
+}
+
+// Tauri 命令：重命名分组
+#[tauri::command]
+pub async fn rename_group(app: tauri::AppHandle, group_id: String, name: String) -> Result<(), String> {
+    ensure_unlocked()?;
+    let data_dir = get_data_dir(&app)?;
+    let name = validate_group_name(&name)?;
+
+    update_group_store(&data_dir, |groups, _| {
+        find_group(groups, &group_id)?.name = name;
+        Ok(())
+    })?;
+
+    let _ = app.emit("group-data-changed", ());
+    Ok(())
+}
+
+// Tauri 命令：设置分组的颜色、图标、新任务默认优先级和默认截止时间，传入 None 表示清除
+#[tauri::command]
+pub async fn set_group_metadata(
+    app: tauri::AppHandle,
+    group_id: String,
+    color: Option<String>,
+    icon: Option<String>,
+    default_priority: i32,
+    default_deadline_offset: Option<String>,
+) -> Result<(), String> {
+    ensure_unlocked()?;
+    let data_dir = get_data_dir(&app)?;
+
+    let non_empty = |value: Option<String>| value.map(|v| v.trim().to_string()).filter(|v| !v.is_empty());
+    let color = non_empty(color);
+    let icon = non_empty(icon);
+    let default_deadline_offset = non_empty(default_deadline_offset);
+    validate_group_metadata(color.as_deref(), default_priority, default_deadline_offset.as_deref())?;
+
+    update_group_store(&data_dir, |groups, _| {
+        let group = find_group(groups, &group_id)?;
+        group.color = color;
+        group.icon = icon;
+        group.default_priority = default_priority;
+        group.default_deadline_offset = default_deadline_offset;
+        Ok(())
+    })?;
+
+    let _ = app.emit("group-data-changed", ());
+    Ok(())
+}
+
+// Tauri 命令：把分组（连同所有下级分组）移到另一个分组下，parent_id 为 None 时移为顶层分组
+#[tauri::command]
+pub async fn move_group(app: tauri::AppHandle, group_id: String, parent_id: Option<String>) -> Result<(), String> {
+    ensure_unlocked()?;
+    if group_id == DEFAULT_GROUP_ID {
+        return Err("默认分组不能移动到其他分组下".to_string());
+    }
+    let data_dir = get_data_dir(&app)?;
+
+    update_group_store(&data_dir, |groups, _| {
+        find_group(groups, &group_id)?;
+        if let Some(parent_id) = &parent_id {
+            validate_parent(groups, parent_id)?;
+            if subtree_ids(groups, &group_id).contains(parent_id) {
+                return Err("不能把分组移动到它自己或它的下级分组下".to_string());
+            }
+        }
+
+        let order = next_sibling_order(groups, parent_id.as_deref());
+        let group = find_group(groups, &group_id)?;
+        if group.parent_id != parent_id {
+            group.parent_id = parent_id;
+            group.order = order;
+        }
+        Ok(())
+    })?;
+
+    let _ = app.emit("group-data-changed", ());
+    Ok(())
+}
+
+// Tauri 命令：删除分组，按 strategy 处理分组中的任务（包括已完成的任务）
+// 下级分组移到被删除分组的上级分组下，默认分组不能删除
+#[tauri::command]
+pub async fn delete_group(app: tauri::AppHandle, group_id: String, strategy: GroupDeleteStrategy) -> Result<(), String> {
+    ensure_unlocked()?;
+    if group_id == DEFAULT_GROUP_ID {
+        return Err("默认分组不能删除".to_string());
+    }
+    let data_dir = get_data_dir(&app)?;
+
+    let groups = read_group_store(&data_dir)?.groups;
+    if !groups.iter().any(|group| group.id == group_id) {
+        return Err(format!("未找到指定的分组: id='{}'", group_id));
+    }
+    if let GroupDeleteStrategy::MoveToGroup { target_group_id } = &strategy {
+        if *target_group_id == group_id || !groups.iter().any(|group| group.id == *target_group_id) {
+            return Err(format!("无效的目标分组: id='{}'", target_group_id));
+        }
+    }
+
+    // 任务移动到的目标分组，None 表示把这些任务移到归档中
+    let target = match &strategy {
+        GroupDeleteStrategy::MoveToDefault => Some(DEFAULT_GROUP_ID),
+        GroupDeleteStrategy::MoveToGroup { target_group_id } => Some(target_group_id.as_str()),
+        GroupDeleteStrategy::DeleteTodos => None,
+    };
+
+    // 先处理任务再删除分组，中途失败时不会留下所属分组不存在的任务
+    let affected = update_todo_store(&data_dir, |todos, _| {
+        let target = match target {
+            Some(target) => target,
+            None => {
+                // 先写归档文件再从任务列表中移除，误删分组后仍可从归档中恢复任务
+                let (removed, kept): (Vec<Todo>, Vec<Todo>) = std::mem::take(todos)
+                    .into_iter()
+                    .partition(|todo| todo.group_id == group_id);
+                *todos = kept;
+                return archive_todos(&data_dir, removed);
+            }
+        };
+
+        // 移入的任务保持原有顺序，排在目标分组已有任务之后
+        let next_order = todos.iter()
+            .filter(|todo| todo.group_id == target)
+            .map(|todo| todo.order)
+            .max()
+            .unwrap_or(0) + 1;
+        let mut moved = 0;
+        for todo in todos.iter_mut().filter(|todo| todo.group_id == group_id) {
+            todo.group_id = target.to_string();
+            todo.order += next_order;
+            moved += 1;
+        }
+        Ok(moved)
+    })?;
+
+    update_group_store(&data_dir, |groups, _| {
+        let parent_id = find_group(groups, &group_id)?.parent_id.clone();
+        for child in groups.iter_mut().filter(|group| group.parent_id.as_deref() == Some(group_id.as_str())) {
+            child.parent_id = parent_id.clone();
+        }
+        groups.retain(|group| group.id != group_id);
+        Ok(())
+    })?;
+
+    log::info!("已删除分组 {}，处理了 {} 个任务", group_id, affected);
+    let _ = app.emit("group-data-changed", ());
+    let _ = app.emit("todo-data-changed", ());
+    Ok(())
+}
+
+// Tauri 命令：按给定的分组ID顺序重新排序，未列出的分组保持原有相对顺序排在后面
+// 同一上级分组下的分组按 order 排列
+#[tauri::command]
+pub async fn reorder_groups(app: tauri::AppHandle, group_ids: Vec<String>) -> Result<(), String> {
+    ensure_unlocked()?;
+    let data_dir = get_data_dir(&app)?;
+
+    update_group_store(&data_dir, |groups, _| {
+        if let Some(unknown) = group_ids.iter().find(|id| !groups.iter().any(|group| group.id == **id)) {
+            return Err(format!("未找到指定的分组: id='{}'", unknown));
+        }
+
+        groups.sort_by_key(|group| {
+            let position = group_ids.iter().position(|id| *id == group.id);
+            (position.is_none(), position, group.order)
+        });
+        for (order, group) in groups.iter_mut().enumerate() {
+            group.order = order as i32;
+        }
+        Ok(())
+    })?;
+
+    let _ = app.emit("group-data-changed", ());
+    Ok(())
+}
+
+// Tauri 命令：折叠或展开分组
+#[tauri::command]
+pub async fn set_group_collapsed(app: tauri::AppHandle, group_id: String, collapsed: bool) -> Result<(), String> {
+    ensure_unlocked()?;
+    let data_dir = get_data_dir(&app)?;
+
+    update_group_store(&data_dir, |groups, _| {
+        find_group(groups, &group_id)?.collapsed = collapsed;
+        Ok(())
+    })?;
+
+    let _ = app.emit("group-data-changed", ());
+    Ok(())
+}
+
+// Tauri 命令：获取各分组的未完成和已完成任务数（按分组顺序）
+#[tauri::command]
+pub async fn get_group_counts(app: tauri::AppHandle) -> Result<Vec<GroupTodoCounts>, String> {
+    ensure_unlocked()?;
+    let data_dir = get_data_dir(&app)?;
+    let mut groups = read_group_store(&data_dir)?.groups;
+    let todos = read_todo_store(&data_dir)?.todos;
+    groups.sort_by_key(|group| group.order);
+
+    // 各分组直接包含的 (未完成数, 已完成数)
+    let mut direct: HashMap<&str, (u32, u32)> = HashMap::new();
+    for todo in &todos {
+        let counts = direct.entry(todo.group_id.as_str()).or_default();
+        if todo.completed {
+            counts.1 += 1;
+        } else {
+            counts.0 += 1;
+        }
+    }
+
+    Ok(groups.iter()
+        .map(|group| {
+            let (pending, completed) = direct.get(group.id.as_str()).copied().unwrap_or_default();
+            let (total_pending, total_completed) = subtree_ids(&groups, &group.id).iter()
+                .filter_map(|id| direct.get(id.as_str()))
+                .fold((0, 0), |(p, c), (pending, completed)| (p + pending, c + completed));
+            GroupTodoCounts {
+                group_id: group.id.clone(),
+                pending,
+                completed,
+                total_pending,
+                total_completed,
+            }
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn now() -> DateTime<Local> {
+        Local.with_ymd_and_hms(2024, 9, 1, 12, 0, 0).unwrap()
+    }
+
+    fn at(year: i32, month: u32, day: u32, hour: u32, minute: u32) -> i64 {
+        Local.with_ymd_and_hms(year, month, day, hour, minute, 0).unwrap().timestamp()
+    }
+
+    #[test]
+    fn resolves_deadline_offsets() {
+        assert_eq!(resolve_deadline_offset("+2d 18:00", now()), Ok(at(2024, 9, 3, 18, 0)));
+        assert_eq!(resolve_deadline_offset("+1w", now()), Ok(at(2024, 9, 8, 23, 59)));
+        assert_eq!(resolve_deadline_offset("+3h", now()), Ok(now().timestamp() + 3 * 3600));
+        assert_eq!(resolve_deadline_offset("18:00", now()), Ok(at(2024, 9, 1, 18, 0)));
+        assert_eq!(resolve_deadline_offset("09:00", now()), Ok(at(2024, 9, 2, 9, 0)));
+        assert_eq!(parse_deadline("明天 09:30", now()), Ok(at(2024, 9, 2, 9, 30)));
+        assert_eq!(parse_deadline("2024-10-01", now()), Ok(at(2024, 10, 1, 23, 59)));
+    }
+
+    #[test]
+    fn rejects_huge_or_negative_offsets() {
+        for offset in ["+100000000d", "+10000000000h", "+9223372036854775807w", "+3651d", "+-1d"] {
+            assert!(resolve_deadline_offset(offset, now()).is_err(), "{}", offset);
+            assert!(parse_deadline(offset, now()).is_err(), "{}", offset);
+        }
+        assert!(resolve_deadline_offset("+3650d", now()).is_ok());
+    }
+}
@@ -0,0 +1,268 @@
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+use git2::{IndexAddOption, Repository, Signature, Sort};
+use serde::de::DeserializeOwned;
+use serde_json;
+use tauri::Emitter;
+
+use crate::models::{GroupData, TodoDataWithGroups, TodoHistoryEntry};
+use crate::system::app_lock::ensure_unlocked;
+use super::encryption::decrypt_contents;
+use super::todo_data::{get_data_dir, update_group_store, update_todo_store};
+
+const TODO_FILE: &str = "todos_with_groups.json";
+const GROUP_FILE: &str = "groups.json";
+
+// 不需要记录历史的文件：窗口位置和同步状态变化频繁且无需回溯，备份本身就是历史副本
+// 设置文件中有接口访问令牌和PIN等敏感信息，不能留在历史记录中
+const IGNORED: [&str; 6] = [
+    "window_position.json",
+    "app_settings.json",
+    "webdav_state.json",
+    "caldav_state.json",
+    "sync_conflicts/",
+    "backups/",
+];
+
+fn git_error(action: &str, e: git2::Error) -> String {
+    format!("{}失败: {}", action, e.message())
+}
+
+// 打开数据目录中的历史记录仓库，不存在时创建
+fn open_or_init(data_dir: &Path) -> Result<Repository, String> {
+    if let Ok(repo) = Repository::open(data_dir) {
+        return Ok(repo);
+    }
+
+    Repository::init(data_dir).map_err(|e| git_error("创建历史记录仓库", e))
+}
+
+// 确保 .gitignore 包含所有不需要记录历史的文件，旧版本创建的 .gitignore 会补上缺少的条目
+fn ensure_gitignore(data_dir: &Path) -> Result<(), String> {
+    let gitignore_path = data_dir.join(".gitignore");
+    let mut contents = fs::read_to_string(&gitignore_path).unwrap_or_default();
+    let missing: Vec<&str> = IGNORED.into_iter()
+        .filter(|pattern| !contents.lines().any(|line| line.trim() == *pattern))
+        .collect();
+    if missing.is_empty() {
+        return Ok(());
+    }
+
+    if !contents.is_empty() && !contents.ends_with('\n') {
+        contents.push('\n');
+    }
+    for pattern in missing {
+        contents.push_str(pattern);
+        contents.push('\n');
+    }
+    fs::write(&gitignore_path, contents)
+        .map_err(|e| format!("写入.gitignore失败: {}", e))
+}
+
+// 把数据目录的当前状态提交到历史记录，没有变化时返回 None
+pub fn commit_data_dir(data_dir: &Path, message: &str) -> Result<Option<String>, String> {
+    let repo = open_or_init(data_dir)?;
+    ensure_gitignore(data_dir)?;
+
+    // 已经被记录过的忽略文件（例如旧版本记录的设置文件）从索引中移除，之后的版本不再包含
+    let mut index = repo.index().map_err(|e| git_error("读取历史记录索引", e))?;
+    let ignored: Vec<&str> = IGNORED.iter().map(|pattern| pattern.trim_end_matches('/')).collect();
+    index.remove_all(&ignored, None)
+        .and_then(|_| index.add_all(["*"], IndexAddOption::DEFAULT, None))
+        .and_then(|_| index.update_all(["*"], None))
+        .and_then(|_| index.write())
+        .map_err(|e| git_error("更新历史记录索引", e))?;
+    let tree_id = index.write_tree().map_err(|e| git_error("写入历史记录", e))?;
+
+    let parent = repo.head().ok().and_then(|head| head.peel_to_commit().ok());
+    if parent.as_ref().is_some_and(|parent| parent.tree_id() == tree_id) {
+        return Ok(None);
+    }
+
+    let tree = repo.find_tree(tree_id).map_err(|e| git_error("写入历史记录", e))?;
+    let signature = Signature::now("DeskHive", "deskhive@localhost")
+        .map_err(|e| git_error("写入历史记录", e))?;
+    let parents: Vec<&git2::Commit> = parent.iter().collect();
+    let commit_id = repo.commit(Some("HEAD"), &signature, &signature, message, &tree, &parents)
+        .map_err(|e| git_error("提交历史记录", e))?;
+
+    Ok(Some(commit_id.to_string()))
+}
+
+// 删除历史记录仓库（启用加密后不能保留明文的历史），仓库存在时返回 true
+pub fn purge_history(data_dir: &Path) -> Result<bool, String> {
+    let git_dir = match Repository::open(data_dir) {
+        Ok(repo) => repo.path().to_path_buf(),
+        Err(_) => return Ok(false),
+    };
+    fs::remove_dir_all(&git_dir)
+        .map_err(|e| format!("清除修改历史失败: {}", e))?;
+    Ok(true)
+}
+
+// 记录一批文件变化，由数据目录监听在合并事件后调用
+pub fn record_changes(data_dir: &Path, changed_files: &HashSet<String>) {
+    let mut names: Vec<&str> = changed_files.iter()
+        .map(|name| name.as_str())
+        .filter(|name| !name.starts_with('.'))
+        .collect();
+    if names.is_empty() {
+        return;
+    }
+    names.sort();
+
+    match commit_data_dir(data_dir, &format!("更新 {}", names.join(", "))) {
+        Ok(Some(commit)) => log::info!("已记录数据修改历史: {}", commit),
+        Ok(None) => {}
+        Err(e) => log::error!("记录数据修改历史失败: {}", e),
+    }
+}
+
+// 读取某次提交中的数据文件（加密的文件会被解密），文件不存在时返回 None
+fn read_file_at<T: DeserializeOwned>(repo: &Repository, commit: &git2::Commit, file_name: &str) -> Result<Option<T>, String> {
+    let tree = commit.tree().map_err(|e| git_error("读取历史记录", e))?;
+    let entry = match tree.get_path(Path::new(file_name)) {
+        Ok(entry) => entry,
+        Err(_) => return Ok(None),
+    };
+    let blob = repo.find_blob(entry.id()).map_err(|e| git_error("读取历史记录", e))?;
+
+    let contents = String::from_utf8_lossy(blob.content());
+    serde_json::from_str(&decrypt_contents(&contents)?)
+        .map(Some)
+        .map_err(|e| format!("解析历史版本中的 {} 失败: {}", file_name, e))
+}
+
+// Tauri 命令：获取任务的修改历史（最新的在前）
+#[tauri::command]
+pub async fn get_todo_history(app: tauri::AppHandle, todo_id: String) -> Result<Vec<TodoHistoryEntry>, String> {
+    ensure_unlocked()?;
+    let data_dir = get_data_dir(&app)?;
+    let repo = match Repository::open(&data_dir) {
+        Ok(repo) => repo,
+        Err(_) => return Ok(Vec::new()),
+    };
+    if repo.head().is_err() {
+        return Ok(Vec::new());
+    }
+
+    let mut revwalk = repo.revwalk().map_err(|e| git_error("读取历史记录", e))?;
+    revwalk.set_sorting(Sort::TIME | Sort::REVERSE)
+        .and_then(|_| revwalk.push_head())
+        .map_err(|e| git_error("读取历史记录", e))?;
+
+    // 从最早的提交开始，只保留任务内容发生变化的版本
+    let mut history = Vec::new();
+    let mut previous: Option<serde_json::Value> = None;
+    for commit_id in revwalk {
+        let commit_id = commit_id.map_err(|e| git_error("读取历史记录", e))?;
+        let commit = repo.find_commit(commit_id).map_err(|e| git_error("读取历史记录", e))?;
+
+        // 损坏的历史版本直接跳过
+        let todo_data: TodoDataWithGroups = match read_file_at(&repo, &commit, TODO_FILE) {
+            Ok(Some(todo_data)) => todo_data,
+            Ok(None) => TodoDataWithGroups::default(),
+            Err(_) => continue,
+        };
+        let todo = todo_data.todos.into_iter().find(|todo| todo.id == todo_id);
+        let value = todo.as_ref().and_then(|todo| serde_json::to_value(todo).ok());
+        if value == previous {
+            continue;
+        }
+
+        previous = value;
+        history.push(TodoHistoryEntry {
+            commit: commit_id.to_string(),
+            timestamp: commit.time().seconds(),
+            message: commit.message().unwrap_or_default().to_string(),
+            todo,
+        });
+    }
+
+    history.reverse();
+    Ok(history)
+}
+
+// Tauri 命令：把任务和分组数据恢复到某次提交时的状态
+// 恢复本身作为一次新的修改保存，之后的历史不会丢失，也会通过同步传播到其他设备
+#[tauri::command]
+pub async fn restore_data_from_history(app: tauri::AppHandle, commit: String) -> Result<(), String> {
+    ensure_unlocked()?;
+    let data_dir = get_data_dir(&app)?;
+    let repo = Repository::open(&data_dir).map_err(|e| git_error("打开历史记录仓库", e))?;
+    let target = repo.revparse_single(&commit)
+        .and_then(|object| object.peel_to_commit())
+        .map_err(|e| git_error(&format!("查找提交 {} ", commit), e))?;
+
+    let todo_data: TodoDataWithGroups = read_file_at(&repo, &target, TODO_FILE)?.unwrap_or_default();
+    let group_data: Option<GroupData> = read_file_at(&repo, &target, GROUP_FILE)?;
+
+    if let Some(group_data) = group_data {
+        update_group_store(&data_dir, |groups, _| {
+            let current: HashSet<String> = groups.iter().map(|group| group.id.clone()).collect();
+            *groups = group_data.groups;
+            // 恢复已删除的数据项时重新记录修改时间，否则同步时会被删除记录再次删除
+            for group in groups.iter_mut().filter(|group| !current.contains(&group.id)) {
+                group.updated_at = 0;
+                group.field_updated_at.clear();
+            }
+            Ok(())
+        })?;
+        let _ = app.emit("group-data-changed", ());
+    }
+
+    update_todo_store(&data_dir, |todos, _| {
+        let current: HashSet<String> = todos.iter().map(|todo| todo.id.clone()).collect();
+        *todos = todo_data.todos;
+        for todo in todos.iter_mut().filter(|todo| !current.contains(&todo.id)) {
+            todo.updated_at = 0;
+            todo.field_updated_at.clear();
+        }
+        Ok(())
+    })?;
+    let _ = app.emit("todo-data-changed", ());
+
+    log::info!("已将数据恢复到提交 {}", target.id());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tracked_files(data_dir: &Path) -> Vec<String> {
+        let repo = Repository::open(data_dir).unwrap();
+        let tree = repo.head().unwrap().peel_to_tree().unwrap();
+        tree.iter().filter_map(|entry| entry.name().map(str::to_string)).collect()
+    }
+
+    #[test]
+    fn untracks_settings_committed_by_older_versions() {
+        let data_dir = std::env::temp_dir().join(format!("deskhive-history-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&data_dir).unwrap();
+        Repository::init(&data_dir).unwrap();
+        // 旧版本的 .gitignore 没有忽略设置文件
+        fs::write(data_dir.join(".gitignore"), "window_position.json\nbackups/").unwrap();
+        fs::write(data_dir.join("app_settings.json"), r#"{"api_token":"secret"}"#).unwrap();
+        fs::write(data_dir.join(TODO_FILE), r#"{"todos":[]}"#).unwrap();
+
+        let repo = Repository::open(&data_dir).unwrap();
+        let mut index = repo.index().unwrap();
+        index.add_path(Path::new("app_settings.json")).unwrap();
+        index.write().unwrap();
+        drop(index);
+        drop(repo);
+
+        commit_data_dir(&data_dir, "第一次提交").unwrap();
+        assert_eq!(tracked_files(&data_dir), [".gitignore", TODO_FILE]);
+
+        let gitignore = fs::read_to_string(data_dir.join(".gitignore")).unwrap();
+        let lines: Vec<&str> = gitignore.lines().collect();
+        assert_eq!(lines.len(), IGNORED.len());
+        assert!(lines.contains(&"app_settings.json"));
+
+        fs::write(data_dir.join("app_settings.json"), r#"{"api_token":"changed"}"#).unwrap();
+        assert_eq!(commit_data_dir(&data_dir, "只修改设置").unwrap(), None);
+    }
+}
@@ -0,0 +1,383 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDate, TimeZone, Weekday};
+use serde::{Deserialize, Serialize};
+
+use crate::models::HolidayDay;
+use crate::system::app_lock::ensure_unlocked;
+use super::storage::{read_data_file, write_json_file};
+use super::todo_data::get_data_dir;
+
+// 内置的法定节假日安排（国务院办公厅每年发布）：名称、放假开始日期、放假结束日期、调休上班的日期
+// 更新的安排可以通过 import_holiday_calendar 导入，导入的数据优先于内置数据
+const BUILTIN_HOLIDAYS: [(&str, &str, &str, &[&str]); 20] = [
+    // 2024年
+    ("元旦", "2024-01-01", "2024-01-01", &[]),
+    ("春节", "2024-02-10", "2024-02-17", &["2024-02-04", "2024-02-18"]),
+    ("清明节", "2024-04-04", "2024-04-06", &["2024-04-07"]),
+    ("劳动节", "2024-05-01", "2024-05-05", &["2024-04-28", "2024-05-11"]),
+    ("端午节", "2024-06-10", "2024-06-10", &[]),
+    ("中秋节", "2024-09-15", "2024-09-17", &["2024-09-14"]),
+    ("国庆节", "2024-10-01", "2024-10-07", &["2024-09-29", "2024-10-12"]),
+    // 2025年
+    ("元旦", "2025-01-01", "2025-01-01", &[]),
+    ("春节", "2025-01-28", "2025-02-04", &["2025-01-26", "2025-02-08"]),
+    ("清明节", "2025-04-04", "2025-04-06", &[]),
+    ("劳动节", "2025-05-01", "2025-05-05", &["2025-04-27"]),
+    ("端午节", "2025-05-31", "2025-06-02", &[]),
+    ("国庆节、中秋节", "2025-10-01", "2025-10-08", &["2025-09-28", "2025-10-11"]),
+    // 2026年
+    ("元旦", "2026-01-01", "2026-01-03", &["2026-01-04"]),
+    ("春节", "2026-02-15", "2026-02-23", &["2026-02-14", "2026-02-28"]),
+    ("清明节", "2026-04-04", "2026-04-06", &[]),
+    ("劳动节", "2026-05-01", "2026-05-05", &["2026-05-09"]),
+    ("端午节", "2026-06-19", "2026-06-21", &[]),
+    ("中秋节", "2026-09-25", "2026-09-27", &[]),
+    ("国庆节", "2026-10-01", "2026-10-07", &["2026-09-20", "2026-10-10"]),
+];
+
+// 用户导入的节假日安排保存的文件
+const OVERRIDES_FILE: &str = "holiday_overrides.json";
+
+// ICS 文件中一个事件最多包含的天数，防止错误的数据生成大量日期
+const MAX_EVENT_DAYS: i64 = 31;
+
+// 查找上一个或下一个工作日时最多查找的天数
+const MAX_SEARCH_DAYS: i64 = 60;
+
+// 某一天的节假日安排：名称，是否放假
+type DayEntry = (String, bool);
+
+static BUILTIN: OnceLock<HashMap<NaiveDate, DayEntry>> = OnceLock::new();
+
+// 用户导入的节假日安排，None 表示还没有读取
+static OVERRIDES: Mutex<Option<HashMap<NaiveDate, DayEntry>>> = Mutex::new(None);
+
+// 节假日安排文件的内容，兼容 holiday-cn 的格式（{"days": [{"name", "date", "isOffDay"}]}）和直接的数组
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum HolidayFile {
+    Calendar { days: Vec<HolidayDay> },
+    Days(Vec<HolidayDay>),
+}
+
+// 某一天的类型
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum DayKind {
+    Workday,
+    Weekend,
+    Holiday(String),          // 法定节假日放假
+    AdjustedWorkday(String),  // 调休上班
+}
+
+impl DayKind {
+    pub fn is_workday(&self) -> bool {
+        matches!(self, DayKind::Workday | DayKind::AdjustedWorkday(_))
+    }
+}
+
+fn parse_date(date: &str) -> Result<NaiveDate, String> {
+    NaiveDate::parse_from_str(date.trim(), "%Y-%m-%d")
+        .map_err(|e| format!("无效的日期 {}，格式应为 YYYY-MM-DD: {}", date, e))
+}
+
+fn builtin() -> &'static HashMap<NaiveDate, DayEntry> {
+    BUILTIN.get_or_init(|| {
+        let mut days = HashMap::new();
+        for (name, start, end, workdays) in BUILTIN_HOLIDAYS {
+            if let (Ok(start), Ok(end)) = (parse_date(start), parse_date(end)) {
+                for date in start.iter_days().take_while(|date| *date <= end) {
+                    days.insert(date, (name.to_string(), true));
+                }
+            }
+            for workday in workdays.iter().filter_map(|workday| parse_date(workday).ok()) {
+                days.insert(workday, (name.to_string(), false));
+            }
+        }
+        days
+    })
+}
+
+fn overrides_path(data_dir: &Path) -> PathBuf {
+    data_dir.join(OVERRIDES_FILE)
+}
+
+fn read_overrides(data_dir: &Path) -> Result<Vec<HolidayDay>, String> {
+    let path = overrides_path(data_dir);
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let contents = read_data_file(&path)?;
+    match serde_json::from_str(&contents).map_err(|e| format!("解析节假日数据失败: {}", e))? {
+        HolidayFile::Calendar { days } | HolidayFile::Days(days) => Ok(days),
+    }
+}
+
+fn to_entries(days: &[HolidayDay]) -> HashMap<NaiveDate, DayEntry> {
+    days.iter()
+        .filter_map(|day| parse_date(&day.date).ok().map(|date| (date, (day.name.clone(), day.is_off_day))))
+        .collect()
+}
+
+// 读取用户导入的节假日安排（只在第一次调用时读取）
+pub(crate) fn init_holiday_calendar(data_dir: &Path) {
+    let mut overrides = OVERRIDES.lock().unwrap_or_else(|e| e.into_inner());
+    if overrides.is_some() {
+        return;
+    }
+    let days = read_overrides(data_dir).unwrap_or_else(|e| {
+        log::error!("读取导入的节假日安排失败: {}", e);
+        Vec::new()
+    });
+    *overrides = Some(to_entries(&days));
+}
+
+// 判断某一天的类型：导入的安排优先，其次是内置的安排，都没有时按周末判断
+pub(crate) fn day_kind(date: NaiveDate) -> DayKind {
+    let overridden = OVERRIDES.lock().ok()
+        .and_then(|overrides| overrides.as_ref().and_then(|overrides| overrides.get(&date).cloned()));
+    match overridden.or_else(|| builtin().get(&date).cloned()) {
+        Some((name, true)) => DayKind::Holiday(name),
+        Some((name, false)) => DayKind::AdjustedWorkday(name),
+        None if matches!(date.weekday(), Weekday::Sat | Weekday::Sun) => DayKind::Weekend,
+        None => DayKind::Workday,
+    }
+}
+
+pub(crate) fn is_workday_date(date: NaiveDate) -> bool {
+    day_kind(date).is_workday()
+}
+
+// 某一天之后（不含当天）的第一个工作日
+pub(crate) fn next_workday_after(date: NaiveDate) -> NaiveDate {
+    (1..=MAX_SEARCH_DAYS)
+        .map(|days| date + Duration::days(days))
+        .find(|date| is_workday_date(*date))
+        .unwrap_or(date + Duration::days(1))
+}
+
+// 某一天之前（不含当天）的最后一个工作日
+pub(crate) fn previous_workday_before(date: NaiveDate) -> NaiveDate {
+    (1..=MAX_SEARCH_DAYS)
+        .map(|days| date - Duration::days(days))
+        .find(|date| is_workday_date(*date))
+        .unwrap_or(date - Duration::days(1))
+}
+
+// 把时间移到工作日的同一时刻：不是工作日时，later 为 true 顺延到下一个工作日，否则提前到上一个工作日
+pub(crate) fn move_to_workday(time: DateTime<Local>, later: bool) -> DateTime<Local> {
+    let date = time.date_naive();
+    if is_workday_date(date) {
+        return time;
+    }
+    let workday = if later { next_workday_after(date) } else { previous_workday_before(date) };
+    Local.from_local_datetime(&workday.and_time(time.time())).earliest().unwrap_or(time)
+}
+
+// 展开 ICS 中折行的内容行
+fn unfold_ics(contents: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for line in contents.lines() {
+        let line = line.trim_end_matches('\r');
+        match (line.strip_prefix(' ').or_else(|| line.strip_prefix('\t')), lines.last_mut()) {
+            (Some(continuation), Some(last)) => last.push_str(continuation),
+            _ => lines.push(line.to_string()),
+        }
+    }
+    lines
+}
+
+// 解析 ICS 中的日期，如 20251001 或 20251001T000000
+fn parse_ics_date(value: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(value.get(..8)?, "%Y%m%d").ok()
+}
+
+// 解析 ICS 日历：每个事件是一段假期，标题含"班"的事件（如"补班"、"调休上班"）是调休上班的日期
+fn parse_ics(contents: &str) -> Result<Vec<HolidayDay>, String> {
+    let mut days = Vec::new();
+    let mut event: Option<(Option<NaiveDate>, Option<NaiveDate>, String)> = None;
+
+    for line in unfold_ics(contents) {
+        let Some((property, value)) = line.split_once(':') else { continue };
+        let name = property.split(';').next().unwrap_or_default().to_ascii_uppercase();
+        match (name.as_str(), event.as_mut()) {
+            ("BEGIN", _) if value.eq_ignore_ascii_case("VEVENT") => event = Some((None, None, String::new())),
+            ("DTSTART", Some(event)) => event.0 = parse_ics_date(value),
+            ("DTEND", Some(event)) => event.1 = parse_ics_date(value),
+            ("SUMMARY", Some(event)) => event.2 = value.replace("\\,", ",").replace("\\;", ";").trim().to_string(),
+            ("END", Some(_)) if value.eq_ignore_ascii_case("VEVENT") => {
+                let Some((Some(start), end, summary)) = event.take() else {
+                    return Err("ICS 事件缺少开始日期".to_string());
+                };
+                // 全天事件的结束日期不包含在事件内
+                let end = end.filter(|end| *end > start).unwrap_or(start + Duration::days(1));
+                if (end - start).num_days() > MAX_EVENT_DAYS {
+                    return Err(format!("ICS 事件 {} 超过 {} 天", summary, MAX_EVENT_DAYS));
+                }
+                let is_off_day = !summary.contains('班');
+                let name = summary.trim_end_matches(['休', '班', '补', '(', ')', '（', '）', ' ']).to_string();
+                let name = if name.is_empty() { summary } else { name };
+                for date in start.iter_days().take_while(|date| *date < end) {
+                    days.push(HolidayDay { date: date.format("%Y-%m-%d").to_string(), name: name.clone(), is_off_day });
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(days)
+}
+
+// 解析 JSON 格式的节假日安排
+fn parse_json(contents: &str) -> Result<Vec<HolidayDay>, String> {
+    let days = match serde_json::from_str(contents).map_err(|e| format!("解析节假日JSON失败: {}", e))? {
+        HolidayFile::Calendar { days } | HolidayFile::Days(days) => days,
+    };
+    for day in &days {
+        parse_date(&day.date)?;
+    }
+    Ok(days)
+}
+
+// Tauri 命令：判断某一天（格式：2024-09-17）是否为工作日，考虑法定节假日和调休
+#[tauri::command]
+pub async fn is_workday(date: String) -> Result<bool, String> {
+    Ok(is_workday_date(parse_date(&date)?))
+}
+
+// Tauri 命令：获取某一天之后的第一个工作日
+#[tauri::command]
+pub async fn next_workday(date: String) -> Result<String, String> {
+    Ok(next_workday_after(parse_date(&date)?).format("%Y-%m-%d").to_string())
+}
+
+// Tauri 命令：导入节假日安排（JSON 或 ICS 文件），与已导入的安排合并，同一天以新导入的为准
+// 返回导入的天数
+#[tauri::command]
+pub async fn import_holiday_calendar(app: tauri::AppHandle, path: String) -> Result<usize, String> {
+    ensure_unlocked()?;
+    let contents = std::fs::read_to_string(&path)
+        .map_err(|e| format!("读取节假日文件失败: {}", e))?;
+    let imported = if contents.trim_start().starts_with("BEGIN:VCALENDAR") {
+        parse_ics(&contents)?
+    } else {
+        parse_json(&contents)?
+    };
+    if imported.is_empty() {
+        return Err("文件中没有节假日安排".to_string());
+    }
+
+    let data_dir = get_data_dir(&app)?;
+    let mut days: HashMap<String, HolidayDay> = read_overrides(&data_dir)?.into_iter()
+        .map(|day| (day.date.clone(), day))
+        .collect();
+    let count = imported.len();
+    days.extend(imported.into_iter().map(|day| (day.date.clone(), day)));
+    let mut days: Vec<HolidayDay> = days.into_values().collect();
+    days.sort_by(|a, b| a.date.cmp(&b.date));
+
+    write_json_file(&overrides_path(&data_dir), &HolidayFile::Calendar { days: days.clone() })?;
+    *OVERRIDES.lock().unwrap_or_else(|e| e.into_inner()) = Some(to_entries(&days));
+    log::info!("已导入 {} 天的节假日安排", count);
+    Ok(count)
+}
+
+// Tauri 命令：清除导入的节假日安排，恢复使用内置的安排
+#[tauri::command]
+pub async fn clear_holiday_overrides(app: tauri::AppHandle) -> Result<(), String> {
+    ensure_unlocked()?;
+    let path = overrides_path(&get_data_dir(&app)?);
+    if path.exists() {
+        std::fs::remove_file(&path).map_err(|e| format!("删除节假日数据失败: {}", e))?;
+    }
+    *OVERRIDES.lock().unwrap_or_else(|e| e.into_inner()) = Some(HashMap::new());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(date: &str) -> NaiveDate {
+        parse_date(date).unwrap()
+    }
+
+    // 日期、名称、是否放假
+    fn summary(days: &[HolidayDay]) -> Vec<(&str, &str, bool)> {
+        days.iter().map(|day| (day.date.as_str(), day.name.as_str(), day.is_off_day)).collect()
+    }
+
+    #[test]
+    fn builtin_days_and_weekends() {
+        assert_eq!(day_kind(date("2024-10-01")), DayKind::Holiday("国庆节".to_string()));
+        assert_eq!(day_kind(date("2024-10-05")), DayKind::Holiday("国庆节".to_string()));
+        assert_eq!(day_kind(date("2024-09-29")), DayKind::AdjustedWorkday("国庆节".to_string()));
+        assert_eq!(day_kind(date("2024-09-28")), DayKind::Weekend);
+        assert_eq!(day_kind(date("2024-10-08")), DayKind::Workday);
+        assert!(is_workday_date(date("2024-09-29")));
+        assert!(!is_workday_date(date("2024-10-01")));
+    }
+
+    #[test]
+    fn finds_next_and_previous_workdays() {
+        // 国庆假期之后的第一个工作日
+        assert_eq!(next_workday_after(date("2024-09-30")), date("2024-10-08"));
+        // 周六调休上班
+        assert_eq!(next_workday_after(date("2024-10-11")), date("2024-10-12"));
+        assert_eq!(next_workday_after(date("2024-09-27")), date("2024-09-29"));
+        assert_eq!(previous_workday_before(date("2024-10-08")), date("2024-09-30"));
+
+        let saturday = Local.with_ymd_and_hms(2024, 10, 5, 18, 0, 0).unwrap();
+        assert_eq!(move_to_workday(saturday, true), Local.with_ymd_and_hms(2024, 10, 8, 18, 0, 0).unwrap());
+        assert_eq!(move_to_workday(saturday, false), Local.with_ymd_and_hms(2024, 9, 30, 18, 0, 0).unwrap());
+    }
+
+    #[test]
+    fn parses_ics_holidays_and_adjusted_workdays() {
+        let contents = "BEGIN:VCALENDAR\r\n\
+            BEGIN:VEVENT\r\n\
+            DTSTART;VALUE=DATE:20251001\r\n\
+            DTEND;VALUE=DATE:20251004\r\n\
+            SUMMARY:国庆节\r\n\
+            \x20 休\r\n\
+            END:VEVENT\r\n\
+            BEGIN:VEVENT\r\n\
+            DTSTART;VALUE=DATE:20250928\r\n\
+            SUMMARY:国庆节补班\r\n\
+            END:VEVENT\r\n\
+            END:VCALENDAR\r\n";
+        let days = parse_ics(contents).unwrap();
+        assert_eq!(summary(&days), vec![
+            ("2025-10-01", "国庆节", true),
+            ("2025-10-02", "国庆节", true),
+            ("2025-10-03", "国庆节", true),
+            ("2025-09-28", "国庆节", false),
+        ]);
+    }
+
+    #[test]
+    fn rejects_invalid_ics_events() {
+        let too_long = "BEGIN:VEVENT\nDTSTART:20250101\nDTEND:20250301\nSUMMARY:假期\nEND:VEVENT\n";
+        assert!(parse_ics(too_long).is_err());
+        let no_start = "BEGIN:VEVENT\nSUMMARY:假期\nEND:VEVENT\n";
+        assert!(parse_ics(no_start).is_err());
+    }
+
+    #[test]
+    fn overrides_take_precedence_over_builtin_days() {
+        let days = parse_json(r#"{"days": [
+            {"name": "国庆节", "date": "2026-10-08", "isOffDay": true},
+            {"name": "国庆节", "date": "2026-10-10", "isOffDay": true}
+        ]}"#).unwrap();
+        OVERRIDES.lock().unwrap().get_or_insert_with(HashMap::new).extend(to_entries(&days));
+
+        // 内置安排中 10月8日是工作日、10月10日是调休上班
+        assert_eq!(day_kind(date("2026-10-08")), DayKind::Holiday("国庆节".to_string()));
+        assert_eq!(day_kind(date("2026-10-10")), DayKind::Holiday("国庆节".to_string()));
+        assert_eq!(next_workday_after(date("2026-10-07")), date("2026-10-09"));
+        // 没有导入的日期仍使用内置安排
+        assert_eq!(day_kind(date("2026-10-01")), DayKind::Holiday("国庆节".to_string()));
+
+        assert!(parse_json(r#"[{"name": "元旦", "date": "2026/01/01", "is_off_day": true}]"#).is_err());
+    }
+}
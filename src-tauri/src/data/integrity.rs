@@ -0,0 +1,282 @@
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::Emitter;
+use uuid::Uuid;
+
+use crate::models::{IntegrityIssue, IntegrityReport, IssueSeverity, Todo, TodoGroup};
+use crate::system::app_lock::ensure_unlocked;
+use super::todo_data::{get_data_dir, read_group_store, read_todo_store, update_group_store, update_todo_store, DEFAULT_GROUP_ID};
+
+// 修复前备份的数据文件
+const BACKUP_FILES: [&str; 2] = ["todos_with_groups.json", "groups.json"];
+
+// 备份目录，每次修复前在其中新建一个以时间命名的子目录
+const BACKUP_DIR: &str = "backups";
+
+fn issue(severity: IssueSeverity, kind: &str, item_id: &str, message: String, fixable: bool) -> IntegrityIssue {
+    IntegrityIssue {
+        severity,
+        kind: kind.to_string(),
+        item_id: item_id.to_string(),
+        message,
+        fixable,
+    }
+}
+
+// 检查分组数据，fix 为 true 时同时修复
+fn check_groups(groups: &mut Vec<TodoGroup>, fix: bool, issues: &mut Vec<IntegrityIssue>) {
+    // 重复的分组ID：保留第一个
+    let mut seen = HashSet::new();
+    groups.retain(|group| {
+        if seen.insert(group.id.clone()) {
+            return true;
+        }
+        issues.push(issue(IssueSeverity::Error, "duplicate_group_id", &group.id,
+            format!("分组「{}」的ID与其他分组重复", group.name), true));
+        !fix
+    });
+
+    for group in groups.iter_mut() {
+        if group.name.trim().is_empty() {
+            issues.push(issue(IssueSeverity::Warning, "empty_group_name", &group.id,
+                "分组名称为空".to_string(), true));
+            if fix {
+                group.name = "未命名分组".to_string();
+            }
+        }
+    }
+}
+
+// 检查任务数据，fix 为 true 时同时修复
+fn check_todos(todos: &mut Vec<Todo>, group_ids: &HashSet<String>, fix: bool, issues: &mut Vec<IntegrityIssue>) {
+    // 重复的任务ID：内容相同的视为重复保存，删除多余的副本；内容不同的为多余的副本分配新ID
+    let mut seen: HashMap<String, (String, i64)> = HashMap::new();
+    let mut kept = Vec::with_capacity(todos.len());
+    for mut todo in std::mem::take(todos) {
+        match seen.get(&todo.id) {
+            None => {}
+            Some((text, created_at)) if *text == todo.text && *created_at == todo.created_at => {
+                issues.push(issue(IssueSeverity::Error, "duplicate_todo_id", &todo.id,
+                    format!("任务「{}」重复出现", todo.text), true));
+                if fix {
+                    continue;
+                }
+            }
+            Some(_) => {
+                issues.push(issue(IssueSeverity::Error, "duplicate_todo_id", &todo.id,
+                    format!("任务「{}」与其他任务使用了相同的ID", todo.text), true));
+                if fix {
+                    todo.id = Uuid::new_v4().to_string();
+                }
+            }
+        }
+        seen.entry(todo.id.clone()).or_insert_with(|| (todo.text.clone(), todo.created_at));
+        kept.push(todo);
+    }
+    *todos = kept;
+
+    for todo in todos.iter_mut() {
+        if todo.text.trim().is_empty() {
+            issues.push(issue(IssueSeverity::Warning, "empty_todo_text", &todo.id,
+                "任务内容为空".to_string(), false));
+        }
+
+        if !group_ids.contains(&todo.group_id) {
+            issues.push(issue(IssueSeverity::Warning, "missing_group", &todo.id,
+                format!("任务「{}」所属的分组 {} 不存在", todo.text, todo.group_id), true));
+            if fix {
+                todo.group_id = DEFAULT_GROUP_ID.to_string();
+            }
+        }
+
+        if !todo.completed && todo.completed_at.is_some() {
+            issues.push(issue(IssueSeverity::Warning, "completed_at_on_pending", &todo.id,
+                format!("未完成的任务「{}」记录了完成时间", todo.text), true));
+            if fix {
+                todo.completed_at = None;
+            }
+        }
+
+        if todo.completed && todo.completed_at.is_none() {
+            issues.push(issue(IssueSeverity::Info, "missing_completed_at", &todo.id,
+                format!("已完成的任务「{}」缺少完成时间", todo.text), true));
+            if fix {
+                // 以最后修改时间作为完成时间，没有记录时用创建时间
+                todo.completed_at = Some(if todo.updated_at > 0 { todo.updated_at } else { todo.created_at });
+            }
+        }
+
+        if !(0..=1).contains(&todo.priority) {
+            issues.push(issue(IssueSeverity::Warning, "invalid_priority", &todo.id,
+                format!("任务「{}」的优先级 {} 无效", todo.text, todo.priority), true));
+            if fix {
+                todo.priority = todo.priority.clamp(0, 1);
+            }
+        }
+    }
+}
+
+// 把数据文件原样复制到新的备份目录（加密的文件保持加密）
+fn backup_data_files(data_dir: &Path) -> Result<PathBuf, String> {
+    let backup_dir = data_dir.join(BACKUP_DIR)
+        .join(format!("integrity-{}", chrono::Local::now().format("%Y%m%d-%H%M%S")));
+    fs::create_dir_all(&backup_dir)
+        .map_err(|e| format!("创建备份目录失败: {}", e))?;
+
+    for file_name in BACKUP_FILES {
+        let path = data_dir.join(file_name);
+        if path.exists() {
+            fs::copy(&path, backup_dir.join(file_name))
+                .map_err(|e| format!("备份 {} 失败: {}", file_name, e))?;
+        }
+    }
+    Ok(backup_dir)
+}
+
+// 检查任务和分组数据，fix 为 true 且存在可修复的问题时先备份再修复
+pub fn run_integrity_check(data_dir: &Path, fix: bool) -> Result<IntegrityReport, String> {
+    let mut report = IntegrityReport::default();
+    let mut groups = read_group_store(data_dir)?.groups;
+    let mut todos = read_todo_store(data_dir)?.todos;
+    check_groups(&mut groups, false, &mut report.issues);
+    let group_ids: HashSet<String> = groups.into_iter().map(|group| group.id).collect();
+    check_todos(&mut todos, &group_ids, false, &mut report.issues);
+
+    if fix && report.issues.iter().any(|issue| issue.fixable) {
+        report.backup_dir = Some(backup_data_files(data_dir)?.display().to_string());
+
+        // 在数据锁内重新检查并修复，检查之后发生的修改不会被覆盖
+        let mut issues = Vec::new();
+        let group_ids = update_group_store(data_dir, |groups, _| {
+            check_groups(groups, true, &mut issues);
+            Ok(groups.iter().map(|group| group.id.clone()).collect::<HashSet<String>>())
+        })?;
+        update_todo_store(data_dir, |todos, _| {
+            check_todos(todos, &group_ids, true, &mut issues);
+            Ok(())
+        })?;
+        report.issues = issues;
+        report.fixed = true;
+    }
+
+    // 严重的问题排在前面
+    report.issues.sort_by_key(|issue| std::cmp::Reverse(issue.severity));
+    Ok(report)
+}
+
+// 启动时只检查数据，不做修改，结果写入日志；修复由用户通过 check_data_integrity 确认后执行
+pub fn check_at_startup(app: &tauri::AppHandle) {
+    let report = match get_data_dir(app).and_then(|data_dir| run_integrity_check(&data_dir, false)) {
+        Ok(report) => report,
+        Err(e) => {
+            log::error!("数据完整性检查失败: {}", e);
+            return;
+        }
+    };
+
+    for issue in &report.issues {
+        match issue.severity {
+            IssueSeverity::Error => log::error!("数据完整性检查: [{}] {}", issue.kind, issue.message),
+            IssueSeverity::Warning => log::warn!("数据完整性检查: [{}] {}", issue.kind, issue.message),
+            IssueSeverity::Info => log::info!("数据完整性检查: [{}] {}", issue.kind, issue.message),
+        }
+    }
+    let fixable = report.issues.iter().filter(|issue| issue.fixable).count();
+    if fixable > 0 {
+        log::warn!("数据完整性检查发现 {} 个可修复的问题，可调用 check_data_integrity 并指定 fix 修复", fixable);
+    }
+}
+
+// Tauri 命令：检查任务和分组数据的完整性，fix 为 true 时修复可修复的问题（修复前先备份）
+#[tauri::command]
+pub async fn check_data_integrity(app: tauri::AppHandle, fix: bool) -> Result<IntegrityReport, String> {
+    ensure_unlocked()?;
+    let data_dir = get_data_dir(&app)?;
+    let report = run_integrity_check(&data_dir, fix)?;
+
+    if report.fixed {
+        let _ = app.emit("group-data-changed", ());
+        let _ = app.emit("todo-data-changed", ());
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::test_todo;
+
+    fn kinds(issues: &[IntegrityIssue]) -> Vec<&str> {
+        issues.iter().map(|issue| issue.kind.as_str()).collect()
+    }
+
+    #[test]
+    fn removes_repeated_todos_and_renumbers_conflicting_ids() {
+        let mut todos = vec![
+            test_todo("a", 1_700_000_000, None, None),
+            test_todo("a", 1_700_000_000, None, None),
+            Todo { text: "另一个任务".to_string(), ..test_todo("a", 1_700_000_000, None, None) },
+        ];
+        let group_ids = HashSet::from([DEFAULT_GROUP_ID.to_string()]);
+
+        let mut issues = Vec::new();
+        check_todos(&mut todos, &group_ids, false, &mut issues);
+        assert_eq!(kinds(&issues), ["duplicate_todo_id", "duplicate_todo_id"]);
+        assert_eq!(todos.len(), 3);
+
+        let mut issues = Vec::new();
+        check_todos(&mut todos, &group_ids, true, &mut issues);
+        assert_eq!(todos.len(), 2);
+        assert_eq!(todos[0].id, "a");
+        assert_ne!(todos[1].id, "a");
+        assert_eq!(todos[1].text, "另一个任务");
+
+        let mut issues = Vec::new();
+        check_todos(&mut todos, &group_ids, true, &mut issues);
+        assert!(issues.is_empty());
+    }
+
+    #[test]
+    fn moves_todos_of_missing_groups_to_the_default_group() {
+        let mut todos = vec![Todo { group_id: "deleted".to_string(), ..test_todo("a", 1_700_000_000, None, None) }];
+        let group_ids = HashSet::from([DEFAULT_GROUP_ID.to_string()]);
+
+        let mut issues = Vec::new();
+        check_todos(&mut todos, &group_ids, true, &mut issues);
+        assert_eq!(kinds(&issues), ["missing_group"]);
+        assert_eq!(todos[0].group_id, DEFAULT_GROUP_ID);
+    }
+
+    #[test]
+    fn fixes_completed_at_to_match_the_completed_flag() {
+        let mut todos = vec![
+            Todo { completed: false, ..test_todo("reopened", 1_700_000_000, None, Some(1_700_000_100)) },
+            Todo { completed: true, updated_at: 1_700_000_200, ..test_todo("edited", 1_700_000_000, None, None) },
+            Todo { completed: true, ..test_todo("never-edited", 1_700_000_000, None, None) },
+        ];
+        let group_ids = HashSet::from([DEFAULT_GROUP_ID.to_string()]);
+
+        let mut issues = Vec::new();
+        check_todos(&mut todos, &group_ids, true, &mut issues);
+        assert_eq!(kinds(&issues), ["completed_at_on_pending", "missing_completed_at", "missing_completed_at"]);
+        assert_eq!(todos[0].completed_at, None);
+        // 以最后修改时间作为完成时间，没有记录时用创建时间
+        assert_eq!(todos[1].completed_at, Some(1_700_000_200));
+        assert_eq!(todos[2].completed_at, Some(1_700_000_000));
+    }
+
+    #[test]
+    fn keeps_the_first_of_duplicate_groups() {
+        let mut groups = vec![
+            TodoGroup { id: "work".to_string(), name: "工作".to_string(), ..Default::default() },
+            TodoGroup { id: "work".to_string(), name: "工作副本".to_string(), ..Default::default() },
+        ];
+
+        let mut issues = Vec::new();
+        check_groups(&mut groups, true, &mut issues);
+        assert_eq!(kinds(&issues), ["duplicate_group_id"]);
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].name, "工作");
+    }
+}
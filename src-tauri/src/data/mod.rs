@@ -1,12 +1,20 @@
 pub mod todo_data;
 pub mod app_settings;
 pub mod window_position;
+pub mod archive;
+pub mod storage;
+pub mod watcher;
+pub mod history;
+pub mod encryption;
+pub mod groups;
+pub mod integrity;
+pub mod holidays;
 
 // 重新导出公共函数
 pub use todo_data::{
     save_todo_data, load_todo_data, set_todo_deadline, update_todo_text,
     save_todo_data_with_groups, load_todo_data_with_groups,
-    save_group_data, load_group_data
+    save_group_data, load_group_data, add_todo, update_todo, set_todo_completed, delete_todo, export_todos
 };
 pub use app_settings::{save_app_settings, load_app_settings, apply_opacity};
 pub use window_position::{save_window_position, load_window_position};
\ No newline at end of file
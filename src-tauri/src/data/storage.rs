@@ -0,0 +1,104 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, MutexGuard, OnceLock};
+use serde::Serialize;
+
+use super::encryption::{decrypt_contents, encrypt_for};
+
+// 记录每个数据文件最近一次已知的内容（应用写入或已处理过的内容），
+// 用于区分应用自身的写入和外部修改，并作为与外部修改合并时的本地版本
+static KNOWN_CONTENTS: OnceLock<Mutex<HashMap<PathBuf, String>>> = OnceLock::new();
+
+// 数据文件的写入次数，后台同步据此判断本地数据是否有变化
+static DATA_VERSION: AtomicU64 = AtomicU64::new(0);
+
+// 每个数据文件最近一次与外部版本（同步工具同步进来的版本）一致的时间，合并外部修改时作为冲突判断的基准
+static SYNC_BASES: OnceLock<Mutex<HashMap<PathBuf, i64>>> = OnceLock::new();
+
+// 读取-合并-写回数据文件时持有的锁，防止后台合并与前端保存互相覆盖
+static STORE_LOCK: Mutex<()> = Mutex::new(());
+
+fn known_contents() -> &'static Mutex<HashMap<PathBuf, String>> {
+    KNOWN_CONTENTS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+// 记录文件的已知内容
+pub fn record_contents(path: &Path, contents: &str) {
+    if let Ok(mut known) = known_contents().lock() {
+        known.insert(path.to_path_buf(), contents.to_string());
+    }
+}
+
+// 获取文件最近一次已知的内容
+pub fn last_known_contents(path: &Path) -> Option<String> {
+    known_contents()
+        .lock()
+        .ok()
+        .and_then(|known| known.get(path).cloned())
+}
+
+fn sync_bases() -> &'static Mutex<HashMap<PathBuf, i64>> {
+    SYNC_BASES.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+// 记录文件在某一时刻与外部版本一致
+pub fn mark_synced(path: &Path, at: i64) {
+    if let Ok(mut bases) = sync_bases().lock() {
+        bases.insert(path.to_path_buf(), at);
+    }
+}
+
+// 获取文件最近一次与外部版本一致的时间
+pub fn sync_base(path: &Path) -> Option<i64> {
+    sync_bases()
+        .lock()
+        .ok()
+        .and_then(|bases| bases.get(path).copied())
+}
+
+// 判断文件内容是否与最近一次已知内容一致（即由应用自身写入或已处理过）
+pub fn is_known_contents(path: &Path, contents: &str) -> bool {
+    known_contents()
+        .lock()
+        .map(|known| known.get(path).is_some_and(|known| known == contents))
+        .unwrap_or(false)
+}
+
+// 写入数据文件，并记录写入的内容，供文件监听器忽略应用自身的写入
+// 启用数据加密时，需要加密的文件会以加密格式写入
+pub fn write_data_file(path: &Path, contents: &str) -> io::Result<()> {
+    let contents = encrypt_for(path, contents).map_err(io::Error::other)?;
+    record_contents(path, &contents);
+    fs::write(path, &contents)?;
+    DATA_VERSION.fetch_add(1, Ordering::SeqCst);
+    Ok(())
+}
+
+// 读取数据文件，加密的文件会被解密
+pub fn read_data_file(path: &Path) -> Result<String, String> {
+    let contents = fs::read_to_string(path)
+        .map_err(|e| format!("读取文件失败: {}", e))?;
+    decrypt_contents(&contents)
+}
+
+// 将数据序列化为JSON并写入数据文件
+pub fn write_json_file<T: Serialize>(path: &Path, data: &T) -> Result<(), String> {
+    let json_data = serde_json::to_string_pretty(data)
+        .map_err(|e| format!("序列化数据失败: {}", e))?;
+    
+    write_data_file(path, &json_data)
+        .map_err(|e| format!("写入文件失败: {}", e))
+}
+
+// 获取数据文件锁
+pub fn lock_store() -> MutexGuard<'static, ()> {
+    STORE_LOCK.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+// 获取当前数据版本
+pub fn data_version() -> u64 {
+    DATA_VERSION.load(Ordering::SeqCst)
+}
@@ -1,12 +1,20 @@
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
 use serde_json;
-use tauri::Manager;
+use tauri::{Emitter, Manager};
 
-use crate::models::{Todo, TodoData};
+use crate::models::{GroupData, Todo, TodoData, TodoDataWithGroups, TodoExport, TodoGroup, TodoPatch, Tombstone};
+use crate::sync::merge::{fill_missing_fields, stamp_changes};
+use crate::system::app_lock::ensure_unlocked;
+use super::encryption::encrypt_contents;
+use super::storage::{lock_store, read_data_file, write_data_file};
+
+// 默认分组ID
+pub(crate) const DEFAULT_GROUP_ID: &str = "default";
 
 // 获取数据目录路径
-fn get_data_dir(app: &tauri::AppHandle) -> Result<PathBuf, String> {
+pub(crate) fn get_data_dir(app: &tauri::AppHandle) -> Result<PathBuf, String> {
     // 使用文档目录而不是应用数据目录，这样重装或更新应用时数据不会丢失
     let document_dir = app.path().document_dir()
         .map_err(|e| format!("获取用户文档目录失败: {}", e))?;
@@ -26,6 +34,7 @@ fn get_data_dir(app: &tauri::AppHandle) -> Result<PathBuf, String> {
 // Tauri 命令：保存todo数据
 #[tauri::command]
 pub async fn save_todo_data(app: tauri::AppHandle, pending_todos: Vec<Todo>, completed_todos: Vec<Todo>) -> Result<(), String> {
+    ensure_unlocked()?;
     let data_dir = get_data_dir(&app)?;
     let file_path = data_dir.join("todo_list.json");
     
@@ -37,7 +46,7 @@ pub async fn save_todo_data(app: tauri::AppHandle, pending_todos: Vec<Todo>, com
     let json_data = serde_json::to_string_pretty(&todo_data)
         .map_err(|e| format!("序列化数据失败: {}", e))?;
     
-    fs::write(&file_path, json_data)
+    write_data_file(&file_path, &json_data)
         .map_err(|e| format!("写入文件失败: {}", e))?;
     
     Ok(())
@@ -46,6 +55,7 @@ pub async fn save_todo_data(app: tauri::AppHandle, pending_todos: Vec<Todo>, com
 // Tauri 命令：加载todo数据
 #[tauri::command]
 pub async fn load_todo_data(app: tauri::AppHandle) -> Result<TodoData, String> {
+    ensure_unlocked()?;
     let data_dir = get_data_dir(&app)?;
     let file_path = data_dir.join("todo_list.json");
     
@@ -57,8 +67,7 @@ pub async fn load_todo_data(app: tauri::AppHandle) -> Result<TodoData, String> {
         });
     }
     
-    let json_data = fs::read_to_string(&file_path)
-        .map_err(|e| format!("读取文件失败: {}", e))?;
+    let json_data = read_data_file(&file_path)?;
     
     let todo_data: TodoData = serde_json::from_str(&json_data)
         .map_err(|e| format!("解析JSON失败: {}", e))?;
@@ -74,6 +83,7 @@ pub async fn update_todo_text(
     is_completed: bool,
     new_text: String
 ) -> Result<(), String> {
+    ensure_unlocked()?;
     println!("准备更新任务文本: id='{}', completed={}, new_text='{}'?", 
         todo_id, is_completed, new_text);
     
@@ -116,9 +126,7 @@ pub async fn update_todo_text(
             Err(e) => println!("数据保存失败: {}", e),
         }
         
-        if save_result.is_err() {
-            return save_result;
-        }
+        save_result.as_ref()?;
         
         println!("任务文本更新成功");
         Ok(())
@@ -137,6 +145,7 @@ pub async fn set_todo_deadline(
     is_completed: bool,
     deadline: Option<i64>
 ) -> Result<(), String> {
+    ensure_unlocked()?;
     println!("准备设置截止时间: id='{}', completed={}, deadline={:?}", 
         todo_id, is_completed, deadline);
     
@@ -179,9 +188,7 @@ pub async fn set_todo_deadline(
             Err(e) => println!("数据保存失败: {}", e),
         }
         
-        if save_result.is_err() {
-            return save_result;
-        }
+        save_result.as_ref()?;
         
         // 根据deadline值提供不同的成功消息
         if deadline.is_some() {
@@ -197,89 +204,443 @@ pub async fn set_todo_deadline(
     }
 }
 
-// Tauri 命令：保存带分组的todo数据
-#[tauri::command]
-pub async fn save_todo_data_with_groups(app: tauri::AppHandle, todos: Vec<Todo>) -> Result<(), String> {
-    let data_dir = get_data_dir(&app)?;
+// 读取带分组的todo数据文件
+pub(crate) fn read_todo_store(data_dir: &Path) -> Result<TodoDataWithGroups, String> {
     let file_path = data_dir.join("todos_with_groups.json");
     
-    let todo_data = crate::models::TodoDataWithGroups {
-        todos,
-    };
-    
-    let json_data = serde_json::to_string_pretty(&todo_data)
-        .map_err(|e| format!("序列化数据失败: {}", e))?;
+    if !file_path.exists() {
+        // 如果文件不存在，返回空数据
+        return Ok(TodoDataWithGroups::default());
+    }
     
-    fs::write(&file_path, json_data)
-        .map_err(|e| format!("写入文件失败: {}", e))?;
+    let json_data = read_data_file(&file_path)?;
     
-    Ok(())
+    serde_json::from_str(&json_data)
+        .map_err(|e| format!("解析JSON失败: {}", e))
 }
 
-// Tauri 命令：加载带分组的todo数据
-#[tauri::command]
-pub async fn load_todo_data_with_groups(app: tauri::AppHandle) -> Result<crate::models::TodoDataWithGroups, String> {
-    let data_dir = get_data_dir(&app)?;
+// 写入带分组的todo数据文件
+pub(crate) fn write_todo_store(data_dir: &Path, todo_data: &TodoDataWithGroups) -> Result<(), String> {
     let file_path = data_dir.join("todos_with_groups.json");
     
+    let json_data = serde_json::to_string_pretty(todo_data)
+        .map_err(|e| format!("序列化数据失败: {}", e))?;
+    
+    write_data_file(&file_path, &json_data)
+        .map_err(|e| format!("写入文件失败: {}", e))
+}
+
+// 读取分组数据文件
+pub(crate) fn read_group_store(data_dir: &Path) -> Result<GroupData, String> {
+    let file_path = data_dir.join("groups.json");
+    
     if !file_path.exists() {
-        // 如果文件不存在，返回空数据
-        return Ok(crate::models::TodoDataWithGroups {
-            todos: Vec::new(),
+        // 如果文件不存在，返回默认分组
+        return Ok(GroupData {
+            groups: vec![default_group()],
+            tombstones: Vec::new(),
         });
     }
     
-    let json_data = fs::read_to_string(&file_path)
-        .map_err(|e| format!("读取文件失败: {}", e))?;
+    let json_data = read_data_file(&file_path)?;
     
-    let todo_data: crate::models::TodoDataWithGroups = serde_json::from_str(&json_data)
+    let mut group_data: GroupData = serde_json::from_str(&json_data)
         .map_err(|e| format!("解析JSON失败: {}", e))?;
+    normalize_groups(&mut group_data.groups);
     
-    Ok(todo_data)
+    Ok(group_data)
 }
 
-// Tauri 命令：保存分组数据
-#[tauri::command]
-pub async fn save_group_data(app: tauri::AppHandle, groups: Vec<crate::models::TodoGroup>) -> Result<(), String> {
-    let data_dir = get_data_dir(&app)?;
+// 默认分组，始终存在，不能删除
+fn default_group() -> TodoGroup {
+    TodoGroup {
+        id: DEFAULT_GROUP_ID.to_string(),
+        name: "未分组".to_string(),
+        ..Default::default()
+    }
+}
+
+// 整理分组列表：缺少默认分组时补回，修复上级分组不存在或形成循环的层级关系
+// 同步合并后可能出现这些情况，例如两台设备分别把 A 移到 B 下、把 B 移到 A 下
+fn normalize_groups(groups: &mut Vec<TodoGroup>) {
+    if !groups.iter().any(|group| group.id == DEFAULT_GROUP_ID) {
+        groups.insert(0, default_group());
+    }
+    super::groups::repair_group_tree(groups);
+}
+
+// 写入分组数据文件
+pub(crate) fn write_group_store(data_dir: &Path, group_data: &GroupData) -> Result<(), String> {
     let file_path = data_dir.join("groups.json");
     
-    let group_data = crate::models::GroupData {
+    let json_data = serde_json::to_string_pretty(group_data)
+        .map_err(|e| format!("序列化数据失败: {}", e))?;
+    
+    write_data_file(&file_path, &json_data)
+        .map_err(|e| format!("写入文件失败: {}", e))
+}
+
+// 保存带分组的todo数据
+// 与磁盘上的数据比较，记录各字段的修改时间和删除记录，供同步合并使用
+// base 为保存方上次看到的数据，后端内部基于最新数据修改时传 None
+pub(crate) fn save_todo_store(data_dir: &Path, todos: Vec<Todo>, base: Option<&[Todo]>) -> Result<(), String> {
+    let _guard = lock_store();
+    stamp_and_write_todos(data_dir, todos, base)
+}
+
+// 在数据锁内读取、修改并保存todo数据，修改期间其他保存会等待，不会互相覆盖
+pub(crate) fn update_todo_store<R>(
+    data_dir: &Path,
+    update: impl FnOnce(&mut Vec<Todo>, &[Tombstone]) -> Result<R, String>,
+) -> Result<R, String> {
+    let _guard = lock_store();
+    let mut todo_data = read_todo_store(data_dir)?;
+    let result = update(&mut todo_data.todos, &todo_data.tombstones)?;
+    stamp_and_write_todos(data_dir, todo_data.todos, None)?;
+    Ok(result)
+}
+
+// 调用方需持有数据锁
+fn stamp_and_write_todos(data_dir: &Path, todos: Vec<Todo>, base: Option<&[Todo]>) -> Result<(), String> {
+    // 旧文件损坏时仍允许保存，只是无法记录修改时间
+    let previous = read_todo_store(data_dir).unwrap_or_default();
+    let (todos, tombstones) = stamp_changes(
+        &previous.todos,
+        &previous.tombstones,
+        todos,
+        base,
+        chrono::Utc::now().timestamp(),
+    )?;
+    
+    write_todo_store(data_dir, &TodoDataWithGroups { todos, tombstones })
+}
+
+// 保存分组数据，规则同 save_todo_store
+pub(crate) fn save_group_store(data_dir: &Path, groups: Vec<TodoGroup>, base: Option<&[TodoGroup]>) -> Result<(), String> {
+    let _guard = lock_store();
+    stamp_and_write_groups(data_dir, groups, base)
+}
+
+// 在数据锁内读取、修改并保存分组数据，规则同 update_todo_store
+pub(crate) fn update_group_store<R>(
+    data_dir: &Path,
+    update: impl FnOnce(&mut Vec<TodoGroup>, &[Tombstone]) -> Result<R, String>,
+) -> Result<R, String> {
+    let _guard = lock_store();
+    let mut group_data = read_group_store(data_dir)?;
+    let result = update(&mut group_data.groups, &group_data.tombstones)?;
+    stamp_and_write_groups(data_dir, group_data.groups, None)?;
+    Ok(result)
+}
+
+// 调用方需持有数据锁
+fn stamp_and_write_groups(data_dir: &Path, mut groups: Vec<TodoGroup>, base: Option<&[TodoGroup]>) -> Result<(), String> {
+    normalize_groups(&mut groups);
+    let previous = read_group_store(data_dir).unwrap_or_default();
+    let (groups, tombstones) = stamp_changes(
+        &previous.groups,
+        &previous.tombstones,
         groups,
+        base,
+        chrono::Utc::now().timestamp(),
+    )?;
+    
+    write_group_store(data_dir, &GroupData { groups, tombstones })
+}
+
+// 整理标签：去掉开头的 #、首尾空白、空标签和重复的标签
+pub(crate) fn normalize_tags(tags: Vec<String>) -> Vec<String> {
+    let mut normalized: Vec<String> = Vec::with_capacity(tags.len());
+    for tag in tags {
+        let tag = tag.trim().trim_start_matches(['#', '＃']).trim().to_string();
+        if !tag.is_empty() && !normalized.contains(&tag) {
+            normalized.push(tag);
+        }
+    }
+    normalized
+}
+
+// 新建任务，未指定的优先级和截止时间使用所属分组的默认设置
+pub(crate) fn add_todo_to_store(
+    data_dir: &Path,
+    text: &str,
+    group_id: Option<&str>,
+    priority: Option<i32>,
+    deadline: Option<i64>,
+    tags: Vec<String>,
+) -> Result<Todo, String> {
+    let text = text.trim();
+    if text.is_empty() {
+        return Err("任务内容不能为空".to_string());
+    }
+    if let Some(priority) = priority.filter(|priority| !(0..=1).contains(priority)) {
+        return Err(format!("无效的优先级: {}", priority));
+    }
+
+    let group_id = group_id.unwrap_or(DEFAULT_GROUP_ID);
+    let now = chrono::Local::now();
+
+    // 在数据锁内检查分组，避免分组在检查之后被删除
+    update_todo_store(data_dir, |todos, _| {
+        let group = read_group_store(data_dir)?.groups.into_iter()
+            .find(|group| group.id == group_id)
+            .ok_or_else(|| format!("未找到指定的分组: id='{}'", group_id))?;
+
+        let deadline = match (deadline, &group.default_deadline_offset) {
+            (Some(deadline), _) => Some(deadline),
+            (None, Some(offset)) => {
+                let deadline = super::groups::resolve_deadline_offset(offset, now)?;
+                // 设置了跳过非工作日时，默认截止时间顺延到下一个工作日的同一时刻
+                if super::app_settings::read_app_settings(data_dir).skip_non_workdays_for_deadlines {
+                    super::holidays::init_holiday_calendar(data_dir);
+                    let deadline = chrono::DateTime::from_timestamp(deadline, 0)
+                        .map(|deadline| deadline.with_timezone(&chrono::Local))
+                        .ok_or("无效的默认截止时间")?;
+                    Some(super::holidays::move_to_workday(deadline, true).timestamp())
+                } else {
+                    Some(deadline)
+                }
+            }
+            (None, None) => None,
+        };
+
+        let order = todos.iter()
+            .filter(|todo| todo.group_id == group.id)
+            .map(|todo| todo.order)
+            .max()
+            .unwrap_or(0) + 1;
+        let todo = Todo {
+            id: uuid::Uuid::new_v4().to_string(),
+            text: text.to_string(),
+            completed: false,
+            created_at: now.timestamp(),
+            completed_at: None,
+            deadline,
+            order,
+            group_id: group.id.clone(),
+            priority: priority.unwrap_or(group.default_priority),
+            tags: normalize_tags(tags),
+            updated_at: 0,
+            field_updated_at: Default::default(),
+        };
+        todos.push(todo.clone());
+        Ok(todo)
+    })
+}
+
+// Tauri 命令：新建任务，返回新建的任务
+#[tauri::command]
+pub async fn add_todo(
+    app: tauri::AppHandle,
+    text: String,
+    group_id: Option<String>,
+    priority: Option<i32>,
+    deadline: Option<i64>,
+    tags: Option<Vec<String>>,
+) -> Result<Todo, String> {
+    ensure_unlocked()?;
+    let data_dir = get_data_dir(&app)?;
+    let todo = add_todo_to_store(&data_dir, &text, group_id.as_deref(), priority, deadline, tags.unwrap_or_default())?;
+    
+    let _ = app.emit("todo-data-changed", ());
+    Ok(todo)
+}
+
+// 在数据锁内修改指定的任务，返回修改后的任务
+fn modify_todo(data_dir: &Path, todo_id: &str, modify: impl FnOnce(&mut Todo) -> Result<(), String>) -> Result<Todo, String> {
+    update_todo_store(data_dir, |todos, _| {
+        let todo = todos.iter_mut()
+            .find(|todo| todo.id == todo_id)
+            .ok_or_else(|| format!("未找到指定的todo项: id='{}'", todo_id))?;
+        modify(todo)?;
+        Ok(todo.clone())
+    })
+}
+
+// Tauri 命令：修改任务的内容、截止时间、优先级、所属分组或排序
+#[tauri::command]
+pub async fn update_todo(app: tauri::AppHandle, todo_id: String, patch: TodoPatch) -> Result<Todo, String> {
+    ensure_unlocked()?;
+    let data_dir = get_data_dir(&app)?;
+    
+    if let Some(priority) = patch.priority.filter(|priority| !(0..=1).contains(priority)) {
+        return Err(format!("无效的优先级: {}", priority));
+    }
+    let text = match &patch.text {
+        Some(text) if text.trim().is_empty() => return Err("任务内容不能为空".to_string()),
+        Some(text) => Some(text.trim().to_string()),
+        None => None,
     };
     
-    let json_data = serde_json::to_string_pretty(&group_data)
+    let todo = modify_todo(&data_dir, &todo_id, |todo| {
+        // 在数据锁内检查分组，避免分组在检查之后被删除
+        if let Some(group_id) = &patch.group_id {
+            if !read_group_store(&data_dir)?.groups.iter().any(|group| group.id == *group_id) {
+                return Err(format!("未找到指定的分组: id='{}'", group_id));
+            }
+        }
+        if let Some(text) = text {
+            todo.text = text;
+        }
+        if let Some(deadline) = patch.deadline {
+            todo.deadline = deadline;
+        }
+        if let Some(priority) = patch.priority {
+            todo.priority = priority;
+        }
+        if let Some(group_id) = patch.group_id {
+            todo.group_id = group_id;
+        }
+        if let Some(order) = patch.order {
+            todo.order = order;
+        }
+        if let Some(tags) = patch.tags {
+            todo.tags = normalize_tags(tags);
+        }
+        Ok(())
+    })?;
+    
+    let _ = app.emit("todo-data-changed", ());
+    Ok(todo)
+}
+
+// 完成任务或取消完成，返回修改后的任务
+pub(crate) fn set_todo_completed_in_store(data_dir: &Path, todo_id: &str, completed: bool) -> Result<Todo, String> {
+    modify_todo(data_dir, todo_id, |todo| {
+        if todo.completed != completed {
+            todo.completed = completed;
+            todo.completed_at = completed.then(|| chrono::Utc::now().timestamp());
+        }
+        Ok(())
+    })
+}
+
+// Tauri 命令：完成任务或取消完成
+#[tauri::command]
+pub async fn set_todo_completed(app: tauri::AppHandle, todo_id: String, completed: bool) -> Result<Todo, String> {
+    ensure_unlocked()?;
+    let data_dir = get_data_dir(&app)?;
+    let todo = set_todo_completed_in_store(&data_dir, &todo_id, completed)?;
+    
+    let _ = app.emit("todo-data-changed", ());
+    Ok(todo)
+}
+
+// 导出当前的任务和分组，启用数据加密时导出内容同样加密，plaintext 为 true 时才导出明文
+pub(crate) fn export_store(data_dir: &Path, plaintext: bool) -> Result<String, String> {
+    let export = TodoExport {
+        todos: read_todo_store(data_dir)?.todos,
+        groups: read_group_store(data_dir)?.groups,
+    };
+    let json_data = serde_json::to_string_pretty(&export)
         .map_err(|e| format!("序列化数据失败: {}", e))?;
+    if plaintext {
+        Ok(json_data)
+    } else {
+        encrypt_contents(&json_data)
+    }
+}
+
+// Tauri 命令：导出当前的任务和分组，返回导出内容
+#[tauri::command]
+pub async fn export_todos(app: tauri::AppHandle, plaintext: Option<bool>) -> Result<String, String> {
+    ensure_unlocked()?;
+    export_store(&get_data_dir(&app)?, plaintext.unwrap_or(false))
+}
+
+// Tauri 命令：删除任务
+#[tauri::command]
+pub async fn delete_todo(app: tauri::AppHandle, todo_id: String) -> Result<(), String> {
+    ensure_unlocked()?;
+    let data_dir = get_data_dir(&app)?;
     
-    fs::write(&file_path, json_data)
-        .map_err(|e| format!("写入文件失败: {}", e))?;
+    update_todo_store(&data_dir, |todos, _| {
+        let before = todos.len();
+        todos.retain(|todo| todo.id != todo_id);
+        if todos.len() == before {
+            return Err(format!("未找到指定的todo项: id='{}'", todo_id));
+        }
+        Ok(())
+    })?;
     
+    let _ = app.emit("todo-data-changed", ());
     Ok(())
 }
 
-// Tauri 命令：加载分组数据
+// 前端最近一次加载或保存的数据，用于判断前端实际修改了哪些内容
+// 前端总是整体保存，没有这个基准时，同步或其他途径新增的数据会被前端过期的列表覆盖
+static FRONTEND_TODOS: Mutex<Option<Vec<Todo>>> = Mutex::new(None);
+static FRONTEND_GROUPS: Mutex<Option<Vec<TodoGroup>>> = Mutex::new(None);
+
+// Tauri 命令：保存带分组的todo数据
 #[tauri::command]
-pub async fn load_group_data(app: tauri::AppHandle) -> Result<crate::models::GroupData, String> {
+pub async fn save_todo_data_with_groups(app: tauri::AppHandle, todos: Vec<serde_json::Value>) -> Result<(), String> {
+    ensure_unlocked()?;
     let data_dir = get_data_dir(&app)?;
-    let file_path = data_dir.join("groups.json");
+    let mut frontend_todos = FRONTEND_TODOS.lock().map_err(|e| e.to_string())?;
     
-    if !file_path.exists() {
-        // 如果文件不存在，返回默认分组
-        return Ok(crate::models::GroupData {
-            groups: vec![crate::models::TodoGroup {
-                id: "default".to_string(),
-                name: "未分组".to_string(),
-                order: 0,
-                collapsed: false,
-            }],
-        });
+    // 前端没有传入的字段沿用它上次加载的值（没有加载过时以磁盘上的数据为准）
+    let todos: Vec<Todo> = match frontend_todos.as_deref() {
+        Some(base) => fill_missing_fields(todos, base)?,
+        None => fill_missing_fields(todos, &read_todo_store(&data_dir)?.todos)?,
+    };
+    save_todo_store(&data_dir, todos.clone(), frontend_todos.as_deref())?;
+    *frontend_todos = Some(todos);
+    
+    Ok(())
+}
+
+// Tauri 命令：加载带分组的todo数据
+#[tauri::command]
+pub async fn load_todo_data_with_groups(app: tauri::AppHandle) -> Result<TodoDataWithGroups, String> {
+    ensure_unlocked()?;
+    let data_dir = get_data_dir(&app)?;
+    let todo_data = read_todo_store(&data_dir)?;
+    
+    if let Ok(mut frontend_todos) = FRONTEND_TODOS.lock() {
+        *frontend_todos = Some(todo_data.todos.clone());
     }
     
-    let json_data = fs::read_to_string(&file_path)
-        .map_err(|e| format!("读取文件失败: {}", e))?;
+    Ok(todo_data)
+}
+
+// Tauri 命令：保存分组数据
+#[tauri::command]
+pub async fn save_group_data(app: tauri::AppHandle, groups: Vec<serde_json::Value>) -> Result<(), String> {
+    ensure_unlocked()?;
+    let data_dir = get_data_dir(&app)?;
+    let mut frontend_groups = FRONTEND_GROUPS.lock().map_err(|e| e.to_string())?;
     
-    let group_data: crate::models::GroupData = serde_json::from_str(&json_data)
-        .map_err(|e| format!("解析JSON失败: {}", e))?;
+    // 前端没有传入的字段（上级分组、颜色、图标、新建任务的默认值等）沿用它上次加载的值
+    let groups: Vec<TodoGroup> = match frontend_groups.as_deref() {
+        Some(base) => fill_missing_fields(groups, base)?,
+        None => fill_missing_fields(groups, &read_group_store(&data_dir)?.groups)?,
+    };
+    save_group_store(&data_dir, groups.clone(), frontend_groups.as_deref())?;
+    *frontend_groups = Some(groups);
+    drop(frontend_groups);
+    crate::system::tray::refresh_tray_menu(&app);
+    
+    // 被删除分组中的任务移到默认分组，避免任务的所属分组不存在
+    let moved = super::groups::reassign_orphaned_todos(&data_dir)?;
+    if moved > 0 {
+        log::info!("已将 {} 个所属分组不存在的任务移到默认分组", moved);
+        let _ = app.emit("todo-data-changed", ());
+    }
+    
+    Ok(())
+}
+
+// Tauri 命令：加载分组数据
+#[tauri::command]
+pub async fn load_group_data(app: tauri::AppHandle) -> Result<GroupData, String> {
+    ensure_unlocked()?;
+    let data_dir = get_data_dir(&app)?;
+    let group_data = read_group_store(&data_dir)?;
+    
+    if let Ok(mut frontend_groups) = FRONTEND_GROUPS.lock() {
+        *frontend_groups = Some(group_data.groups.clone());
+    }
     
     Ok(group_data)
 }
@@ -0,0 +1,128 @@
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::time::Duration;
+use notify::{EventKind, RecursiveMode, Watcher};
+use serde_json;
+use tauri::Emitter;
+
+use crate::models::AppSettings;
+use crate::sync::folder::{conflicted_copy_target, mark_data_files_synced, merge_conflicted_copy, reconcile_data_file};
+use super::history::record_changes;
+use super::storage::{is_known_contents, record_contents};
+use super::todo_data::get_data_dir;
+
+// 同一批文件事件的合并时间窗口（同步工具和编辑器通常会连续触发多个事件）
+const DEBOUNCE_INTERVAL: Duration = Duration::from_millis(300);
+
+// 处理一个发生变化的数据文件
+fn handle_changed_file(app: &tauri::AppHandle, path: &Path) {
+    let file_name = match path.file_name() {
+        Some(name) => name.to_string_lossy().to_string(),
+        None => return,
+    };
+
+    // 同步工具生成的冲突副本：合并到对应的数据文件
+    if conflicted_copy_target(&file_name).is_some() {
+        if !path.exists() {
+            return;
+        }
+        match merge_conflicted_copy(path) {
+            Ok((event, _)) => {
+                let _ = app.emit(event, ());
+            }
+            Err(e) => log::error!("合并冲突副本失败: {}: {}", file_name, e),
+        }
+        return;
+    }
+
+    // 文件被删除或暂时不可读（例如同步工具正在替换文件）时忽略，等待下一次事件
+    let contents = match fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(_) => return,
+    };
+
+    // 忽略应用自身的写入以及已经处理过的内容
+    if is_known_contents(path, &contents) {
+        return;
+    }
+
+    let result = match file_name.as_str() {
+        "app_settings.json" => serde_json::from_str::<AppSettings>(&contents)
+            .map(|_| {
+                record_contents(path, &contents);
+                "app-settings-changed"
+            })
+            .map_err(|e| e.to_string()),
+        _ => match reconcile_data_file(path, &contents) {
+            Some(result) => result,
+            None => return,
+        },
+    };
+
+    match result {
+        Ok(event) => {
+            log::info!("检测到数据文件被外部修改，重新加载: {}", file_name);
+            let _ = app.emit(event, ());
+        }
+        Err(e) => {
+            log::error!("外部修改的数据文件无效: {}: {}", file_name, e);
+            let _ = app.emit("data-file-invalid", serde_json::json!({
+                "file": file_name,
+                "error": e,
+            }));
+        }
+    }
+}
+
+// 启动数据目录监听，检测外部修改并通知前端重新加载，启用历史记录时同时提交修改
+pub fn start_data_watcher(app: tauri::AppHandle) -> Result<(), String> {
+    let data_dir = get_data_dir(&app)?;
+
+    let (tx, rx) = mpsc::channel::<notify::Result<notify::Event>>();
+    let mut watcher = notify::recommended_watcher(tx)
+        .map_err(|e| format!("创建文件监听器失败: {}", e))?;
+    watcher.watch(&data_dir, RecursiveMode::NonRecursive)
+        .map_err(|e| format!("监听数据目录失败: {}", e))?;
+    mark_data_files_synced(&data_dir);
+
+    std::thread::spawn(move || {
+        // 监听器需要在线程生命周期内保持存活
+        let _watcher = watcher;
+
+        while let Ok(first) = rx.recv() {
+            // 合并短时间内的连续事件
+            let mut changed: HashSet<PathBuf> = HashSet::new();
+            let mut pending = Some(first);
+            while let Some(result) = pending {
+                match result {
+                    Ok(event) => {
+                        if matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_)) {
+                            changed.extend(event.paths);
+                        }
+                    }
+                    Err(e) => log::error!("文件监听错误: {}", e),
+                }
+                pending = rx.recv_timeout(DEBOUNCE_INTERVAL).ok();
+            }
+
+            for path in &changed {
+                handle_changed_file(&app, path);
+            }
+
+            // 启用历史记录时，同一批修改提交为一个版本
+            let history_enabled = tauri::async_runtime::block_on(crate::data::load_app_settings(app.clone()))
+                .is_ok_and(|settings| settings.git_history_enabled);
+            if history_enabled {
+                let names: HashSet<String> = changed.iter()
+                    .filter_map(|path| path.file_name())
+                    .map(|name| name.to_string_lossy().to_string())
+                    .collect();
+                record_changes(&data_dir, &names);
+            }
+        }
+    });
+
+    Ok(())
+}
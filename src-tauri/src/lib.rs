@@ -2,16 +2,8 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
 #[cfg(target_os = "windows")]
-use windows::Win32::System::Threading::CreateMutexW;
-#[cfg(target_os = "windows")]
-use windows::Win32::Foundation::CloseHandle;
-#[cfg(target_os = "windows")]
-use std::ffi::OsStr;
-#[cfg(target_os = "windows")]
-use std::os::windows::ffi::OsStrExt;
-
 use std::sync::atomic::{AtomicBool, Ordering};
-use tauri::{Manager, Emitter};
+use tauri::{Manager, Emitter, Listener};
 
 // 模块声明
 mod models;
@@ -20,6 +12,11 @@ mod window;
 mod system;
 mod utils;
 mod notification;
+mod stats;
+mod calendar;
+mod sync;
+mod api;
+mod cli;
 
 // 重新导出需要的类型和函数
 use data::{
@@ -31,6 +28,11 @@ use data::{
     load_todo_data_with_groups,
     save_group_data,
     load_group_data,
+    add_todo,
+    update_todo,
+    set_todo_completed,
+    delete_todo,
+    export_todos,
     save_app_settings,
     load_app_settings,
     apply_opacity,
@@ -39,6 +41,7 @@ use data::{
 };
 
 // 创建一个全局变量来跟踪Win+D状态
+#[cfg(target_os = "windows")]
 static WIN_D_PRESSED: AtomicBool = AtomicBool::new(false);
 
 // Tauri 命令：获取应用版本
@@ -98,47 +101,37 @@ async fn test_notification(app: tauri::AppHandle) -> Result<(), String> {
     Ok(())
 }
 
-// 检查是否已经有一个实例在运行
-#[cfg(target_os = "windows")]
-fn is_single_instance() -> bool {
-    unsafe {
-        let app_name = "DeskHive_Single_Instance_Mutex";
-        let wide_name: Vec<u16> = OsStr::new(app_name).encode_wide().chain(Some(0)).collect();
-        
-        let mutex = CreateMutexW(
-            None,
-            true,
-            windows::core::PCWSTR(wide_name.as_ptr()),
-        );
-        
-        match mutex {
-            Ok(handle) => {
-                let last_error = windows::Win32::Foundation::GetLastError();
-                if last_error.0 == 183 { // ERROR_ALREADY_EXISTS
-                    let _ = CloseHandle(handle);
-                    return false; // 已经存在实例
-                }
-                true // 没有其他实例在运行
-            }
-            Err(_) => {
-                false // 创建互斥锁失败
-            }
+// 整理数据：检查数据问题（只记录日志），归档超过设定天数的已完成任务，合并同步工具生成的冲突副本
+async fn prepare_data(app: tauri::AppHandle) {
+    data::integrity::check_at_startup(&app);
+
+    if let Err(e) = data::archive::archive_old_todos(app.clone()).await {
+        log::error!("归档已完成任务失败: {}", e);
+    }
+
+    match sync::folder::merge_all_conflicted_copies(&app) {
+        Ok(report) if !report.is_empty() => {
+            log::info!("已合并冲突副本: 新增 {}，修改 {}，删除 {}", report.added, report.updated, report.removed);
         }
+        Ok(_) => {}
+        Err(e) => log::error!("合并冲突副本失败: {}", e),
     }
 }
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
-    // 检查是否已经有一个实例在运行
-    #[cfg(target_os = "windows")]
-    {
-        if !is_single_instance() {
-            // 已经有一个实例在运行，直接退出
-            return;
-        }
+    // 带命令参数启动时作为命令行工具运行
+    if let Some(code) = cli::run_from_args() {
+        std::process::exit(code);
     }
-    
+
     tauri::Builder::default()
+        // 只允许运行一个实例，再次启动时把参数和工作目录转发给已运行的实例后退出
+        .plugin(tauri_plugin_single_instance::init(|app, argv, cwd| {
+            system::instance::handle_second_instance(app, argv, cwd);
+        }))
+        .plugin(tauri_plugin_deep_link::init())
+        .plugin(tauri_plugin_global_shortcut::Builder::new().build())
         .plugin(tauri_plugin_notification::init())
         .invoke_handler(tauri::generate_handler![
             // 数据相关命令
@@ -150,11 +143,43 @@ pub fn run() {
             load_todo_data_with_groups,
             save_group_data,
             load_group_data,
+            add_todo,
+            update_todo,
+            set_todo_completed,
+            delete_todo,
+            export_todos,
             save_app_settings,
             load_app_settings,
             apply_opacity,
             save_window_position,
             load_window_position,
+            data::archive::archive_completed_todos,
+            data::archive::list_archive_months,
+            data::archive::search_archive,
+            data::archive::export_archive,
+            data::archive::restore_archived_todos,
+            data::groups::create_group,
+            data::groups::rename_group,
+            data::groups::set_group_metadata,
+            data::groups::move_group,
+            data::groups::delete_group,
+            data::groups::reorder_groups,
+            data::groups::set_group_collapsed,
+            data::groups::get_group_counts,
+            data::integrity::check_data_integrity,
+            api::regenerate_api_token,
+            data::history::get_todo_history,
+            data::history::restore_data_from_history,
+            data::encryption::get_encryption_status,
+            data::encryption::enable_encryption,
+            data::encryption::unlock_data,
+            data::encryption::change_encryption_passphrase,
+            data::encryption::disable_encryption,
+            sync::folder::merge_sync_conflicts,
+            sync::webdav::sync_webdav_now,
+            sync::webdav::get_webdav_sync_status,
+            sync::caldav::sync_caldav_now,
+            sync::caldav::get_caldav_sync_status,
             
             // 窗口管理命令
             window::management::toggle_main_window,
@@ -164,29 +189,71 @@ pub fn run() {
             window::management::open_settings_window,
             window::management::close_settings_window,
             window::management::reset_window_position,
+            window::quick_add::open_quick_add_window,
+            window::quick_add::close_quick_add_window,
+            window::quick_add::get_quick_add_groups,
+            window::quick_add::submit_quick_add,
             
             // 系统相关命令
             system::date_info::get_current_date,
+            system::date_info::get_date_info,
+            data::holidays::is_workday,
+            data::holidays::next_workday,
+            data::holidays::import_holiday_calendar,
+            data::holidays::clear_holiday_overrides,
+            system::app_lock::get_app_lock_status,
+            system::app_lock::lock_app,
+            system::app_lock::unlock_app,
+            system::app_lock::set_app_lock_pin,
+            system::app_lock::report_user_activity,
+            system::deep_link::get_pending_deep_link_adds,
+            system::deep_link::confirm_deep_link_add,
             get_app_version,
             is_dev_mode,
             quit_app,
             emit_theme_changed,
             emit_priority_color_changed,
-            test_notification
+            test_notification,
+            
+            // 统计相关命令
+            stats::get_statistics,
+            calendar::get_calendar_month,
+            calendar::timeline::get_timeline
         ])
         .setup(|app| {
-            // 初始化日志系统
-            if cfg!(debug_assertions) {
-                app.handle().plugin(
-                    tauri_plugin_log::Builder::default()
-                        .level(log::LevelFilter::Info)
-                        .build(),
-                )?;
-            }
+            // 初始化日志系统：发布版本同样需要记录日志（启动时的数据检查结果、同步错误等），写入日志目录
+            app.handle().plugin(
+                tauri_plugin_log::Builder::default()
+                    .level(log::LevelFilter::Info)
+                    .build(),
+            )?;
 
             // 创建系统托盘
             system::tray::create_tray(app)?;
 
+            // 设置了PIN时以锁定状态启动
+            system::app_lock::init_app_lock(app.handle());
+
+            // 读取数据加密配置，加密的数据需要解锁后才能整理
+            let data_dir = data::todo_data::get_data_dir(app.handle())?;
+            data::encryption::init_encryption(&data_dir);
+            data::holidays::init_holiday_calendar(&data_dir);
+            if data::encryption::is_locked() {
+                log::info!("数据已加密，等待解锁");
+                let unlock_handle = app.handle().clone();
+                app.listen("data-unlocked", move |_| {
+                    tauri::async_runtime::spawn(prepare_data(unlock_handle.clone()));
+                });
+            } else {
+                // 在前端加载数据之前完成
+                tauri::async_runtime::block_on(prepare_data(app.handle().clone()));
+            }
+
+            // 监听数据目录，外部修改数据文件时通知前端重新加载
+            if let Err(e) = data::watcher::start_data_watcher(app.handle().clone()) {
+                log::error!("启动数据文件监听失败: {}", e);
+            }
+
             // 启动通知检查定时器
             let app_handle = app.handle().clone();
             std::thread::spawn(move || {
@@ -198,6 +265,21 @@ pub fn run() {
                 }
             });
 
+            // 启动WebDAV、CalDAV后台同步（未启用时只做设置检查）
+            sync::webdav::start_webdav_sync(app.handle().clone());
+            sync::caldav::start_caldav_sync(app.handle().clone());
+
+            // 启动本机HTTP接口和本地控制接口（未启用时只做设置检查）
+            api::http::start_api_server(app.handle().clone());
+            api::rpc::start_rpc_server(app.handle().clone());
+            api::rpc::hold_instance_marker();
+
+            // 注册 deskhive:// 链接并处理启动时打开的链接
+            system::deep_link::init_deep_link(app.handle());
+
+            // 注册快速添加任务的全局快捷键
+            window::quick_add::init_quick_add_shortcut(app.handle());
+
             // 获取主窗口
             if let Some(window) = app.get_webview_window("main") {
                 // 同步加载并应用保存的设置和位置（在显示窗口之前）
@@ -0,0 +1,101 @@
+use serde::{Deserialize, Serialize};
+
+use super::Todo;
+
+// 日历中的一天
+#[derive(Serialize, Clone)]
+pub struct CalendarDay {
+    pub date: String,                   // 日期，格式：2024-09-17
+    pub day: u32,                       // 公历日，格式：17
+    pub weekday: u32,                   // 星期，0=星期一 … 6=星期日
+    pub is_today: bool,                 // 是否为今天
+    pub lunar_month: String,            // 农历月份，格式：八月、闰四月
+    pub lunar_day: String,              // 农历日期，格式：十五
+    pub lunar_label: String,            // 日历格子中显示的农历文字：节日 > 节气 > 农历初一显示月份 > 农历日期
+    pub festivals: Vec<String>,         // 当天的节日
+    pub solar_term: Option<String>,     // 当天的节气
+    pub is_workday: bool,               // 是否为工作日（考虑法定节假日和调休）
+    pub is_holiday: bool,               // 是否为法定节假日放假
+    pub is_adjusted_workday: bool,      // 是否为调休上班的日期
+    pub holiday_name: Option<String>,   // 放假或调休对应的节日名称
+    pub due: Vec<Todo>,                 // 当天截止的未完成任务（按截止时间排列）
+    pub scheduled: Vec<Todo>,           // 按创建时间排在当天的未完成任务
+    pub completed: Vec<Todo>,           // 当天完成的任务（按完成时间排列）
+}
+
+// 日历中的一个月
+#[derive(Serialize, Clone)]
+pub struct CalendarMonth {
+    pub year: i32,
+    pub month: u32,
+    pub deadline_priority: bool,        // 是否按截止时间排列未完成的任务（timeline_deadline_priority 设置）
+    pub days: Vec<CalendarDay>,         // 当月每一天
+}
+
+// 时间轴的日期范围（包含首尾两天，格式：2024-09-17），未指定的一端不限制
+#[derive(Deserialize, Clone, Default)]
+pub struct TimelineRange {
+    #[serde(default)]
+    pub start: Option<String>,
+    #[serde(default)]
+    pub end: Option<String>,
+}
+
+// 时间轴选项
+#[derive(Deserialize, Clone, Default)]
+pub struct TimelineOptions {
+    #[serde(default)]
+    pub include_completed: bool,          // 是否包含已完成的任务（放在最后的"已完成"分组中）
+    #[serde(default)]
+    pub group_id: Option<String>,         // 只包含该分组及其下级分组的任务
+    #[serde(default)]
+    pub deadline_priority: Option<bool>,  // 是否优先按截止时间排列，未指定时使用 timeline_deadline_priority 设置
+    #[serde(default)]
+    pub locale: TimelineLocale,           // 分组名称和星期使用的语言
+}
+
+// 时间轴分组名称的语言
+#[derive(Deserialize, Clone, Copy, Default, PartialEq)]
+pub enum TimelineLocale {
+    #[default]
+    #[serde(rename = "zh-CN")]
+    ZhCn,   // 中文：今天、9月17日、周二
+    #[serde(rename = "en")]
+    En,     // 英文：Today、Sep 17、Tue
+}
+
+// 时间轴中任务的状态
+#[derive(Serialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum TimelineStatus {
+    Pending,    // 未完成
+    Overdue,    // 已过截止时间但未完成
+    Completed,  // 已完成
+}
+
+// 时间轴中的一项
+#[derive(Serialize, Clone)]
+pub struct TimelineItem {
+    pub todo: Todo,
+    pub display_time: i64,         // 用于排序和显示的时间（截止时间、创建时间或完成时间），Unix时间戳（秒）
+    pub status: TimelineStatus,
+}
+
+// 时间轴中的一个分组：已逾期、某一天或已完成
+#[derive(Serialize, Clone)]
+pub struct TimelineBucket {
+    pub key: String,               // "overdue"、"completed" 或日期（格式：2024-09-17）
+    pub label: String,             // 显示的名称，格式：已逾期、今天、明天、9月17日、2025年1月3日（英文：Overdue、Today、Sep 17、Jan 3, 2025）
+    pub weekday: Option<String>,   // 日期分组的星期，格式：周二（英文：Tue）
+    pub items: Vec<TimelineItem>,
+}
+
+// 时间轴
+#[derive(Serialize, Clone)]
+pub struct Timeline {
+    pub deadline_priority: bool,   // 是否优先按截止时间排列
+    pub buckets: Vec<TimelineBucket>,
+    pub pending_count: u32,        // 未完成（未逾期）的任务数
+    pub overdue_count: u32,        // 已逾期的任务数
+    pub completed_count: u32,      // 已完成的任务数
+}
@@ -6,7 +6,34 @@ pub struct DateInfo {
     pub solar_date: String,      // 公历日期，格式：2024年8月28日
     pub lunar_date: String,      // 农历日期，格式：甲辰年七月廿五
     pub weekday: String,         // 星期，格式：星期三
-    pub lunar_year: String,      // 农历年份，格式：甲辰年（龙年）
-    pub lunar_month: String,     // 农历月份，格式：七月
+    pub lunar_year: String,      // 农历年份，格式：甲辰年
+    pub lunar_month: String,     // 农历月份，格式：七月、闰四月
     pub lunar_day: String,       // 农历日期，格式：廿五
+    pub zodiac: String,          // 生肖，格式：龙
+    pub is_leap_month: bool,     // 是否为农历闰月
+    pub solar_term: Option<String>,           // 当天的节气，格式：立春
+    pub next_solar_term: Option<UpcomingDay>, // 下一个节气
+    pub festivals: Vec<String>,               // 当天的节日，格式：["中秋节", "国庆节"]
+    pub next_festival: Option<UpcomingDay>,   // 下一个节日
+    pub is_workday: bool,                     // 是否为工作日（考虑法定节假日和调休）
+    pub is_holiday: bool,                     // 是否为法定节假日放假
+    pub is_adjusted_workday: bool,            // 是否为调休上班的日期
+    pub holiday_name: Option<String>,         // 放假或调休对应的节日名称，格式：国庆节
+}
+
+// 节假日安排中的一天，导入时兼容 holiday-cn 的 isOffDay 字段名
+#[derive(Serialize, Deserialize, Clone)]
+pub struct HolidayDay {
+    pub date: String,            // 日期，格式：2024-10-01
+    pub name: String,            // 节日名称，格式：国庆节
+    #[serde(alias = "isOffDay")]
+    pub is_off_day: bool,        // true 为放假，false 为调休上班
+}
+
+// 即将到来的节日或节气
+#[derive(Serialize, Deserialize, Clone)]
+pub struct UpcomingDay {
+    pub name: String,            // 名称，同一天有多个节日时用顿号连接
+    pub date: String,            // 日期，格式：2024-09-17
+    pub days_until: i64,         // 距离当天的天数
 }
\ No newline at end of file
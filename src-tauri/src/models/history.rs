@@ -0,0 +1,12 @@
+use serde::Serialize;
+
+use super::todo::Todo;
+
+// 任务在历史记录中的一个版本
+#[derive(Serialize, Clone)]
+pub struct TodoHistoryEntry {
+    pub commit: String,      // 提交ID
+    pub timestamp: i64,      // 提交时间，Unix时间戳（秒）
+    pub message: String,     // 提交说明
+    pub todo: Option<Todo>,  // 该版本中的任务，None 表示任务在此次提交中被删除或归档
+}
@@ -0,0 +1,28 @@
+use serde::Serialize;
+
+// 数据问题的严重程度
+#[derive(Serialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum IssueSeverity {
+    Info,     // 不影响使用，例如缺少可推断的字段
+    Warning,  // 数据不一致，可能导致显示或统计错误
+    Error,    // 可能导致数据丢失或覆盖，例如重复的ID
+}
+
+// 检查发现的单个问题
+#[derive(Serialize, Clone)]
+pub struct IntegrityIssue {
+    pub severity: IssueSeverity,
+    pub kind: String,             // 问题类型，如 duplicate_todo_id
+    pub item_id: String,          // 相关的任务或分组ID
+    pub message: String,          // 问题描述
+    pub fixable: bool,            // 是否可以自动修复
+}
+
+// 数据完整性检查结果
+#[derive(Serialize, Clone, Default)]
+pub struct IntegrityReport {
+    pub issues: Vec<IntegrityIssue>,
+    pub fixed: bool,                   // 是否已修复可修复的问题
+    pub backup_dir: Option<String>,    // 修复前的备份目录
+}
@@ -2,8 +2,20 @@ pub mod todo;
 pub mod settings;
 pub mod date;
 pub mod window;
+pub mod stats;
+pub mod sync;
+pub mod history;
+pub mod security;
+pub mod integrity;
+pub mod calendar;
 
 pub use todo::*;
 pub use settings::*;
 pub use date::*;
-pub use window::*;
\ No newline at end of file
+pub use window::*;
+pub use stats::*;
+pub use sync::*;
+pub use history::*;
+pub use security::*;
+pub use integrity::*;
+pub use calendar::*;
\ No newline at end of file
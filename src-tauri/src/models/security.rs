@@ -0,0 +1,16 @@
+use serde::Serialize;
+
+// 数据加密状态
+#[derive(Serialize, Clone)]
+pub struct EncryptionStatus {
+    pub enabled: bool,   // 是否启用了数据加密
+    pub unlocked: bool,  // 是否已输入密码解锁
+}
+
+// 应用锁状态
+#[derive(Serialize, Clone)]
+pub struct AppLockStatus {
+    pub enabled: bool,           // 是否设置了PIN
+    pub locked: bool,            // 当前是否处于锁定状态
+    pub auto_lock_minutes: u32,  // 空闲自动锁定时间（分钟），0表示不自动锁定
+}
@@ -25,6 +25,48 @@ pub struct AppSettings {
     pub enable_deadline_notification: bool, // 是否启用截止时间通知
     #[serde(default = "default_notification_minutes_before")]
     pub notification_minutes_before: u32, // 提前多少分钟通知
+    #[serde(default = "default_archive_after_days")]
+    pub archive_after_days: u32, // 已完成任务超过多少天后归档，0表示不归档
+    #[serde(default = "default_webdav_enabled")]
+    pub webdav_enabled: bool, // 是否启用WebDAV同步
+    #[serde(default = "default_webdav_url")]
+    pub webdav_url: String, // WebDAV目录地址，例如 https://example.com/remote.php/dav/files/user/DeskHive
+    #[serde(default = "default_webdav_username")]
+    pub webdav_username: String, // WebDAV用户名
+    #[serde(default = "default_webdav_password")]
+    pub webdav_password: String, // WebDAV密码（建议使用应用专用密码），保存在系统钥匙串中，这里只会出现旧版本保存的明文密码
+    #[serde(default = "default_webdav_sync_interval_minutes")]
+    pub webdav_sync_interval_minutes: u32, // WebDAV定时同步间隔（分钟）
+    #[serde(default = "default_caldav_enabled")]
+    pub caldav_enabled: bool, // 是否启用CalDAV任务同步
+    #[serde(default = "default_caldav_url")]
+    pub caldav_url: String, // CalDAV日历主目录地址，每个分组对应其中的一个任务列表，例如 https://example.com/remote.php/dav/calendars/user/
+    #[serde(default = "default_caldav_username")]
+    pub caldav_username: String, // CalDAV用户名
+    #[serde(default = "default_caldav_password")]
+    pub caldav_password: String, // CalDAV密码（建议使用应用专用密码），保存在系统钥匙串中，这里只会出现旧版本保存的明文密码
+    #[serde(default = "default_caldav_sync_interval_minutes")]
+    pub caldav_sync_interval_minutes: u32, // CalDAV定时同步间隔（分钟）
+    #[serde(default = "default_git_history_enabled")]
+    pub git_history_enabled: bool, // 是否用git记录数据目录的每次修改
+    #[serde(default = "default_app_lock_pin_hash")]
+    pub app_lock_pin_hash: String, // 应用锁PIN的Argon2哈希，为空表示未启用应用锁
+    #[serde(default = "default_auto_lock_minutes")]
+    pub auto_lock_minutes: u32, // 空闲多少分钟后自动锁定，0表示不自动锁定
+    #[serde(default = "default_api_enabled")]
+    pub api_enabled: bool, // 是否启用本机HTTP接口
+    #[serde(default = "default_api_port")]
+    pub api_port: u16, // 本机HTTP接口端口（只监听127.0.0.1）
+    #[serde(default = "default_api_token")]
+    pub api_token: String, // 本机HTTP接口的访问令牌，启用时自动生成
+    #[serde(default = "default_rpc_enabled")]
+    pub rpc_enabled: bool, // 是否启用本地控制接口（当前用户的Unix套接字或命名管道）
+    #[serde(default = "default_quick_add_shortcut")]
+    pub quick_add_shortcut: String, // 快速添加任务的全局快捷键，为空时不启用
+    #[serde(default = "default_skip_non_workdays_for_deadlines")]
+    pub skip_non_workdays_for_deadlines: bool, // 默认截止时间遇到节假日和周末时顺延到下一个工作日
+    #[serde(default = "default_skip_non_workdays_for_reminders")]
+    pub skip_non_workdays_for_reminders: bool, // 截止提醒遇到节假日和周末时提前到上一个工作日
 }
 
 impl Default for AppSettings {
@@ -42,6 +84,27 @@ impl Default for AppSettings {
             timeline_deadline_priority: true,
             enable_deadline_notification: false,
             notification_minutes_before: 30,
+            archive_after_days: 0,
+            webdav_enabled: false,
+            webdav_url: "".to_string(),
+            webdav_username: "".to_string(),
+            webdav_password: "".to_string(),
+            webdav_sync_interval_minutes: 15,
+            caldav_enabled: false,
+            caldav_url: "".to_string(),
+            caldav_username: "".to_string(),
+            caldav_password: "".to_string(),
+            caldav_sync_interval_minutes: 15,
+            git_history_enabled: false,
+            app_lock_pin_hash: "".to_string(),
+            auto_lock_minutes: 5,
+            api_enabled: false,
+            api_port: 27121,
+            api_token: "".to_string(),
+            rpc_enabled: false,
+            quick_add_shortcut: "CommandOrControl+Alt+N".to_string(),
+            skip_non_workdays_for_deadlines: false,
+            skip_non_workdays_for_reminders: false,
         }
     }
 }
@@ -85,4 +148,88 @@ pub fn default_enable_deadline_notification() -> bool {
 
 pub fn default_notification_minutes_before() -> u32 {
     30
+}
+
+pub fn default_archive_after_days() -> u32 {
+    0 // 默认不归档，需要在设置中开启
+}
+
+pub fn default_webdav_enabled() -> bool {
+    false
+}
+
+pub fn default_webdav_url() -> String {
+    "".to_string()
+}
+
+pub fn default_webdav_username() -> String {
+    "".to_string()
+}
+
+pub fn default_webdav_password() -> String {
+    "".to_string()
+}
+
+pub fn default_webdav_sync_interval_minutes() -> u32 {
+    15
+}
+
+pub fn default_caldav_enabled() -> bool {
+    false
+}
+
+pub fn default_caldav_url() -> String {
+    "".to_string()
+}
+
+pub fn default_caldav_username() -> String {
+    "".to_string()
+}
+
+pub fn default_caldav_password() -> String {
+    "".to_string()
+}
+
+pub fn default_caldav_sync_interval_minutes() -> u32 {
+    15
+}
+
+pub fn default_git_history_enabled() -> bool {
+    false
+}
+
+pub fn default_app_lock_pin_hash() -> String {
+    "".to_string()
+}
+
+pub fn default_auto_lock_minutes() -> u32 {
+    5
+}
+
+pub fn default_api_enabled() -> bool {
+    false
+}
+
+pub fn default_api_port() -> u16 {
+    27121
+}
+
+pub fn default_api_token() -> String {
+    "".to_string()
+}
+
+pub fn default_rpc_enabled() -> bool {
+    false
+}
+
+pub fn default_quick_add_shortcut() -> String {
+    "CommandOrControl+Alt+N".to_string()
+}
+
+pub fn default_skip_non_workdays_for_deadlines() -> bool {
+    false
+}
+
+pub fn default_skip_non_workdays_for_reminders() -> bool {
+    false
 }
\ No newline at end of file
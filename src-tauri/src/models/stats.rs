@@ -0,0 +1,40 @@
+use serde::Serialize;
+
+// 每日统计
+#[derive(Serialize, Clone)]
+pub struct DailyStatistics {
+    pub date: String,    // 日期，格式：2024-08-28
+    pub created: u32,    // 当天创建的任务数
+    pub completed: u32,  // 当天完成的任务数
+}
+
+// 分组统计
+#[derive(Serialize, Clone)]
+pub struct GroupStatistics {
+    pub group_id: String,
+    pub group_name: String,
+    pub parent_id: Option<String>,  // 上级分组ID，None 表示顶层分组
+    pub created: u32,          // 统计范围内创建的任务数
+    pub completed: u32,        // 统计范围内完成的任务数
+    pub pending: u32,          // 当前未完成的任务数
+    pub completion_rate: f64,  // 统计范围内创建的任务的完成率（0.0 ~ 1.0）
+}
+
+// 效率统计结果
+#[derive(Serialize, Clone)]
+pub struct Statistics {
+    pub range: String,                         // 统计范围：today | week | month | year | all
+    pub start_date: String,                    // 统计起始日期
+    pub end_date: String,                      // 统计结束日期
+    pub daily: Vec<DailyStatistics>,           // 每日创建/完成数
+    pub total_created: u32,                    // 范围内创建的任务数
+    pub total_completed: u32,                  // 范围内完成的任务数
+    pub completion_rate: f64,                  // 范围内创建的任务的完成率（0.0 ~ 1.0）
+    pub average_lead_time_secs: Option<i64>,   // 平均完成耗时（秒），从创建到完成
+    pub on_time: u32,                          // 按时完成（完成时间不晚于截止时间）
+    pub late: u32,                             // 逾期完成
+    pub overdue_pending: u32,                  // 当前已过截止时间但未完成
+    pub groups: Vec<GroupStatistics>,          // 分组统计
+    pub current_streak: u32,                   // 当前连续完成天数
+    pub longest_streak: u32,                   // 最长连续完成天数
+}
@@ -0,0 +1,41 @@
+use serde::Serialize;
+
+// 合并时发生冲突的字段（两端都在上次同步后修改了该字段）
+#[derive(Serialize, Clone)]
+pub struct FieldConflict {
+    pub item_id: String,
+    pub field: String,
+    pub kept: String, // 保留的版本：local | remote
+}
+
+// 合并结果统计（相对于本地数据）
+#[derive(Serialize, Clone, Default)]
+pub struct MergeReport {
+    pub added: u32,                     // 从远端新增的数据项
+    pub updated: u32,                   // 被远端修改的数据项
+    pub removed: u32,                   // 被远端删除的数据项
+    pub conflicts: Vec<FieldConflict>,  // 冲突字段及其处理结果
+}
+
+impl MergeReport {
+    // 合并另一份统计
+    pub fn absorb(&mut self, other: MergeReport) {
+        self.added += other.added;
+        self.updated += other.updated;
+        self.removed += other.removed;
+        self.conflicts.extend(other.conflicts);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.added == 0 && self.updated == 0 && self.removed == 0 && self.conflicts.is_empty()
+    }
+}
+
+// 同步状态（WebDAV、CalDAV）
+#[derive(Serialize, Clone, Default)]
+pub struct SyncStatus {
+    pub syncing: bool,                // 是否正在同步
+    pub last_sync: Option<i64>,       // 上次成功同步的时间，Unix时间戳（秒）
+    pub last_error: Option<String>,   // 上次同步失败的原因
+    pub last_report: MergeReport,     // 上次同步的合并结果
+}
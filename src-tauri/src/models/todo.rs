@@ -1,4 +1,5 @@
-use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use serde::{Deserialize, Deserializer, Serialize};
 use uuid::Uuid;
 
 #[derive(Serialize, Deserialize, Clone)]
@@ -18,6 +19,12 @@ pub struct Todo {
     pub group_id: String, // 所属分组ID
     #[serde(default)] // 为了兼容旧数据，设为默认值
     pub priority: i32, // 优先级：0=普通，1=重要
+    #[serde(default)] // 为了兼容旧数据，设为默认值
+    pub tags: Vec<String>, // 标签
+    #[serde(default)] // 为了兼容旧数据，设为默认值
+    pub updated_at: i64, // 最后修改时间，Unix时间戳（秒），用于同步合并
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub field_updated_at: BTreeMap<String, i64>, // 各字段的最后修改时间，未记录的字段以updated_at为准
 }
 
 // 默认分组ID
@@ -30,6 +37,25 @@ fn generate_uuid() -> String {
     Uuid::new_v4().to_string()
 }
 
+// 测试用的任务：内容与ID相同，属于默认分组，其余字段可用结构体更新语法覆盖
+#[cfg(test)]
+pub(crate) fn test_todo(id: &str, created_at: i64, deadline: Option<i64>, completed_at: Option<i64>) -> Todo {
+    Todo {
+        id: id.to_string(),
+        text: id.to_string(),
+        completed: completed_at.is_some(),
+        created_at,
+        completed_at,
+        deadline,
+        order: 0,
+        group_id: default_group_id(),
+        priority: 0,
+        tags: Vec::new(),
+        updated_at: 0,
+        field_updated_at: BTreeMap::new(),
+    }
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct TodoData {
     pub pending_todos: Vec<Todo>,
@@ -37,20 +63,93 @@ pub struct TodoData {
 }
 
 
-#[derive(Serialize, Deserialize, Clone)]
+#[derive(Serialize, Deserialize, Clone, Default)]
 pub struct TodoGroup {
     pub id: String,
     pub name: String,
     pub order: i32,
     pub collapsed: bool,
+    #[serde(default)] // 为了兼容旧数据，设为默认值
+    pub parent_id: Option<String>, // 上级分组ID，None 表示顶层分组
+    #[serde(default)] // 为了兼容旧数据，设为默认值
+    pub color: Option<String>, // 分组颜色，格式：#RRGGBB
+    #[serde(default)] // 为了兼容旧数据，设为默认值
+    pub icon: Option<String>, // 分组图标（emoji）
+    #[serde(default)] // 为了兼容旧数据，设为默认值
+    pub default_priority: i32, // 在该分组中新建任务的默认优先级
+    #[serde(default)] // 为了兼容旧数据，设为默认值
+    pub default_deadline_offset: Option<String>, // 在该分组中新建任务的默认截止时间，如 "+2d 18:00"
+    #[serde(default)] // 为了兼容旧数据，设为默认值
+    pub updated_at: i64, // 最后修改时间，Unix时间戳（秒），用于同步合并
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub field_updated_at: BTreeMap<String, i64>, // 各字段的最后修改时间，未记录的字段以updated_at为准
 }
 
-#[derive(Serialize, Deserialize)]
+// 删除记录（墓碑），用于同步时传播删除操作
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Tombstone {
+    pub id: String,
+    pub deleted_at: i64, // 删除时间，Unix时间戳（秒）
+}
+
+#[derive(Serialize, Deserialize, Default)]
 pub struct TodoDataWithGroups {
     pub todos: Vec<Todo>,
+    #[serde(default)] // 为了兼容旧数据，设为默认值
+    pub tombstones: Vec<Tombstone>,
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Default)]
 pub struct GroupData {
     pub groups: Vec<TodoGroup>,
+    #[serde(default)] // 为了兼容旧数据，设为默认值
+    pub tombstones: Vec<Tombstone>,
+}
+
+// 删除分组时对分组中任务的处理方式
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum GroupDeleteStrategy {
+    MoveToDefault,                             // 移到默认分组
+    MoveToGroup { target_group_id: String },   // 移到指定分组
+    DeleteTodos,                               // 从任务列表中移除这些任务（移到归档中，可以恢复）
+}
+
+// 分组的任务数，total_* 包含所有下级分组中的任务
+#[derive(Serialize, Clone)]
+pub struct GroupTodoCounts {
+    pub group_id: String,
+    pub pending: u32,
+    pub completed: u32,
+    pub total_pending: u32,
+    pub total_completed: u32,
+}
+
+// 导出文件：任务及其所属分组（包括分组颜色和图标）
+#[derive(Serialize)]
+pub struct TodoExport {
+    pub todos: Vec<Todo>,
+    pub groups: Vec<TodoGroup>,
+}
+
+// 修改任务时传入的字段，未传入的字段保持不变
+#[derive(Deserialize, Default)]
+pub struct TodoPatch {
+    #[serde(default)]
+    pub text: Option<String>,
+    #[serde(default, deserialize_with = "deserialize_some")]
+    pub deadline: Option<Option<i64>>, // 传入 null 表示移除截止时间
+    #[serde(default)]
+    pub priority: Option<i32>,
+    #[serde(default)]
+    pub group_id: Option<String>,
+    #[serde(default)]
+    pub order: Option<i32>,
+    #[serde(default)]
+    pub tags: Option<Vec<String>>,
+}
+
+// 区分字段未传入（None）和传入 null（Some(None)）
+fn deserialize_some<'de, D: Deserializer<'de>, T: Deserialize<'de>>(deserializer: D) -> Result<Option<T>, D::Error> {
+    T::deserialize(deserializer).map(Some)
 }
@@ -18,6 +18,11 @@ impl NotificationManager {
 
     /// 检查并发送截止时间通知
     pub fn check_and_notify(&self) {
+        // 锁定时不读取任务，避免通知内容泄露任务标题
+        if crate::system::app_lock::is_app_locked() {
+            return;
+        }
+
         // 使用阻塞方式加载设置
         let app_handle = self.app.clone();
         let settings = tauri::async_runtime::block_on(async {
@@ -37,11 +42,9 @@ impl NotificationManager {
             return;
         }
 
-        // 使用阻塞方式加载待办事项数据
-        let app_handle = self.app.clone();
-        let todo_data = tauri::async_runtime::block_on(async {
-            crate::data::load_todo_data_with_groups(app_handle).await
-        });
+        // 加载待办事项数据
+        let todo_data = crate::data::todo_data::get_data_dir(&self.app)
+            .and_then(|data_dir| crate::data::todo_data::read_todo_store(&data_dir));
 
         let todo_data = match todo_data {
             Ok(data) => data,
@@ -72,10 +75,17 @@ impl NotificationManager {
             // 计算距离截止时间的秒数
             let time_until_deadline = deadline - now;
 
-            // 检查是否在通知时间窗口内（提前时间 ± 30秒）
+            // 提醒时间，设置了跳过非工作日时提前到上一个工作日的同一时刻
+            let mut remind_at = deadline - notification_threshold;
+            if settings.skip_non_workdays_for_reminders {
+                if let Some(time) = chrono::DateTime::from_timestamp(remind_at, 0) {
+                    remind_at = crate::data::holidays::move_to_workday(time.with_timezone(&chrono::Local), false).timestamp();
+                }
+            }
+
+            // 检查是否在通知时间窗口内（提醒时间 ± 30秒）
             let should_notify = time_until_deadline > 0 
-                && time_until_deadline <= notification_threshold + 30
-                && time_until_deadline >= notification_threshold - 30;
+                && (now - remind_at).abs() <= 30;
 
             if should_notify && !notified.contains(&todo.id) {
                 // 发送通知
@@ -104,7 +114,7 @@ impl NotificationManager {
         // 格式化截止时间
         let deadline = todo.deadline.unwrap_or(0);
         let deadline_time = chrono::DateTime::from_timestamp(deadline, 0)
-            .unwrap_or_else(|| chrono::Utc::now());
+            .unwrap_or_else(chrono::Utc::now);
         
         let formatted_deadline = deadline_time
             .with_timezone(&chrono::Local)
@@ -121,6 +131,9 @@ impl NotificationManager {
         // 构建通知标题
         let title = if minutes_left == 0 {
             "你有一个即将到期的任务，请及时处理".to_string()
+        } else if minutes_left >= 24 * 60 {
+            // 提醒提前到了上一个工作日
+            format!("你有一个 {} 天后到期的任务，请及时处理", minutes_left / (24 * 60))
         } else {
             format!("你有一个 {} 分钟内到期的任务，请及时处理", minutes_left)
         };
@@ -0,0 +1,349 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use chrono::{DateTime, Duration, Local, NaiveDate, TimeZone};
+
+use crate::models::{DailyStatistics, GroupStatistics, Statistics, Todo, TodoGroup};
+use crate::system::app_lock::ensure_unlocked;
+
+// 将Unix时间戳（秒）转换为本地日期
+fn local_date(timestamp: i64) -> Option<NaiveDate> {
+    Local.timestamp_opt(timestamp, 0)
+        .single()
+        .map(|dt| dt.date_naive())
+}
+
+// 计算比率，分母为0时返回0
+fn ratio(numerator: u32, denominator: u32) -> f64 {
+    if denominator == 0 {
+        0.0
+    } else {
+        numerator as f64 / denominator as f64
+    }
+}
+
+// 根据统计范围计算起始日期
+fn range_start(range: &str, today: NaiveDate, todos: &[Todo]) -> Result<NaiveDate, String> {
+    let start = match range {
+        "today" => today,
+        "week" => today - Duration::days(6),
+        "month" => today - Duration::days(29),
+        "year" => today - Duration::days(364),
+        "all" => todos.iter()
+            .filter_map(|todo| local_date(todo.created_at))
+            .min()
+            .unwrap_or(today)
+            .min(today),
+        _ => return Err(format!("不支持的统计范围: {}", range)),
+    };
+    Ok(start)
+}
+
+// 计算连续完成天数（当前连续天数，最长连续天数）
+// 当天还没有完成任务时，当前连续天数从昨天开始往前计算
+fn compute_streaks(days: &BTreeSet<NaiveDate>, today: NaiveDate) -> (u32, u32) {
+    let mut longest = 0;
+    let mut run = 0;
+    let mut previous: Option<NaiveDate> = None;
+    for day in days {
+        run = match previous {
+            Some(prev) if *day - prev == Duration::days(1) => run + 1,
+            _ => 1,
+        };
+        longest = longest.max(run);
+        previous = Some(*day);
+    }
+
+    let mut current = 0;
+    let mut cursor = if days.contains(&today) {
+        today
+    } else {
+        today - Duration::days(1)
+    };
+    while days.contains(&cursor) {
+        current += 1;
+        cursor -= Duration::days(1);
+    }
+
+    (current, longest)
+}
+
+// 计算效率统计
+pub fn compute_statistics(
+    todos: &[Todo],
+    groups: &[TodoGroup],
+    range: &str,
+    now: DateTime<Local>,
+) -> Result<Statistics, String> {
+    let today = now.date_naive();
+    let start = range_start(range, today, todos)?;
+    let in_range = |date: NaiveDate| date >= start && date <= today;
+
+    // 初始化每日统计
+    let mut daily: BTreeMap<NaiveDate, (u32, u32)> = BTreeMap::new();
+    let mut day = start;
+    while day <= today {
+        daily.insert(day, (0, 0));
+        day += Duration::days(1);
+    }
+
+    // 分组统计：(创建数, 创建且已完成数, 完成数, 未完成数)
+    let mut group_counts: HashMap<&str, (u32, u32, u32, u32)> = HashMap::new();
+
+    let mut total_created = 0;
+    let mut created_and_completed = 0;
+    let mut total_completed = 0;
+    let mut lead_time_sum: i64 = 0;
+    let mut on_time = 0;
+    let mut late = 0;
+    let mut overdue_pending = 0;
+    let mut completion_days = BTreeSet::new();
+
+    for todo in todos {
+        let counts = group_counts.entry(todo.group_id.as_str()).or_default();
+
+        if !todo.completed {
+            counts.3 += 1;
+            if todo.deadline.is_some_and(|deadline| deadline < now.timestamp()) {
+                overdue_pending += 1;
+            }
+        }
+
+        if let Some(created) = local_date(todo.created_at).filter(|d| in_range(*d)) {
+            total_created += 1;
+            counts.0 += 1;
+            if todo.completed {
+                created_and_completed += 1;
+                counts.1 += 1;
+            }
+            if let Some(entry) = daily.get_mut(&created) {
+                entry.0 += 1;
+            }
+        }
+
+        // 只统计已完成且记录了完成时间的任务
+        let completed_at = match todo.completed_at {
+            Some(t) if todo.completed => t,
+            _ => continue,
+        };
+        let completed_date = match local_date(completed_at) {
+            Some(d) => d,
+            None => continue,
+        };
+        completion_days.insert(completed_date);
+
+        if !in_range(completed_date) {
+            continue;
+        }
+        total_completed += 1;
+        counts.2 += 1;
+        lead_time_sum += (completed_at - todo.created_at).max(0);
+        if let Some(entry) = daily.get_mut(&completed_date) {
+            entry.1 += 1;
+        }
+        if let Some(deadline) = todo.deadline {
+            if completed_at <= deadline {
+                on_time += 1;
+            } else {
+                late += 1;
+            }
+        }
+    }
+
+    let average_lead_time_secs = if total_completed > 0 {
+        Some(lead_time_sum / total_completed as i64)
+    } else {
+        None
+    };
+
+    // 按分组顺序输出，已删除分组中的任务单独列出
+    let mut sorted_groups: Vec<&TodoGroup> = groups.iter().collect();
+    sorted_groups.sort_by_key(|g| g.order);
+    let mut group_stats: Vec<GroupStatistics> = sorted_groups.iter()
+        .map(|group| {
+            let (created, created_done, completed, pending) = group_counts
+                .remove(group.id.as_str())
+                .unwrap_or_default();
+            GroupStatistics {
+                group_id: group.id.clone(),
+                group_name: group.name.clone(),
+                parent_id: group.parent_id.clone(),
+                created,
+                completed,
+                pending,
+                completion_rate: ratio(created_done, created),
+            }
+        })
+        .collect();
+    let mut orphaned: Vec<_> = group_counts.into_iter().collect();
+    orphaned.sort_by_key(|(id, _)| *id);
+    for (id, (created, created_done, completed, pending)) in orphaned {
+        group_stats.push(GroupStatistics {
+            group_id: id.to_string(),
+            group_name: id.to_string(),
+            parent_id: None,
+            created,
+            completed,
+            pending,
+            completion_rate: ratio(created_done, created),
+        });
+    }
+
+    let (current_streak, longest_streak) = compute_streaks(&completion_days, today);
+
+    Ok(Statistics {
+        range: range.to_string(),
+        start_date: start.format("%Y-%m-%d").to_string(),
+        end_date: today.format("%Y-%m-%d").to_string(),
+        daily: daily.into_iter()
+            .map(|(date, (created, completed))| DailyStatistics {
+                date: date.format("%Y-%m-%d").to_string(),
+                created,
+                completed,
+            })
+            .collect(),
+        total_created,
+        total_completed,
+        completion_rate: ratio(created_and_completed, total_created),
+        average_lead_time_secs,
+        on_time,
+        late,
+        overdue_pending,
+        groups: group_stats,
+        current_streak,
+        longest_streak,
+    })
+}
+
+// Tauri 命令：获取效率统计，指定 group_id 时只统计该分组及其下级分组
+#[tauri::command]
+pub async fn get_statistics(app: tauri::AppHandle, range: String, group_id: Option<String>) -> Result<Statistics, String> {
+    ensure_unlocked()?;
+    let data_dir = crate::data::todo_data::get_data_dir(&app)?;
+    let mut todo_data = crate::data::todo_data::read_todo_store(&data_dir)?;
+    let mut group_data = crate::data::todo_data::read_group_store(&data_dir)?;
+
+    if let Some(group_ids) = crate::data::groups::group_filter(&data_dir, group_id.as_deref())? {
+        todo_data.todos.retain(|todo| group_ids.contains(&todo.group_id));
+        group_data.groups.retain(|group| group_ids.contains(&group.id));
+    }
+
+    compute_statistics(&todo_data.todos, &group_data.groups, &range, Local::now())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::groups::subtree_ids;
+    use crate::models::test_todo;
+
+    fn at(year: i32, month: u32, day: u32, hour: u32) -> DateTime<Local> {
+        Local.with_ymd_and_hms(year, month, day, hour, 0, 0).unwrap()
+    }
+
+    fn todo(id: &str, group_id: &str, created: DateTime<Local>, completed: Option<DateTime<Local>>, deadline: Option<DateTime<Local>>) -> Todo {
+        Todo {
+            group_id: group_id.to_string(),
+            ..test_todo(id, created.timestamp(), deadline.map(|t| t.timestamp()), completed.map(|t| t.timestamp()))
+        }
+    }
+
+    fn group(id: &str, order: i32, parent_id: Option<&str>) -> TodoGroup {
+        TodoGroup {
+            id: id.to_string(),
+            name: id.to_string(),
+            order,
+            collapsed: false,
+            parent_id: parent_id.map(String::from),
+            color: None,
+            icon: None,
+            default_priority: 0,
+            default_deadline_offset: None,
+            updated_at: 0,
+            field_updated_at: Default::default(),
+        }
+    }
+
+    #[test]
+    fn streaks_count_back_from_today_or_yesterday() {
+        let today = NaiveDate::from_ymd_opt(2024, 9, 17).unwrap();
+        let days = |offsets: &[i64]| offsets.iter().map(|d| today - Duration::days(*d)).collect::<BTreeSet<_>>();
+
+        assert_eq!(compute_streaks(&days(&[0, 1, 2, 5, 6]), today), (3, 3));
+        // 今天还没有完成任务时从昨天开始计算
+        assert_eq!(compute_streaks(&days(&[1, 2, 10, 11, 12, 13]), today), (2, 4));
+        assert_eq!(compute_streaks(&days(&[3]), today), (0, 1));
+        assert_eq!(compute_streaks(&BTreeSet::new(), today), (0, 0));
+    }
+
+    #[test]
+    fn range_limits_created_and_completed_counts() {
+        let now = at(2024, 9, 17, 12);
+        let todos = vec![
+            todo("recent", "default", at(2024, 9, 16, 9), Some(at(2024, 9, 17, 9)), None),
+            todo("old", "default", at(2024, 9, 1, 9), Some(at(2024, 9, 2, 9)), None),
+            todo("pending", "default", at(2024, 9, 15, 9), None, None),
+        ];
+        let groups = vec![group("default", 0, None)];
+
+        let week = compute_statistics(&todos, &groups, "week", now).unwrap();
+        assert_eq!(week.start_date, "2024-09-11");
+        assert_eq!(week.daily.len(), 7);
+        assert_eq!((week.total_created, week.total_completed), (2, 1));
+        assert_eq!(week.completion_rate, 0.5);
+        assert_eq!(week.average_lead_time_secs, Some(24 * 3600));
+
+        let month = compute_statistics(&todos, &groups, "month", now).unwrap();
+        assert_eq!((month.total_created, month.total_completed), (3, 2));
+
+        let all = compute_statistics(&todos, &groups, "all", now).unwrap();
+        assert_eq!(all.start_date, "2024-09-01");
+
+        let today = compute_statistics(&todos, &groups, "today", now).unwrap();
+        assert_eq!((today.total_created, today.total_completed), (0, 1));
+
+        assert!(compute_statistics(&todos, &groups, "decade", now).is_err());
+    }
+
+    #[test]
+    fn deadlines_split_on_time_late_and_overdue() {
+        let now = at(2024, 9, 17, 12);
+        let todos = vec![
+            todo("on-time", "default", at(2024, 9, 15, 9), Some(at(2024, 9, 16, 9)), Some(at(2024, 9, 16, 18))),
+            todo("late", "default", at(2024, 9, 15, 9), Some(at(2024, 9, 17, 9)), Some(at(2024, 9, 16, 18))),
+            todo("overdue", "default", at(2024, 9, 15, 9), None, Some(at(2024, 9, 17, 11))),
+            todo("upcoming", "default", at(2024, 9, 15, 9), None, Some(at(2024, 9, 17, 13))),
+        ];
+        let stats = compute_statistics(&todos, &[group("default", 0, None)], "week", now).unwrap();
+        assert_eq!((stats.on_time, stats.late, stats.overdue_pending), (1, 1, 1));
+    }
+
+    #[test]
+    fn group_filter_keeps_subgroups_and_lists_orphans() {
+        let now = at(2024, 9, 17, 12);
+        let groups = vec![
+            group("work", 1, None),
+            group("project", 2, Some("work")),
+            group("home", 0, None),
+        ];
+        let mut todos = vec![
+            todo("a", "work", at(2024, 9, 16, 9), Some(at(2024, 9, 16, 10)), None),
+            todo("b", "project", at(2024, 9, 16, 9), None, None),
+            todo("c", "home", at(2024, 9, 16, 9), None, None),
+            todo("d", "deleted", at(2024, 9, 16, 9), None, None),
+        ];
+
+        let all = compute_statistics(&todos, &groups, "week", now).unwrap();
+        let ids: Vec<&str> = all.groups.iter().map(|g| g.group_id.as_str()).collect();
+        assert_eq!(ids, ["home", "work", "project", "deleted"]);
+
+        // 与 get_statistics 一样只保留分组及其下级分组
+        let ids = subtree_ids(&groups, "work");
+        todos.retain(|todo| ids.contains(&todo.group_id));
+        let mut groups = groups;
+        groups.retain(|group| ids.contains(&group.id));
+        let work = compute_statistics(&todos, &groups, "week", now).unwrap();
+        assert_eq!(work.total_created, 2);
+        let counts: Vec<(&str, u32, u32)> = work.groups.iter().map(|g| (g.group_id.as_str(), g.completed, g.pending)).collect();
+        assert_eq!(counts, [("work", 1, 0), ("project", 0, 1)]);
+    }
+}
@@ -0,0 +1,1030 @@
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+use serde::{Deserialize, Serialize};
+use serde_json;
+
+use crate::data::archive::archived_todo_ids;
+use crate::data::encryption::encrypt_for;
+use crate::data::storage::read_data_file;
+use crate::data::todo_data::{read_group_store, read_todo_store, update_todo_store};
+use crate::models::{AppSettings, FieldConflict, MergeReport, SyncStatus, Todo};
+use crate::system::credentials::{resolve_password, CALDAV_ACCOUNT};
+use super::ical::{build_vtodo, parse_vtodo, TaskFields, VTodo};
+use super::scheduler::{spawn_sync_loop, SyncRunner};
+use super::webdav::{basic_authorization, check_plain_http_auth, PutOutcome, RemoteFile, WriteCondition};
+
+// 记录同步令牌和已同步任务的状态文件
+const STATE_FILE: &str = "caldav_state.json";
+
+// 分组对应的任务列表名称前缀，避免与用户已有的日历冲突
+const CALENDAR_PREFIX: &str = "deskhive-";
+
+const DAV_NS: &str = "DAV:";
+
+// 任务列表自上次同步以来的变化
+pub struct CollectionChanges {
+    pub changed: Vec<(String, Option<String>)>, // 新增或修改的任务资源地址及其ETag
+    pub deleted: Vec<String>,                   // 被删除的任务资源地址
+    pub sync_token: Option<String>,             // 下次增量同步使用的同步令牌
+}
+
+// CalDAV 传输层抽象，测试时可以替换为进程内的模拟服务器
+// 所有地址都是完整的URL
+pub trait CalDavTransport {
+    // 确保任务列表存在，并设置显示名称
+    fn ensure_calendar(&self, url: &str, display_name: &str) -> Result<(), String>;
+    // 获取同步令牌之后的变化，令牌为 None 时返回全部任务；令牌失效或任务列表不存在时返回 None
+    fn sync_collection(&self, url: &str, sync_token: Option<&str>) -> Result<Option<CollectionChanges>, String>;
+    // 读取任务资源，不存在时返回 None
+    fn get(&self, url: &str) -> Result<Option<RemoteFile>, String>;
+    // 按前提条件写入任务资源
+    fn put(&self, url: &str, ics: &str, condition: WriteCondition) -> Result<PutOutcome, String>;
+    // 按前提条件删除任务资源，资源已不存在时视为成功
+    fn delete(&self, url: &str, condition: WriteCondition) -> Result<PutOutcome, String>;
+}
+
+fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+// 基于 HTTP 的 CalDAV 客户端
+pub struct CalDavClient {
+    home_url: String,
+    authorization: Option<String>,
+}
+
+impl CalDavClient {
+    pub fn new(home_url: &str, username: &str, password: &str) -> Result<Self, String> {
+        let home_url = home_url.trim().trim_end_matches('/').to_string();
+        if !(home_url.starts_with("https://") || home_url.starts_with("http://")) {
+            return Err(format!("无效的CalDAV地址: {}", home_url));
+        }
+        check_plain_http_auth(&home_url, username)?;
+
+        Ok(Self { home_url, authorization: basic_authorization(username, password) })
+    }
+
+    pub fn home_url(&self) -> &str {
+        &self.home_url
+    }
+
+    fn request(&self, method: &str, url: &str) -> ureq::Request {
+        let request = ureq::request(method, url).timeout(Duration::from_secs(30));
+        match &self.authorization {
+            Some(authorization) => request.set("Authorization", authorization),
+            None => request,
+        }
+    }
+
+    fn set_display_name(&self, url: &str, display_name: &str) -> Result<(), String> {
+        let body = format!(
+            r#"<?xml version="1.0" encoding="utf-8"?>
+<d:propertyupdate xmlns:d="DAV:">
+  <d:set><d:prop><d:displayname>{}</d:displayname></d:prop></d:set>
+</d:propertyupdate>"#,
+            xml_escape(display_name)
+        );
+        self.request("PROPPATCH", url)
+            .set("Content-Type", "application/xml; charset=utf-8")
+            .send_string(&body)
+            .map(|_| ())
+            .map_err(|e| format!("更新任务列表名称失败: {}", e))
+    }
+}
+
+// 解析 sync-collection 报告返回的 multistatus
+fn parse_multistatus(collection_url: &str, body: &str) -> Result<CollectionChanges, String> {
+    let doc = roxmltree::Document::parse(body)
+        .map_err(|e| format!("解析CalDAV响应失败: {}", e))?;
+    let base = url::Url::parse(collection_url)
+        .map_err(|e| format!("无效的任务列表地址: {}", e))?;
+    let child_text = |node: roxmltree::Node, name: &str| node.children()
+        .find(|child| child.has_tag_name((DAV_NS, name)))
+        .and_then(|child| child.text())
+        .map(|text| text.trim().to_string());
+
+    let mut changes = CollectionChanges {
+        changed: Vec::new(),
+        deleted: Vec::new(),
+        sync_token: child_text(doc.root_element(), "sync-token"),
+    };
+
+    for response in doc.root_element().children().filter(|n| n.has_tag_name((DAV_NS, "response"))) {
+        let href = match child_text(response, "href") {
+            Some(href) if !href.ends_with('/') => href,
+            _ => continue, // 任务列表本身
+        };
+        let url = base.join(&href)
+            .map_err(|e| format!("无效的任务地址 {}: {}", href, e))?
+            .to_string();
+
+        // 被删除的资源只返回状态
+        if child_text(response, "status").is_some_and(|status| status.contains(" 404")) {
+            changes.deleted.push(url);
+            continue;
+        }
+        let etag = response.descendants()
+            .find(|n| n.has_tag_name((DAV_NS, "getetag")))
+            .and_then(|n| n.text())
+            .map(|etag| etag.trim().to_string());
+        changes.changed.push((url, etag));
+    }
+
+    Ok(changes)
+}
+
+impl CalDavTransport for CalDavClient {
+    fn ensure_calendar(&self, url: &str, display_name: &str) -> Result<(), String> {
+        let body = format!(
+            r#"<?xml version="1.0" encoding="utf-8"?>
+<c:mkcalendar xmlns:d="DAV:" xmlns:c="urn:ietf:params:xml:ns:caldav">
+  <d:set>
+    <d:prop>
+      <d:displayname>{}</d:displayname>
+      <c:supported-calendar-component-set><c:comp name="VTODO"/></c:supported-calendar-component-set>
+    </d:prop>
+  </d:set>
+</c:mkcalendar>"#,
+            xml_escape(display_name)
+        );
+        match self.request("MKCALENDAR", url)
+            .set("Content-Type", "application/xml; charset=utf-8")
+            .send_string(&body)
+        {
+            Ok(_) => Ok(()),
+            // 405：任务列表已存在，只更新名称
+            Err(ureq::Error::Status(405, _)) => self.set_display_name(url, display_name),
+            Err(e) => Err(format!("创建任务列表失败: {}", e)),
+        }
+    }
+
+    fn sync_collection(&self, url: &str, sync_token: Option<&str>) -> Result<Option<CollectionChanges>, String> {
+        let token = match sync_token {
+            Some(token) => format!("<d:sync-token>{}</d:sync-token>", xml_escape(token)),
+            None => "<d:sync-token/>".to_string(),
+        };
+        let body = format!(
+            r#"<?xml version="1.0" encoding="utf-8"?>
+<d:sync-collection xmlns:d="DAV:">
+  {}
+  <d:sync-level>1</d:sync-level>
+  <d:prop><d:getetag/></d:prop>
+</d:sync-collection>"#,
+            token
+        );
+
+        let response = match self.request("REPORT", url)
+            .set("Depth", "0")
+            .set("Content-Type", "application/xml; charset=utf-8")
+            .send_string(&body)
+        {
+            Ok(response) => response,
+            // 403/409：同步令牌失效；404：任务列表已被删除
+            Err(ureq::Error::Status(403 | 404 | 409, _)) => return Ok(None),
+            Err(e) => return Err(format!("获取任务列表变化失败: {}", e)),
+        };
+        let body = response.into_string()
+            .map_err(|e| format!("读取CalDAV响应失败: {}", e))?;
+
+        parse_multistatus(url, &body).map(Some)
+    }
+
+    fn get(&self, url: &str) -> Result<Option<RemoteFile>, String> {
+        match self.request("GET", url).call() {
+            Ok(response) => {
+                let etag = response.header("ETag").map(|etag| etag.to_string());
+                let contents = response.into_string()
+                    .map_err(|e| format!("读取CalDAV响应失败: {}", e))?;
+                Ok(Some(RemoteFile { contents, etag }))
+            }
+            Err(ureq::Error::Status(404, _)) => Ok(None),
+            Err(e) => Err(format!("下载任务失败: {}", e)),
+        }
+    }
+
+    fn put(&self, url: &str, ics: &str, condition: WriteCondition) -> Result<PutOutcome, String> {
+        let request = self.request("PUT", url)
+            .set("Content-Type", "text/calendar; charset=utf-8");
+        let request = match &condition {
+            WriteCondition::Absent => request.set("If-None-Match", "*"),
+            WriteCondition::Matches(etag) => request.set("If-Match", etag),
+            WriteCondition::Any => request,
+        };
+
+        match request.send_string(ics) {
+            Ok(response) => Ok(PutOutcome::Stored(response.header("ETag").map(|etag| etag.to_string()))),
+            Err(ureq::Error::Status(412, _)) => Ok(PutOutcome::PreconditionFailed),
+            Err(e) => Err(format!("上传任务失败: {}", e)),
+        }
+    }
+
+    fn delete(&self, url: &str, condition: WriteCondition) -> Result<PutOutcome, String> {
+        let request = self.request("DELETE", url);
+        let request = match &condition {
+            WriteCondition::Matches(etag) => request.set("If-Match", etag),
+            _ => request,
+        };
+
+        match request.call() {
+            Ok(_) | Err(ureq::Error::Status(404, _)) => Ok(PutOutcome::Stored(None)),
+            Err(ureq::Error::Status(412, _)) => Ok(PutOutcome::PreconditionFailed),
+            Err(e) => Err(format!("删除任务失败: {}", e)),
+        }
+    }
+}
+
+// 已同步的任务列表
+#[derive(Serialize, Deserialize, Default)]
+struct CalendarState {
+    display_name: String,
+    sync_token: Option<String>,
+}
+
+// 已同步的任务：远端地址、ETag，以及上次同步时两端一致的字段，用于判断哪一端做了修改
+#[derive(Serialize, Deserialize, Clone)]
+struct ItemState {
+    group_id: String,
+    url: String,
+    etag: Option<String>,
+    fields: TaskFields,
+}
+
+// 持久化的同步状态
+#[derive(Serialize, Deserialize, Default)]
+struct CalDavState {
+    home_url: String,
+    calendars: BTreeMap<String, CalendarState>, // 分组ID -> 任务列表
+    items: BTreeMap<String, ItemState>,         // 任务ID -> 远端任务
+}
+
+// 同步状态中包含任务内容，启用数据加密时同样加密保存
+fn load_state(data_dir: &Path) -> Result<CalDavState, String> {
+    let path = data_dir.join(STATE_FILE);
+    if !path.exists() {
+        return Ok(CalDavState::default());
+    }
+    Ok(serde_json::from_str(&read_data_file(&path)?).unwrap_or_default())
+}
+
+fn save_state(data_dir: &Path, state: &CalDavState) -> Result<(), String> {
+    let path = data_dir.join(STATE_FILE);
+    let json_data = serde_json::to_string_pretty(state)
+        .map_err(|e| format!("序列化同步状态失败: {}", e))?;
+    // 不经过 write_data_file，同步状态的变化不应再次触发同步
+    fs::write(&path, encrypt_for(&path, &json_data)?)
+        .map_err(|e| format!("写入同步状态失败: {}", e))
+}
+
+// 资源名只保留URL安全的字符
+fn resource_name(id: &str) -> String {
+    id.chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+        .collect()
+}
+
+fn calendar_url(home_url: &str, group_id: &str) -> String {
+    format!("{}/{}{}/", home_url.trim_end_matches('/'), CALENDAR_PREFIX, resource_name(group_id))
+}
+
+fn item_url(home_url: &str, group_id: &str, todo_id: &str) -> String {
+    format!("{}{}.ics", calendar_url(home_url, group_id), resource_name(todo_id))
+}
+
+fn condition_for(etag: &Option<String>) -> WriteCondition {
+    match etag {
+        Some(etag) => WriteCondition::Matches(etag.clone()),
+        None => WriteCondition::Any,
+    }
+}
+
+// 三方合并单个字段：只有一端修改时采用修改后的值，两端都修改时保留较新的一端
+fn merge_value<T: PartialEq + Clone>(
+    item_id: &str,
+    field: &str,
+    base: Option<&T>,
+    local: &T,
+    remote: &T,
+    remote_newer: bool,
+    conflicts: &mut Vec<FieldConflict>,
+) -> T {
+    if local == remote {
+        return local.clone();
+    }
+    match base {
+        Some(base) if base == local => remote.clone(),
+        Some(base) if base == remote => local.clone(),
+        _ => {
+            conflicts.push(FieldConflict {
+                item_id: item_id.to_string(),
+                field: field.to_string(),
+                kept: if remote_newer { "remote" } else { "local" }.to_string(),
+            });
+            if remote_newer { remote.clone() } else { local.clone() }
+        }
+    }
+}
+
+fn merge_fields(
+    item_id: &str,
+    base: Option<&TaskFields>,
+    local: &TaskFields,
+    remote: &TaskFields,
+    remote_newer: bool,
+    conflicts: &mut Vec<FieldConflict>,
+) -> TaskFields {
+    let text = merge_value(item_id, "text", base.map(|b| &b.text), &local.text, &remote.text, remote_newer, conflicts);
+    let deadline = merge_value(item_id, "deadline", base.map(|b| &b.deadline), &local.deadline, &remote.deadline, remote_newer, conflicts);
+    let priority = merge_value(item_id, "priority", base.map(|b| &b.priority), &local.priority, &remote.priority, remote_newer, conflicts);
+    // 完成状态和完成时间作为一个整体合并
+    let (completed, completed_at) = merge_value(
+        item_id,
+        "completed",
+        base.map(|b| (b.completed, b.completed_at)).as_ref(),
+        &(local.completed, local.completed_at),
+        &(remote.completed, remote.completed_at),
+        remote_newer,
+        conflicts,
+    );
+    TaskFields { text, deadline, priority, completed, completed_at }
+}
+
+// 从远端拉取的任务
+struct RemoteChange {
+    group_id: String,
+    url: String,
+    etag: Option<String>,
+    vtodo: VTodo,
+}
+
+// 把远端的变化应用到本地任务，返回本地数据是否有变化
+// local_removed：本地已删除或已归档的任务
+fn apply_remote_changes(
+    todos: &mut Vec<Todo>,
+    local_removed: &HashSet<String>,
+    changes: Vec<RemoteChange>,
+    deleted_urls: &[String],
+    state: &mut CalDavState,
+    report: &mut MergeReport,
+    now: i64,
+) -> bool {
+    let mut changed = false;
+
+    for change in changes {
+        let uid = change.vtodo.uid.clone();
+        let remote_fields = change.vtodo.fields.clone();
+        let previous = state.items.get(&uid).cloned();
+
+        match todos.iter_mut().find(|todo| todo.id == uid) {
+            Some(todo) => {
+                let local_fields = TaskFields::of(todo);
+                let remote_newer = change.vtodo.last_modified.unwrap_or(now) >= todo.updated_at;
+                let merged = merge_fields(
+                    &uid,
+                    previous.as_ref().map(|item| &item.fields),
+                    &local_fields,
+                    &remote_fields,
+                    remote_newer,
+                    &mut report.conflicts,
+                );
+                // 远端把任务移到了其他任务列表，而本地没有移动过
+                let moved = todo.group_id != change.group_id
+                    && previous.as_ref().is_some_and(|item| item.group_id == todo.group_id);
+
+                if merged != local_fields || moved {
+                    merged.apply_to(todo);
+                    if moved {
+                        todo.group_id = change.group_id.clone();
+                    }
+                    report.updated += 1;
+                    changed = true;
+                }
+            }
+            // 本地已删除的任务不再恢复，推送时会删除远端资源；已归档的任务保留在远端
+            None if local_removed.contains(&uid) => {}
+            None => {
+                let order = todos.iter()
+                    .filter(|todo| todo.group_id == change.group_id)
+                    .map(|todo| todo.order + 1)
+                    .max()
+                    .unwrap_or(0);
+                let mut todo = Todo {
+                    id: uid.clone(),
+                    text: String::new(),
+                    completed: false,
+                    created_at: change.vtodo.created.unwrap_or(now),
+                    completed_at: None,
+                    deadline: None,
+                    order,
+                    group_id: change.group_id.clone(),
+                    priority: 0,
+                    tags: Vec::new(),
+                    updated_at: 0,
+                    field_updated_at: BTreeMap::new(),
+                };
+                remote_fields.apply_to(&mut todo);
+                todos.push(todo);
+                report.added += 1;
+                changed = true;
+            }
+        }
+
+        state.items.insert(uid, ItemState {
+            group_id: change.group_id,
+            url: change.url,
+            etag: change.etag,
+            fields: remote_fields,
+        });
+    }
+
+    for url in deleted_urls {
+        let (id, item) = match state.items.iter().find(|(_, item)| &item.url == url) {
+            Some((id, item)) => (id.clone(), item.clone()),
+            None => continue,
+        };
+        state.items.remove(&id);
+
+        // 本地在上次同步后修改过的任务保留，推送时重新上传
+        if let Some(index) = todos.iter().position(|todo| todo.id == id) {
+            if TaskFields::of(&todos[index]) == item.fields && todos[index].group_id == item.group_id {
+                todos.remove(index);
+                report.removed += 1;
+                changed = true;
+            }
+        }
+    }
+
+    changed
+}
+
+// 在分组对应的任务列表中新建任务，远端已存在同名资源时返回 None，留到下次同步时合并
+fn create_remote(
+    transport: &dyn CalDavTransport,
+    home_url: &str,
+    todo: &Todo,
+    existing: Option<&str>,
+    now: i64,
+) -> Result<Option<ItemState>, String> {
+    let url = item_url(home_url, &todo.group_id, &todo.id);
+    match transport.put(&url, &build_vtodo(todo, existing, now), WriteCondition::Absent)? {
+        PutOutcome::Stored(etag) => Ok(Some(ItemState {
+            group_id: todo.group_id.clone(),
+            url,
+            etag,
+            fields: TaskFields::of(todo),
+        })),
+        PutOutcome::PreconditionFailed => Ok(None),
+    }
+}
+
+// 把本地的变化推送到远端
+fn push_local_changes(
+    transport: &dyn CalDavTransport,
+    home_url: &str,
+    todos: &[Todo],
+    archived: &HashSet<String>,
+    synced_groups: &HashSet<String>,
+    state: &mut CalDavState,
+    now: i64,
+) -> Result<(), String> {
+    for todo in todos.iter().filter(|todo| synced_groups.contains(&todo.group_id)) {
+        let item = match state.items.get(&todo.id) {
+            Some(item) => item.clone(),
+            None => {
+                if let Some(item) = create_remote(transport, home_url, todo, None, now)? {
+                    state.items.insert(todo.id.clone(), item);
+                }
+                continue;
+            }
+        };
+
+        if item.group_id == todo.group_id && item.fields == TaskFields::of(todo) {
+            continue;
+        }
+
+        let existing = match transport.get(&item.url)? {
+            Some(existing) => existing,
+            None => {
+                // 远端资源已不存在，重新创建
+                state.items.remove(&todo.id);
+                if let Some(item) = create_remote(transport, home_url, todo, None, now)? {
+                    state.items.insert(todo.id.clone(), item);
+                }
+                continue;
+            }
+        };
+        // 远端在上次同步后又被修改，下次同步时先合并再推送
+        if existing.etag.is_some() && item.etag.is_some() && existing.etag != item.etag {
+            continue;
+        }
+
+        if item.group_id != todo.group_id {
+            // 本地移到了其他分组：在新任务列表中创建，再删除原任务列表中的资源
+            if let Some(created) = create_remote(transport, home_url, todo, Some(&existing.contents), now)? {
+                transport.delete(&item.url, condition_for(&item.etag))?;
+                state.items.insert(todo.id.clone(), created);
+            }
+            continue;
+        }
+
+        let ics = build_vtodo(todo, Some(&existing.contents), now);
+        if let PutOutcome::Stored(etag) = transport.put(&item.url, &ics, condition_for(&item.etag))? {
+            state.items.insert(todo.id.clone(), ItemState {
+                etag,
+                fields: TaskFields::of(todo),
+                ..item
+            });
+        }
+    }
+
+    // 本地已删除的任务，归档的任务只是移出了任务列表，远端保留
+    let live_ids: HashSet<&str> = todos.iter().map(|todo| todo.id.as_str()).collect();
+    let removed: Vec<(String, ItemState)> = state.items.iter()
+        .filter(|(id, _)| !live_ids.contains(id.as_str()) && !archived.contains(id.as_str()))
+        .map(|(id, item)| (id.clone(), item.clone()))
+        .collect();
+    for (id, item) in removed {
+        if let PutOutcome::Stored(_) = transport.delete(&item.url, condition_for(&item.etag))? {
+            state.items.remove(&id);
+        }
+    }
+
+    Ok(())
+}
+
+// 与 CalDAV 服务器双向同步任务：每个分组对应一个任务列表，每个任务对应一个 VTODO
+// 先用同步令牌增量拉取远端变化并合并到本地，再把本地变化推送到远端
+pub fn sync_with_caldav(
+    transport: &dyn CalDavTransport,
+    home_url: &str,
+    data_dir: &Path,
+) -> Result<(MergeReport, Vec<&'static str>), String> {
+    let mut state = load_state(data_dir)?;
+    if state.home_url != home_url {
+        // 更换了服务器地址，重新完整同步
+        state = CalDavState {
+            home_url: home_url.to_string(),
+            ..Default::default()
+        };
+    }
+
+    let groups = read_group_store(data_dir)?.groups;
+    let synced_groups: HashSet<String> = groups.iter().map(|group| group.id.clone()).collect();
+    state.calendars.retain(|group_id, _| synced_groups.contains(group_id));
+
+    // 确保每个分组都有对应的任务列表，分组改名时同步更新名称
+    for group in &groups {
+        let calendar = state.calendars.entry(group.id.clone()).or_default();
+        if calendar.sync_token.is_none() || calendar.display_name != group.name {
+            transport.ensure_calendar(&calendar_url(home_url, &group.id), &group.name)?;
+            calendar.display_name = group.name.clone();
+        }
+    }
+
+    // 拉取远端变化
+    let mut remote_changes = Vec::new();
+    let mut deleted_urls = Vec::new();
+    let mut sync_tokens = Vec::new();
+    for group in &groups {
+        let url = calendar_url(home_url, &group.id);
+        let token = state.calendars.get(&group.id).and_then(|calendar| calendar.sync_token.clone());
+
+        let (changes, full) = match transport.sync_collection(&url, token.as_deref())? {
+            Some(changes) => (changes, token.is_none()),
+            None => {
+                // 同步令牌失效或任务列表被删除，重新创建并完整同步
+                transport.ensure_calendar(&url, &group.name)?;
+                let changes = transport.sync_collection(&url, None)?
+                    .ok_or_else(|| format!("无法获取任务列表: {}", url))?;
+                (changes, true)
+            }
+        };
+
+        // 完整同步时，远端不再存在的任务视为已删除
+        if full {
+            let present: HashSet<&str> = changes.changed.iter().map(|(url, _)| url.as_str()).collect();
+            deleted_urls.extend(state.items.values()
+                .filter(|item| item.group_id == group.id && !present.contains(item.url.as_str()))
+                .map(|item| item.url.clone()));
+        }
+        deleted_urls.extend(changes.deleted);
+
+        for (item_url, etag) in changes.changed {
+            // 自己上传的版本
+            if etag.is_some() && state.items.values().any(|item| item.url == item_url && item.etag == etag) {
+                continue;
+            }
+            let file = match transport.get(&item_url)? {
+                Some(file) => file,
+                None => continue,
+            };
+            match parse_vtodo(&file.contents) {
+                Ok(vtodo) => remote_changes.push(RemoteChange {
+                    group_id: group.id.clone(),
+                    url: item_url,
+                    etag: file.etag.or(etag),
+                    vtodo,
+                }),
+                Err(e) => log::warn!("跳过无法解析的任务 {}: {}", item_url, e),
+            }
+        }
+        sync_tokens.push((group.id.clone(), changes.sync_token));
+    }
+
+    let now = chrono::Utc::now().timestamp();
+    let archived = archived_todo_ids(data_dir)?;
+    let mut report = MergeReport::default();
+    let mut changed_events = Vec::new();
+    if !remote_changes.is_empty() || !deleted_urls.is_empty() {
+        let local_changed = update_todo_store(data_dir, |todos, tombstones| {
+            let local_removed: HashSet<String> = tombstones.iter()
+                .map(|t| t.id.clone())
+                .chain(archived.iter().cloned())
+                .collect();
+            Ok(apply_remote_changes(todos, &local_removed, remote_changes, &deleted_urls, &mut state, &mut report, now))
+        })?;
+        if local_changed {
+            changed_events.push("todo-data-changed");
+        }
+    }
+    for (group_id, sync_token) in sync_tokens {
+        if let Some(calendar) = state.calendars.get_mut(&group_id) {
+            calendar.sync_token = sync_token;
+        }
+    }
+    save_state(data_dir, &state)?;
+
+    // 推送本地变化
+    let todos = read_todo_store(data_dir)?.todos;
+    let result = push_local_changes(transport, home_url, &todos, &archived, &synced_groups, &mut state, now);
+    save_state(data_dir, &state)?;
+    result?;
+
+    Ok((report, changed_events))
+}
+
+// CalDAV同步的运行状态
+static CALDAV_SYNC: SyncRunner = SyncRunner::new("caldav");
+
+// 执行一次CalDAV同步，并通知前端同步结果
+fn run_sync(app: &tauri::AppHandle, settings: &AppSettings) -> SyncStatus {
+    CALDAV_SYNC.run(app, || {
+        let data_dir = crate::data::todo_data::get_data_dir(app)?;
+        let password = resolve_password(CALDAV_ACCOUNT, &settings.caldav_password)?;
+        let client = CalDavClient::new(&settings.caldav_url, &settings.caldav_username, &password)?;
+        sync_with_caldav(&client, client.home_url(), &data_dir)
+    })
+}
+
+// 启动后台同步：按设定间隔同步，本地数据变化后也会触发同步
+pub fn start_caldav_sync(app: tauri::AppHandle) {
+    spawn_sync_loop(
+        app,
+        |settings| (settings.caldav_enabled && !settings.caldav_url.is_empty())
+            .then_some(settings.caldav_sync_interval_minutes),
+        run_sync,
+    );
+}
+
+// Tauri 命令：立即执行CalDAV同步
+#[tauri::command]
+pub async fn sync_caldav_now(app: tauri::AppHandle) -> Result<SyncStatus, String> {
+    let settings = crate::data::load_app_settings(app.clone()).await?;
+    if settings.caldav_url.is_empty() {
+        return Err("尚未配置CalDAV地址".to_string());
+    }
+
+    tauri::async_runtime::spawn_blocking(move || run_sync(&app, &settings))
+        .await
+        .map_err(|e| format!("CalDAV同步任务失败: {}", e))
+}
+
+// Tauri 命令：获取CalDAV同步状态
+#[tauri::command]
+pub async fn get_caldav_sync_status() -> Result<SyncStatus, String> {
+    Ok(CALDAV_SYNC.current_status())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::{Cell, RefCell};
+    use std::collections::BTreeSet;
+    use std::path::PathBuf;
+    use serde_json::json;
+    use crate::models::{test_todo, TodoDataWithGroups};
+
+    const HOME: &str = "https://dav.example.com/calendars/user";
+
+    // 任务列表中的资源：地址 -> (内容, 版本号)
+    type Calendar = BTreeMap<String, (String, u32)>;
+
+    // 进程内的 CalDAV 服务器：每次修改递增版本号，版本号同时用作ETag和同步令牌
+    #[derive(Default)]
+    struct MemoryCalDav {
+        calendars: RefCell<BTreeMap<String, Calendar>>,
+        changes: RefCell<Vec<(u32, String)>>,
+        revision: Cell<u32>,
+        oldest_token: Cell<u32>,
+        gets: Cell<usize>,
+        puts: Cell<usize>,
+    }
+
+    fn etag(revision: u32) -> String {
+        format!("\"{}\"", revision)
+    }
+
+    fn calendar_of(url: &str) -> String {
+        url[..=url.rfind('/').unwrap()].to_string()
+    }
+
+    impl MemoryCalDav {
+        fn record(&self, url: &str) -> u32 {
+            let revision = self.revision.get() + 1;
+            self.revision.set(revision);
+            self.changes.borrow_mut().push((revision, url.to_string()));
+            revision
+        }
+
+        fn current(&self, url: &str) -> Option<(String, u32)> {
+            self.calendars.borrow().get(&calendar_of(url))?.get(url).cloned()
+        }
+
+        // 其他客户端修改任务
+        fn edit(&self, url: &str, edit: impl FnOnce(&mut Todo)) {
+            let (ics, _) = self.current(url).unwrap();
+            let vtodo = parse_vtodo(&ics).unwrap();
+            let mut todo = todo(&vtodo.uid, "");
+            vtodo.fields.apply_to(&mut todo);
+            edit(&mut todo);
+            let ics = build_vtodo(&todo, Some(&ics), todo.updated_at);
+            let revision = self.record(url);
+            self.calendars.borrow_mut().get_mut(&calendar_of(url)).unwrap().insert(url.to_string(), (ics, revision));
+        }
+
+        fn resource_count(&self) -> usize {
+            self.calendars.borrow().values().map(|items| items.len()).sum()
+        }
+    }
+
+    impl CalDavTransport for MemoryCalDav {
+        fn ensure_calendar(&self, url: &str, _display_name: &str) -> Result<(), String> {
+            self.calendars.borrow_mut().entry(url.to_string()).or_default();
+            Ok(())
+        }
+
+        fn sync_collection(&self, url: &str, sync_token: Option<&str>) -> Result<Option<CollectionChanges>, String> {
+            let calendars = self.calendars.borrow();
+            let items = match calendars.get(url) {
+                Some(items) => items,
+                None => return Ok(None),
+            };
+            let mut changes = CollectionChanges {
+                changed: Vec::new(),
+                deleted: Vec::new(),
+                sync_token: Some(self.revision.get().to_string()),
+            };
+
+            let since = match sync_token.map(|token| token.parse::<u32>()) {
+                None => {
+                    changes.changed = items.iter().map(|(url, (_, revision))| (url.clone(), Some(etag(*revision)))).collect();
+                    return Ok(Some(changes));
+                }
+                Some(Ok(since)) if since >= self.oldest_token.get() => since,
+                Some(_) => return Ok(None),
+            };
+            let touched: BTreeSet<String> = self.changes.borrow().iter()
+                .filter(|(revision, item_url)| *revision > since && calendar_of(item_url) == url)
+                .map(|(_, item_url)| item_url.clone())
+                .collect();
+            for item_url in touched {
+                match items.get(&item_url) {
+                    Some((_, revision)) => changes.changed.push((item_url, Some(etag(*revision)))),
+                    None => changes.deleted.push(item_url),
+                }
+            }
+            Ok(Some(changes))
+        }
+
+        fn get(&self, url: &str) -> Result<Option<RemoteFile>, String> {
+            self.gets.set(self.gets.get() + 1);
+            Ok(self.current(url).map(|(contents, revision)| RemoteFile { contents, etag: Some(etag(revision)) }))
+        }
+
+        fn put(&self, url: &str, ics: &str, condition: WriteCondition) -> Result<PutOutcome, String> {
+            let current = self.current(url).map(|(_, revision)| etag(revision));
+            let allowed = match (&condition, &current) {
+                (WriteCondition::Absent, current) => current.is_none(),
+                (WriteCondition::Matches(expected), Some(current)) => expected == current,
+                (WriteCondition::Matches(_), None) => false,
+                (WriteCondition::Any, _) => true,
+            };
+            if !allowed {
+                return Ok(PutOutcome::PreconditionFailed);
+            }
+            self.puts.set(self.puts.get() + 1);
+            let revision = self.record(url);
+            self.calendars.borrow_mut().entry(calendar_of(url)).or_default().insert(url.to_string(), (ics.to_string(), revision));
+            Ok(PutOutcome::Stored(Some(etag(revision))))
+        }
+
+        fn delete(&self, url: &str, condition: WriteCondition) -> Result<PutOutcome, String> {
+            let current = self.current(url).map(|(_, revision)| etag(revision));
+            if let (WriteCondition::Matches(expected), Some(current)) = (&condition, &current) {
+                if expected != current {
+                    return Ok(PutOutcome::PreconditionFailed);
+                }
+            }
+            if current.is_some() {
+                self.record(url);
+                self.calendars.borrow_mut().get_mut(&calendar_of(url)).unwrap().remove(url);
+            }
+            Ok(PutOutcome::Stored(None))
+        }
+    }
+
+    fn todo(id: &str, text: &str) -> Todo {
+        Todo { text: text.to_string(), updated_at: 1_700_000_000, ..test_todo(id, 1_700_000_000, None, None) }
+    }
+
+    fn data_dir(test: &str, todos: Vec<Todo>) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("deskhive-caldav-{}-{}", test, uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("groups.json"), json!({
+            "groups": [{"id": "default", "name": "默认", "order": 0, "collapsed": false}],
+        }).to_string()).unwrap();
+        write_todos(&dir, todos);
+        dir
+    }
+
+    fn write_todos(dir: &Path, todos: Vec<Todo>) {
+        let data = TodoDataWithGroups { todos, ..Default::default() };
+        fs::write(dir.join("todos_with_groups.json"), serde_json::to_string(&data).unwrap()).unwrap();
+    }
+
+    fn local_todos(dir: &Path) -> Vec<Todo> {
+        read_todo_store(dir).unwrap().todos
+    }
+
+    #[test]
+    fn vtodo_round_trip_keeps_fields_and_foreign_properties() {
+        let mut original = todo("task-1", "买牛奶, 面包; 备注\n第二行");
+        original.deadline = Some(1_700_086_400);
+        original.priority = 1;
+        original.completed = true;
+        original.completed_at = Some(1_700_050_000);
+
+        let ics = build_vtodo(&original, None, 1_700_060_000);
+        let vtodo = parse_vtodo(&ics).unwrap();
+        assert_eq!(vtodo.uid, "task-1");
+        assert_eq!(vtodo.fields, TaskFields::of(&original));
+        assert_eq!(vtodo.created, Some(original.created_at));
+        assert_eq!(vtodo.last_modified, Some(1_700_060_000));
+
+        // 其他客户端添加的描述和提醒在更新时保留
+        let existing = ics.replace("END:VTODO", "DESCRIPTION:详细说明\r\nBEGIN:VALARM\r\nACTION:DISPLAY\r\nSUMMARY:提醒\r\nEND:VALARM\r\nEND:VTODO");
+        let mut updated = original.clone();
+        updated.text = "买牛奶".to_string();
+        updated.completed = false;
+        updated.completed_at = None;
+        let ics = build_vtodo(&updated, Some(&existing), 1_700_070_000);
+        assert!(ics.contains("DESCRIPTION:详细说明"));
+        assert!(ics.contains("SUMMARY:提醒"));
+        assert_eq!(parse_vtodo(&ics).unwrap().fields, TaskFields::of(&updated));
+    }
+
+    #[test]
+    fn pushes_local_todos_and_pulls_them_on_another_device() {
+        let server = MemoryCalDav::default();
+        let mut original = todo("task-1", "写周报");
+        original.deadline = Some(1_700_086_400);
+        let first = data_dir("first", vec![original.clone()]);
+
+        sync_with_caldav(&server, HOME, &first).unwrap();
+        assert!(server.current(&item_url(HOME, "default", "task-1")).is_some());
+
+        let second = data_dir("second", Vec::new());
+        let (report, events) = sync_with_caldav(&server, HOME, &second).unwrap();
+        assert_eq!(report.added, 1);
+        assert_eq!(events, ["todo-data-changed"]);
+        let pulled = local_todos(&second);
+        assert_eq!(pulled.len(), 1);
+        assert_eq!(pulled[0].id, "task-1");
+        assert_eq!(TaskFields::of(&pulled[0]), TaskFields::of(&original));
+    }
+
+    #[test]
+    fn uses_sync_token_for_incremental_changes() {
+        let server = MemoryCalDav::default();
+        let dir = data_dir("token", vec![todo("task-1", "一"), todo("task-2", "二")]);
+        sync_with_caldav(&server, HOME, &dir).unwrap();
+
+        // 没有变化时不下载任何任务
+        server.gets.set(0);
+        sync_with_caldav(&server, HOME, &dir).unwrap();
+        assert_eq!(server.gets.get(), 0);
+
+        // 只下载其他客户端修改过的任务
+        server.edit(&item_url(HOME, "default", "task-2"), |todo| {
+            todo.text = "二（已修改）".to_string();
+            todo.updated_at = 1_800_000_000;
+        });
+        let (report, _) = sync_with_caldav(&server, HOME, &dir).unwrap();
+        assert_eq!(server.gets.get(), 1);
+        assert_eq!(report.updated, 1);
+        let texts: Vec<String> = local_todos(&dir).into_iter().map(|todo| todo.text).collect();
+        assert_eq!(texts, ["一", "二（已修改）"]);
+
+        // 同步令牌失效时完整同步，自己上传的版本不再下载
+        server.oldest_token.set(server.revision.get() + 1);
+        server.gets.set(0);
+        let (report, events) = sync_with_caldav(&server, HOME, &dir).unwrap();
+        assert!(report.is_empty());
+        assert!(events.is_empty());
+        assert_eq!(server.gets.get(), 0);
+    }
+
+    #[test]
+    fn merges_concurrent_edits_and_reports_conflicts() {
+        let server = MemoryCalDav::default();
+        let dir = data_dir("conflict", vec![todo("task-1", "原始")]);
+        sync_with_caldav(&server, HOME, &dir).unwrap();
+
+        update_todo_store(&dir, |todos, _| {
+            todos[0].text = "本地修改".to_string();
+            todos[0].priority = 1;
+            Ok(())
+        }).unwrap();
+        let remote_time = chrono::Utc::now().timestamp() + 3600;
+        server.edit(&item_url(HOME, "default", "task-1"), |todo| {
+            todo.text = "远端修改".to_string();
+            todo.updated_at = remote_time;
+        });
+
+        let (report, _) = sync_with_caldav(&server, HOME, &dir).unwrap();
+        assert_eq!(report.conflicts.len(), 1);
+        assert_eq!(report.conflicts[0].field, "text");
+        assert_eq!(report.conflicts[0].kept, "remote");
+
+        // 只有本地修改的优先级推送到远端
+        let local = local_todos(&dir);
+        assert_eq!(local[0].text, "远端修改");
+        assert_eq!(local[0].priority, 1);
+        let (ics, _) = server.current(&item_url(HOME, "default", "task-1")).unwrap();
+        assert_eq!(parse_vtodo(&ics).unwrap().fields, TaskFields::of(&local[0]));
+    }
+
+    #[test]
+    fn does_not_overwrite_remote_changed_after_pull() {
+        let server = MemoryCalDav::default();
+        let dir = data_dir("etag", vec![todo("task-1", "原始")]);
+        sync_with_caldav(&server, HOME, &dir).unwrap();
+        let url = item_url(HOME, "default", "task-1");
+
+        // 拉取之后、推送之前远端又被修改：ETag 与记录的不一致，本次不推送
+        let mut state = load_state(&dir).unwrap();
+        server.edit(&url, |todo| todo.text = "远端修改".to_string());
+        let puts = server.puts.get();
+        let mut local = todo("task-1", "本地修改");
+        local.updated_at = 1_800_000_000;
+        let groups = HashSet::from(["default".to_string()]);
+        push_local_changes(&server, HOME, &[local], &HashSet::new(), &groups, &mut state, 1_800_000_000).unwrap();
+
+        assert_eq!(server.puts.get(), puts);
+        let (ics, _) = server.current(&url).unwrap();
+        assert_eq!(parse_vtodo(&ics).unwrap().fields.text, "远端修改");
+        assert_eq!(state.items["task-1"].fields.text, "原始");
+    }
+
+    #[test]
+    fn keeps_archived_todos_on_the_server() {
+        let server = MemoryCalDav::default();
+        let mut done = todo("archived", "已归档");
+        done.completed = true;
+        done.completed_at = Some(1_700_050_000);
+        let dir = data_dir("archive", vec![done.clone(), todo("deleted", "已删除")]);
+        sync_with_caldav(&server, HOME, &dir).unwrap();
+        assert_eq!(server.resource_count(), 2);
+
+        fs::create_dir_all(dir.join("archive")).unwrap();
+        let archive = TodoDataWithGroups { todos: vec![done], ..Default::default() };
+        fs::write(dir.join("archive").join("2023-11.json"), serde_json::to_string(&archive).unwrap()).unwrap();
+        write_todos(&dir, Vec::new());
+
+        sync_with_caldav(&server, HOME, &dir).unwrap();
+        assert!(server.current(&item_url(HOME, "default", "archived")).is_some());
+        assert!(server.current(&item_url(HOME, "default", "deleted")).is_none());
+
+        // 完整同步时也不会把归档的任务拉回本地
+        server.oldest_token.set(server.revision.get() + 1);
+        let mut state = load_state(&dir).unwrap();
+        state.items.clear();
+        save_state(&dir, &state).unwrap();
+        sync_with_caldav(&server, HOME, &dir).unwrap();
+        assert!(local_todos(&dir).is_empty());
+    }
+}
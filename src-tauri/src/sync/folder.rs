@@ -0,0 +1,177 @@
+use std::fs;
+use std::path::Path;
+use serde_json;
+use tauri::Emitter;
+
+use crate::data::encryption::decrypt_contents;
+use crate::data::storage::{last_known_contents, lock_store, mark_synced, record_contents, sync_base, write_json_file};
+use crate::models::{GroupData, MergeReport, TodoDataWithGroups};
+use super::merge::SyncStore;
+
+// 合并后的冲突副本移动到该目录，便于需要时手动查看
+const MERGED_CONFLICTS_DIR: &str = "sync_conflicts";
+
+// 参与逐项合并的数据文件及其对应的重新加载事件
+const TODO_FILE: &str = "todos_with_groups.json";
+const GROUP_FILE: &str = "groups.json";
+
+fn reload_event(file_name: &str) -> &'static str {
+    if file_name == TODO_FILE {
+        "todo-data-changed"
+    } else {
+        "group-data-changed"
+    }
+}
+
+// 判断文件是否为同步工具生成的冲突副本，返回对应的数据文件名
+// 例如：todos_with_groups (conflicted copy 2024-08-28).json、todos_with_groups.sync-conflict-20240828-120000-ABCDEFG.json
+pub fn conflicted_copy_target(file_name: &str) -> Option<&'static str> {
+    let lower = file_name.to_lowercase();
+    if !lower.ends_with(".json") || !(lower.contains("conflict") || lower.contains("冲突")) {
+        return None;
+    }
+
+    [TODO_FILE, GROUP_FILE].into_iter()
+        .find(|target| lower.starts_with(target.trim_end_matches(".json")))
+}
+
+// 解析数据文件内容，加密的内容先解密
+fn parse_store<S: SyncStore>(contents: &str) -> Result<S, String> {
+    let contents = decrypt_contents(contents)?;
+    serde_json::from_str(&contents).map_err(|e| format!("解析JSON失败: {}", e))
+}
+
+// 将外部修改后的数据文件与应用最近一次写入的版本合并
+fn reconcile_store<S: SyncStore>(path: &Path, contents: &str) -> Result<MergeReport, String> {
+    let remote: S = parse_store(contents)?;
+    let _guard = lock_store();
+
+    let local = last_known_contents(path).and_then(|known| parse_store::<S>(&known).ok());
+    let local = match local {
+        Some(local) => local,
+        None => {
+            // 应用还没有写入过该文件，直接采用磁盘上的内容
+            record_contents(path, contents);
+            mark_synced(path, chrono::Utc::now().timestamp());
+            return Ok(MergeReport::default());
+        }
+    };
+
+    // 两端都在上次一致之后修改的字段才算冲突，没有记录时把所有修改都视为并发修改
+    let now = chrono::Utc::now().timestamp();
+    let (merged, report) = S::merge(&local, &remote, sync_base(path).unwrap_or(0), now)?;
+    mark_synced(path, now);
+
+    // 外部版本缺少本地修改时需要把合并结果写回文件，否则只记录已处理的内容
+    let as_value = |store: &S| serde_json::to_value(store).map_err(|e| format!("序列化数据失败: {}", e));
+    if as_value(&merged)? == as_value(&remote)? {
+        record_contents(path, contents);
+    } else {
+        write_json_file(path, &merged)?;
+    }
+
+    Ok(report)
+}
+
+// 开始监听数据目录时，磁盘上的数据即为与外部一致的版本
+pub fn mark_data_files_synced(data_dir: &Path) {
+    let now = chrono::Utc::now().timestamp();
+    for file_name in [TODO_FILE, GROUP_FILE] {
+        mark_synced(&data_dir.join(file_name), now);
+    }
+}
+
+// 处理被外部修改的 todos_with_groups.json 或 groups.json，返回需要发送的重新加载事件
+// 文件由同步工具整体替换时，本地尚未同步出去的修改会被合并回来而不是丢失
+pub fn reconcile_data_file(path: &Path, contents: &str) -> Option<Result<&'static str, String>> {
+    let file_name = path.file_name()?.to_string_lossy().to_string();
+
+    let result = match file_name.as_str() {
+        TODO_FILE => reconcile_store::<TodoDataWithGroups>(path, contents),
+        GROUP_FILE => reconcile_store::<GroupData>(path, contents),
+        _ => return None,
+    };
+
+    Some(result.map(|report| {
+        if !report.conflicts.is_empty() {
+            log::warn!("合并 {} 时发现 {} 个冲突字段，已按最后修改时间处理", file_name, report.conflicts.len());
+        }
+        reload_event(&file_name)
+    }))
+}
+
+fn merge_copy_into<S: SyncStore>(target_path: &Path, copy_path: &Path) -> Result<MergeReport, String> {
+    let _guard = lock_store();
+    let local: S = match fs::read_to_string(target_path) {
+        Ok(contents) => parse_store(&contents)?,
+        Err(_) => S::default(),
+    };
+    let copy_contents = fs::read_to_string(copy_path)
+        .map_err(|e| format!("读取冲突副本失败: {}", e))?;
+    let remote: S = parse_store(&copy_contents)?;
+
+    // 冲突副本与数据文件在上次同步之后分叉，以上次与外部版本一致的时间作为基准
+    let now = chrono::Utc::now().timestamp();
+    let (merged, report) = S::merge(&local, &remote, sync_base(target_path).unwrap_or(0), now)?;
+    write_json_file(target_path, &merged)?;
+    mark_synced(target_path, now);
+
+    Ok(report)
+}
+
+// 将冲突副本合并到对应的数据文件，并把冲突副本移到 sync_conflicts 目录
+// 返回需要发送的重新加载事件和合并结果
+pub fn merge_conflicted_copy(copy_path: &Path) -> Result<(&'static str, MergeReport), String> {
+    let file_name = copy_path.file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .ok_or_else(|| "无效的冲突副本路径".to_string())?;
+    let target = conflicted_copy_target(&file_name)
+        .ok_or_else(|| format!("不是冲突副本: {}", file_name))?;
+    let data_dir = copy_path.parent()
+        .ok_or_else(|| "无效的冲突副本路径".to_string())?;
+    let target_path = data_dir.join(target);
+
+    let report = if target == TODO_FILE {
+        merge_copy_into::<TodoDataWithGroups>(&target_path, copy_path)?
+    } else {
+        merge_copy_into::<GroupData>(&target_path, copy_path)?
+    };
+
+    let merged_dir = data_dir.join(MERGED_CONFLICTS_DIR);
+    fs::create_dir_all(&merged_dir)
+        .map_err(|e| format!("创建{}目录失败: {}", MERGED_CONFLICTS_DIR, e))?;
+    fs::rename(copy_path, merged_dir.join(&file_name))
+        .map_err(|e| format!("移动冲突副本失败: {}", e))?;
+
+    log::info!("已合并冲突副本 {}: 新增 {}，修改 {}，删除 {}，冲突字段 {}",
+        file_name, report.added, report.updated, report.removed, report.conflicts.len());
+
+    Ok((reload_event(target), report))
+}
+
+// 合并数据目录中所有的冲突副本
+pub fn merge_all_conflicted_copies(app: &tauri::AppHandle) -> Result<MergeReport, String> {
+    let data_dir = crate::data::todo_data::get_data_dir(app)?;
+    let entries = fs::read_dir(&data_dir)
+        .map_err(|e| format!("读取data目录失败: {}", e))?;
+
+    let mut report = MergeReport::default();
+    for entry in entries.filter_map(|entry| entry.ok()) {
+        let file_name = entry.file_name().to_string_lossy().to_string();
+        if conflicted_copy_target(&file_name).is_none() {
+            continue;
+        }
+
+        let (event, merged) = merge_conflicted_copy(&entry.path())?;
+        report.absorb(merged);
+        let _ = app.emit(event, ());
+    }
+
+    Ok(report)
+}
+
+// Tauri 命令：合并同步工具生成的冲突副本
+#[tauri::command]
+pub async fn merge_sync_conflicts(app: tauri::AppHandle) -> Result<MergeReport, String> {
+    merge_all_conflicted_copies(&app)
+}
@@ -0,0 +1,307 @@
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::models::Todo;
+
+// iCalendar 行的最大长度（字节），超出时需要折行
+const MAX_LINE_OCTETS: usize = 75;
+
+// 与 CalDAV 同步的任务字段
+#[derive(Serialize, Deserialize, Clone, PartialEq, Default, Debug)]
+pub struct TaskFields {
+    pub text: String,
+    pub deadline: Option<i64>,
+    pub priority: i32,
+    pub completed: bool,
+    pub completed_at: Option<i64>,
+}
+
+impl TaskFields {
+    pub fn of(todo: &Todo) -> Self {
+        Self {
+            text: todo.text.clone(),
+            deadline: todo.deadline,
+            priority: todo.priority,
+            completed: todo.completed,
+            completed_at: todo.completed_at,
+        }
+    }
+
+    pub fn apply_to(&self, todo: &mut Todo) {
+        todo.text = self.text.clone();
+        todo.deadline = self.deadline;
+        todo.priority = self.priority;
+        todo.completed = self.completed;
+        todo.completed_at = self.completed_at;
+    }
+}
+
+// 从 VTODO 解析出的任务
+pub struct VTodo {
+    pub uid: String,
+    pub fields: TaskFields,
+    pub created: Option<i64>,
+    pub last_modified: Option<i64>,
+}
+
+// 一行属性：名称和值（参数不影响 DeskHive 使用的字段，解析时忽略）
+struct Property {
+    name: String,
+    value: String,
+}
+
+// 展开折行（以空格或制表符开头的行属于上一行）
+fn unfold(ics: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for line in ics.split('\n') {
+        let line = line.trim_end_matches('\r');
+        match (line.strip_prefix([' ', '\t']), lines.last_mut()) {
+            (Some(rest), Some(last)) => last.push_str(rest),
+            _ => lines.push(line.to_string()),
+        }
+    }
+    lines.retain(|line| !line.is_empty());
+    lines
+}
+
+// 按75字节折行，不拆分多字节字符
+fn fold(line: &str) -> String {
+    let mut folded = String::new();
+    let mut width = 0;
+    for c in line.chars() {
+        let len = c.len_utf8();
+        if width + len > MAX_LINE_OCTETS {
+            folded.push_str("\r\n ");
+            width = 1;
+        }
+        folded.push(c);
+        width += len;
+    }
+    folded
+}
+
+fn parse_property(line: &str) -> Option<Property> {
+    // 参数值可能带引号并包含冒号，值从第一个不在引号内的冒号之后开始
+    let mut in_quotes = false;
+    let colon = line.char_indices().find(|(_, c)| {
+        if *c == '"' {
+            in_quotes = !in_quotes;
+        }
+        *c == ':' && !in_quotes
+    })?.0;
+
+    let (head, value) = (&line[..colon], &line[colon + 1..]);
+    let name = head.split(';').next().unwrap_or(head);
+    Some(Property {
+        name: name.to_ascii_uppercase(),
+        value: value.to_string(),
+    })
+}
+
+fn escape_text(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace('\n', "\\n")
+}
+
+fn unescape_text(text: &str) -> String {
+    let mut result = String::new();
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            result.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') | Some('N') => result.push('\n'),
+            Some(other) => result.push(other),
+            None => {}
+        }
+    }
+    result
+}
+
+fn format_utc(timestamp: i64) -> String {
+    DateTime::<Utc>::from_timestamp(timestamp, 0)
+        .unwrap_or_default()
+        .format("%Y%m%dT%H%M%SZ")
+        .to_string()
+}
+
+// 解析日期时间属性：UTC时间、浮动时间、带TZID的时间（按本地时间处理）和全天日期
+// 全天日期的截止时间取当天23:59
+fn parse_datetime(property: &Property) -> Option<i64> {
+    let value = property.value.trim();
+    if let Some(utc) = value.strip_suffix('Z') {
+        return NaiveDateTime::parse_from_str(utc, "%Y%m%dT%H%M%S")
+            .ok()
+            .map(|dt| dt.and_utc().timestamp());
+    }
+
+    let naive = if value.len() == 8 {
+        NaiveDate::parse_from_str(value, "%Y%m%d").ok()?.and_hms_opt(23, 59, 0)?
+    } else {
+        NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S").ok()?
+    };
+    Local.from_local_datetime(&naive).earliest().map(|dt| dt.timestamp())
+}
+
+// DeskHive 只有普通和重要两级：iCalendar 优先级 1-4 为高优先级
+fn priority_from_ical(value: &str) -> i32 {
+    match value.trim().parse::<i32>() {
+        Ok(p) if (1..=4).contains(&p) => 1,
+        _ => 0,
+    }
+}
+
+// 解析 iCalendar 文本中的第一个 VTODO
+pub fn parse_vtodo(ics: &str) -> Result<VTodo, String> {
+    let mut in_vtodo = false;
+    let mut depth = 0;
+    let mut uid = None;
+    let mut fields = TaskFields::default();
+    let mut status_completed = false;
+    let mut created = None;
+    let mut last_modified = None;
+
+    for line in unfold(ics) {
+        let property = match parse_property(&line) {
+            Some(property) => property,
+            None => continue,
+        };
+
+        match (property.name.as_str(), property.value.to_ascii_uppercase().as_str()) {
+            ("BEGIN", "VTODO") if !in_vtodo => {
+                in_vtodo = true;
+                continue;
+            }
+            ("END", "VTODO") if in_vtodo && depth == 0 => break,
+            // 跳过 VALARM 等子组件中的属性
+            ("BEGIN", _) if in_vtodo => depth += 1,
+            ("END", _) if in_vtodo => depth -= 1,
+            _ => {}
+        }
+        if !in_vtodo || depth > 0 {
+            continue;
+        }
+
+        match property.name.as_str() {
+            "UID" => uid = Some(property.value.trim().to_string()),
+            "SUMMARY" => fields.text = unescape_text(&property.value),
+            "DUE" => fields.deadline = parse_datetime(&property),
+            "PRIORITY" => fields.priority = priority_from_ical(&property.value),
+            "STATUS" => status_completed = property.value.trim().eq_ignore_ascii_case("COMPLETED"),
+            "COMPLETED" => fields.completed_at = parse_datetime(&property),
+            "CREATED" => created = parse_datetime(&property),
+            "LAST-MODIFIED" => last_modified = parse_datetime(&property),
+            _ => {}
+        }
+    }
+
+    let uid = uid.ok_or_else(|| "VTODO缺少UID".to_string())?;
+    fields.completed = status_completed || fields.completed_at.is_some();
+    if !fields.completed {
+        fields.completed_at = None;
+    }
+
+    Ok(VTodo { uid, fields, created, last_modified })
+}
+
+// DeskHive 管理的 VTODO 属性，写入时会被替换
+const MANAGED_PROPERTIES: &[&str] = &[
+    "SUMMARY", "DUE", "PRIORITY", "STATUS", "COMPLETED", "PERCENT-COMPLETE", "LAST-MODIFIED", "DTSTAMP",
+];
+
+fn managed_lines(todo: &Todo, now: i64) -> Vec<String> {
+    let mut lines = vec![
+        format!("DTSTAMP:{}", format_utc(now)),
+        format!("LAST-MODIFIED:{}", format_utc(now)),
+        format!("SUMMARY:{}", escape_text(&todo.text)),
+    ];
+    if let Some(deadline) = todo.deadline {
+        lines.push(format!("DUE:{}", format_utc(deadline)));
+    }
+    if todo.priority >= 1 {
+        lines.push("PRIORITY:1".to_string());
+    }
+    if todo.completed {
+        lines.push("STATUS:COMPLETED".to_string());
+        lines.push("PERCENT-COMPLETE:100".to_string());
+        if let Some(completed_at) = todo.completed_at {
+            lines.push(format!("COMPLETED:{}", format_utc(completed_at)));
+        }
+    } else {
+        lines.push("STATUS:NEEDS-ACTION".to_string());
+    }
+    lines
+}
+
+fn join_lines(lines: &[String]) -> String {
+    let mut ics = String::new();
+    for line in lines {
+        ics.push_str(&fold(line));
+        ics.push_str("\r\n");
+    }
+    ics
+}
+
+// 生成任务对应的 iCalendar 文本
+// existing 为远端当前的内容，更新时保留其中 DeskHive 不管理的属性（描述、提醒、分类等）
+pub fn build_vtodo(todo: &Todo, existing: Option<&str>, now: i64) -> String {
+    let existing_lines = existing.map(unfold).filter(|lines| {
+        lines.iter().any(|line| line.eq_ignore_ascii_case("BEGIN:VTODO"))
+    });
+
+    let existing_lines = match existing_lines {
+        Some(lines) => lines,
+        None => {
+            let mut lines = vec![
+                "BEGIN:VCALENDAR".to_string(),
+                "VERSION:2.0".to_string(),
+                "PRODID:-//DeskHive//DeskHive//ZH".to_string(),
+                "BEGIN:VTODO".to_string(),
+                format!("UID:{}", todo.id),
+                format!("CREATED:{}", format_utc(todo.created_at)),
+            ];
+            lines.extend(managed_lines(todo, now));
+            lines.push("END:VTODO".to_string());
+            lines.push("END:VCALENDAR".to_string());
+            return join_lines(&lines);
+        }
+    };
+
+    let mut lines = Vec::new();
+    let mut in_vtodo = false;
+    let mut depth = 0;
+    let mut replaced = false;
+    for line in existing_lines {
+        let property = parse_property(&line);
+        let (name, value) = property.as_ref()
+            .map(|p| (p.name.as_str(), p.value.to_ascii_uppercase()))
+            .unwrap_or_default();
+
+        match (name, value.as_str()) {
+            ("BEGIN", "VTODO") if !replaced => {
+                in_vtodo = true;
+                lines.push(line);
+                lines.extend(managed_lines(todo, now));
+                continue;
+            }
+            ("END", "VTODO") if in_vtodo && depth == 0 => {
+                in_vtodo = false;
+                replaced = true;
+            }
+            ("BEGIN", _) if in_vtodo => depth += 1,
+            ("END", _) if in_vtodo => depth -= 1,
+            _ => {}
+        }
+
+        if in_vtodo && depth == 0 && MANAGED_PROPERTIES.contains(&name) {
+            continue;
+        }
+        lines.push(line);
+    }
+
+    join_lines(&lines)
+}
@@ -0,0 +1,553 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+use crate::models::{FieldConflict, GroupData, MergeReport, TodoDataWithGroups, Tombstone};
+
+// 删除记录的保留时间，超过后不再参与同步
+pub const TOMBSTONE_RETENTION_SECS: i64 = 90 * 24 * 60 * 60;
+
+// 同步元数据字段，不参与逐字段比较
+const META_FIELDS: [&str; 3] = ["id", "updated_at", "field_updated_at"];
+
+type Object = Map<String, Value>;
+
+// 可以逐项合并的数据文件
+pub trait SyncStore: Serialize + DeserializeOwned + Default {
+    // 以逐字段“最后写入者胜出”的方式合并本地和远端数据
+    // since：上次同步时间，两端都在此之后修改的字段记为冲突
+    fn merge(local: &Self, remote: &Self, since: i64, now: i64) -> Result<(Self, MergeReport), String>;
+    // 删除记录
+    fn tombstones_mut(&mut self) -> &mut Vec<Tombstone>;
+    // 移除指定ID的数据项
+    fn remove_items(&mut self, ids: &HashSet<String>);
+}
+
+impl SyncStore for TodoDataWithGroups {
+    fn merge(local: &Self, remote: &Self, since: i64, now: i64) -> Result<(Self, MergeReport), String> {
+        let (todos, tombstones, report) = merge_items(
+            &local.todos, &local.tombstones,
+            &remote.todos, &remote.tombstones,
+            since, now,
+        )?;
+        Ok((TodoDataWithGroups { todos, tombstones }, report))
+    }
+
+    fn tombstones_mut(&mut self) -> &mut Vec<Tombstone> {
+        &mut self.tombstones
+    }
+
+    fn remove_items(&mut self, ids: &HashSet<String>) {
+        self.todos.retain(|todo| !ids.contains(&todo.id));
+    }
+}
+
+impl SyncStore for GroupData {
+    fn merge(local: &Self, remote: &Self, since: i64, now: i64) -> Result<(Self, MergeReport), String> {
+        let (groups, tombstones, report) = merge_items(
+            &local.groups, &local.tombstones,
+            &remote.groups, &remote.tombstones,
+            since, now,
+        )?;
+        Ok((GroupData { groups, tombstones }, report))
+    }
+
+    fn tombstones_mut(&mut self) -> &mut Vec<Tombstone> {
+        &mut self.tombstones
+    }
+
+    fn remove_items(&mut self, ids: &HashSet<String>) {
+        self.groups.retain(|group| !ids.contains(&group.id));
+    }
+}
+
+fn to_objects<T: Serialize>(items: &[T]) -> Result<Vec<Object>, String> {
+    items.iter()
+        .map(|item| match serde_json::to_value(item) {
+            Ok(Value::Object(object)) => Ok(object),
+            Ok(_) => Err("同步数据项必须是JSON对象".to_string()),
+            Err(e) => Err(format!("序列化同步数据失败: {}", e)),
+        })
+        .collect()
+}
+
+fn from_objects<T: DeserializeOwned>(objects: Vec<Object>) -> Result<Vec<T>, String> {
+    objects.into_iter()
+        .map(|object| serde_json::from_value(Value::Object(object))
+            .map_err(|e| format!("解析同步数据失败: {}", e)))
+        .collect()
+}
+
+fn item_id(object: &Object) -> String {
+    object.get("id").and_then(Value::as_str).unwrap_or_default().to_string()
+}
+
+fn updated_at(object: &Object) -> i64 {
+    object.get("updated_at").and_then(Value::as_i64).unwrap_or(0)
+}
+
+fn field_times(object: &Object) -> BTreeMap<String, i64> {
+    object.get("field_updated_at")
+        .and_then(|value| serde_json::from_value(value.clone()).ok())
+        .unwrap_or_default()
+}
+
+// 字段的修改时间，未单独记录时以整项的修改时间为准
+fn field_time(object: &Object, times: &BTreeMap<String, i64>, field: &str) -> i64 {
+    times.get(field).copied().unwrap_or_else(|| updated_at(object))
+}
+
+// 两个数据项中所有需要逐字段比较的字段
+fn data_fields(a: &Object, b: &Object) -> BTreeSet<String> {
+    a.keys()
+        .chain(b.keys())
+        .filter(|key| !META_FIELDS.contains(&key.as_str()))
+        .cloned()
+        .collect()
+}
+
+// 写入同步元数据，与整项修改时间相同的字段时间不单独记录
+fn set_meta(object: &mut Object, updated: i64, mut times: BTreeMap<String, i64>) {
+    times.retain(|_, time| *time != updated);
+    object.insert("updated_at".to_string(), Value::from(updated));
+    object.insert("field_updated_at".to_string(), Value::from(Map::from_iter(
+        times.into_iter().map(|(field, time)| (field, Value::from(time)))
+    )));
+}
+
+// 清理过期和已被重新创建的数据项对应的删除记录
+fn prune_tombstones(tombstones: HashMap<String, i64>, live_ids: &HashSet<String>, now: i64) -> Vec<Tombstone> {
+    let mut pruned: Vec<Tombstone> = tombstones.into_iter()
+        .filter(|(id, deleted_at)| !live_ids.contains(id) && now - deleted_at < TOMBSTONE_RETENTION_SECS)
+        .map(|(id, deleted_at)| Tombstone { id, deleted_at })
+        .collect();
+    pruned.sort_by(|a, b| a.id.cmp(&b.id));
+    pruned
+}
+
+// 与磁盘上的数据比较，为新保存的数据记录各字段的修改时间，并为被删除的数据项生成删除记录
+// base：保存方上次看到的数据（例如前端最近一次加载的数据），为 None 时以磁盘上的数据为准
+// - 保存方没有修改的字段沿用磁盘上的值，避免用过期的数据覆盖同步进来的修改
+// - 保存方没见过的数据项（例如同步新增的任务）会被保留，只有保存方见过且删除的数据项才记为删除
+// - 前端不认识的字段不会回传，这些字段沿用磁盘上的值
+pub fn stamp_changes<T: Serialize + DeserializeOwned>(
+    previous: &[T],
+    previous_tombstones: &[Tombstone],
+    incoming: Vec<T>,
+    base: Option<&[T]>,
+    now: i64,
+) -> Result<(Vec<T>, Vec<Tombstone>), String> {
+    let previous_objects = to_objects(previous)?;
+    let previous_by_id: HashMap<String, &Object> = previous_objects.iter()
+        .map(|object| (item_id(object), object))
+        .collect();
+    let base_objects = match base {
+        Some(base) => Some(to_objects(base)?),
+        None => None,
+    };
+    let base_by_id: Option<HashMap<String, &Object>> = base_objects.as_ref().map(|objects| objects.iter()
+        .map(|object| (item_id(object), object))
+        .collect());
+
+    let mut stamped = Vec::with_capacity(incoming.len());
+    let mut live_ids = HashSet::new();
+    for mut object in to_objects(&incoming)? {
+        let id = item_id(&object);
+        live_ids.insert(id.clone());
+
+        let previous = match previous_by_id.get(&id) {
+            Some(previous) => *previous,
+            None => {
+                // 新增的数据项
+                let updated = match updated_at(&object) {
+                    0 => now,
+                    existing => existing,
+                };
+                let times = field_times(&object);
+                set_meta(&mut object, updated, times);
+                stamped.push(object);
+                continue;
+            }
+        };
+        let base_object = match &base_by_id {
+            Some(base_by_id) => base_by_id.get(&id).copied(),
+            None => Some(previous),
+        };
+
+        let previous_times = field_times(previous);
+        let mut times = BTreeMap::new();
+        let mut changed = false;
+        for field in data_fields(previous, &object) {
+            let previous_time = field_time(previous, &previous_times, &field);
+            let previous_value = previous.get(&field).cloned();
+            let value = object.get(&field).cloned();
+            let modified = match base_object {
+                Some(base_object) => value.is_some() && value.as_ref() != base_object.get(&field),
+                None => value.is_some(),
+            };
+
+            if modified && value != previous_value {
+                times.insert(field, now);
+                changed = true;
+                continue;
+            }
+
+            // 保存方没有修改该字段，沿用磁盘上的值
+            match previous_value {
+                Some(previous_value) => object.insert(field.clone(), previous_value),
+                None => object.remove(&field),
+            };
+            times.insert(field, previous_time);
+        }
+        let updated = if changed {
+            now.max(updated_at(previous))
+        } else {
+            updated_at(previous)
+        };
+        set_meta(&mut object, updated, times);
+        stamped.push(object);
+    }
+
+    let mut tombstones: HashMap<String, i64> = previous_tombstones.iter()
+        .map(|tombstone| (tombstone.id.clone(), tombstone.deleted_at))
+        .collect();
+    for previous in &previous_objects {
+        let id = item_id(previous);
+        if live_ids.contains(&id) {
+            continue;
+        }
+        let seen = base_by_id.as_ref().map_or(true, |base_by_id| base_by_id.contains_key(&id));
+        if seen {
+            tombstones.insert(id, now);
+        } else {
+            live_ids.insert(id);
+            stamped.push(previous.clone());
+        }
+    }
+
+    Ok((from_objects(stamped)?, prune_tombstones(tombstones, &live_ids, now)))
+}
+
+// 前端只回传它认识的字段，缺少的字段用 reference 中的同一数据项补齐后再解析
+// reference 应为保存方上次看到的数据，补齐的字段与其一致，stamp_changes 会把它们视为未修改
+// 否则缺少的字段会被解析为默认值，当作用户清空了这些字段
+pub fn fill_missing_fields<T: Serialize + DeserializeOwned>(incoming: Vec<Value>, reference: &[T]) -> Result<Vec<T>, String> {
+    let reference_objects = to_objects(reference)?;
+    let reference_by_id: HashMap<String, &Object> = reference_objects.iter()
+        .map(|object| (item_id(object), object))
+        .collect();
+
+    incoming.into_iter()
+        .map(|value| {
+            let mut object = match value {
+                Value::Object(object) => object,
+                _ => return Err("数据项必须是JSON对象".to_string()),
+            };
+            if let Some(known) = reference_by_id.get(&item_id(&object)) {
+                for (field, value) in known.iter() {
+                    if !META_FIELDS.contains(&field.as_str()) && !object.contains_key(field) {
+                        object.insert(field.clone(), value.clone());
+                    }
+                }
+            }
+            serde_json::from_value(Value::Object(object)).map_err(|e| format!("解析数据失败: {}", e))
+        })
+        .collect()
+}
+
+// 逐字段合并同一数据项的两个版本，返回合并结果以及是否采用了远端的修改
+fn merge_object(local: &Object, remote: &Object, since: i64, conflicts: &mut Vec<FieldConflict>) -> (Object, bool) {
+    let local_times = field_times(local);
+    let remote_times = field_times(remote);
+
+    let mut merged = local.clone();
+    let mut times = BTreeMap::new();
+    let mut changed = false;
+    for field in data_fields(local, remote) {
+        let local_time = field_time(local, &local_times, &field);
+        let remote_time = field_time(remote, &remote_times, &field);
+        let local_value = local.get(&field);
+        let remote_value = remote.get(&field);
+
+        if local_value == remote_value {
+            times.insert(field, local_time.max(remote_time));
+            continue;
+        }
+
+        // 修改时间相同时按值排序决定，保证各端合并结果一致
+        let take_remote = match remote_time.cmp(&local_time) {
+            std::cmp::Ordering::Greater => true,
+            std::cmp::Ordering::Less => false,
+            std::cmp::Ordering::Equal => {
+                let rank = |value: Option<&Value>| value.map(|v| v.to_string()).unwrap_or_default();
+                rank(remote_value) > rank(local_value)
+            }
+        };
+
+        if local_time > since && remote_time > since {
+            conflicts.push(FieldConflict {
+                item_id: item_id(local),
+                field: field.clone(),
+                kept: if take_remote { "remote" } else { "local" }.to_string(),
+            });
+        }
+
+        if take_remote {
+            match remote_value {
+                Some(value) => merged.insert(field.clone(), value.clone()),
+                None => merged.remove(&field),
+            };
+            times.insert(field, remote_time);
+            changed = true;
+        } else {
+            times.insert(field, local_time);
+        }
+    }
+
+    set_meta(&mut merged, updated_at(local).max(updated_at(remote)), times);
+    (merged, changed)
+}
+
+// 合并本地和远端的数据项列表
+// 只存在于一端且没有删除记录的数据项会被保留，避免同步时丢失对方尚未见过的新数据
+pub fn merge_items<T: Serialize + DeserializeOwned>(
+    local: &[T],
+    local_tombstones: &[Tombstone],
+    remote: &[T],
+    remote_tombstones: &[Tombstone],
+    since: i64,
+    now: i64,
+) -> Result<(Vec<T>, Vec<Tombstone>, MergeReport), String> {
+    let local_objects = to_objects(local)?;
+    let remote_objects = to_objects(remote)?;
+    let local_ids: HashSet<String> = local_objects.iter().map(item_id).collect();
+    let remote_by_id: HashMap<String, &Object> = remote_objects.iter()
+        .map(|object| (item_id(object), object))
+        .collect();
+
+    // 两端的删除记录取较晚的删除时间
+    let mut tombstones: HashMap<String, i64> = HashMap::new();
+    for tombstone in local_tombstones.iter().chain(remote_tombstones) {
+        let deleted_at = tombstones.entry(tombstone.id.clone()).or_insert(tombstone.deleted_at);
+        *deleted_at = (*deleted_at).max(tombstone.deleted_at);
+    }
+    let is_deleted = |object: &Object| tombstones
+        .get(&item_id(object))
+        .is_some_and(|deleted_at| *deleted_at >= updated_at(object));
+
+    let mut report = MergeReport::default();
+    let mut merged = Vec::new();
+    let mut live_ids = HashSet::new();
+
+    for local_object in &local_objects {
+        let id = item_id(local_object);
+        let (object, changed) = match remote_by_id.get(&id) {
+            Some(remote_object) => merge_object(local_object, remote_object, since, &mut report.conflicts),
+            None => (local_object.clone(), false),
+        };
+
+        if is_deleted(&object) {
+            report.removed += 1;
+            continue;
+        }
+        if changed {
+            report.updated += 1;
+        }
+        live_ids.insert(id);
+        merged.push(object);
+    }
+
+    for remote_object in &remote_objects {
+        let id = item_id(remote_object);
+        if local_ids.contains(&id) || live_ids.contains(&id) {
+            continue;
+        }
+        if is_deleted(remote_object) {
+            continue;
+        }
+        report.added += 1;
+        live_ids.insert(id);
+        merged.push(remote_object.clone());
+    }
+
+    Ok((from_objects(merged)?, prune_tombstones(tombstones, &live_ids, now), report))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use crate::models::TodoGroup;
+
+    fn tombstone(id: &str, deleted_at: i64) -> Tombstone {
+        Tombstone { id: id.to_string(), deleted_at }
+    }
+
+    fn deleted(tombstones: &[Tombstone]) -> Vec<(&str, i64)> {
+        tombstones.iter().map(|tombstone| (tombstone.id.as_str(), tombstone.deleted_at)).collect()
+    }
+
+    fn find<'a>(items: &'a [Value], id: &str) -> &'a Value {
+        items.iter().find(|item| item["id"] == id).expect("数据项不存在")
+    }
+
+    #[test]
+    fn merge_keeps_latest_value_per_field() {
+        let local = vec![json!({"id": "a", "text": "本地", "done": false, "updated_at": 200, "field_updated_at": {"done": 100}})];
+        let remote = vec![json!({"id": "a", "text": "远端", "done": true, "updated_at": 300, "field_updated_at": {"text": 100}})];
+
+        let (merged, tombstones, report) = merge_items(&local, &[], &remote, &[], 150, 400).unwrap();
+        let merged = find(&merged, "a");
+        assert_eq!(merged["text"], "本地");
+        assert_eq!(merged["done"], true);
+        assert_eq!(merged["updated_at"], 300);
+        assert_eq!(report.updated, 1);
+        assert!(report.conflicts.is_empty());
+        assert!(tombstones.is_empty());
+    }
+
+    #[test]
+    fn merge_reports_conflicts_only_after_since() {
+        let local = vec![json!({"id": "a", "text": "本地", "updated_at": 200})];
+        let remote = vec![json!({"id": "a", "text": "远端", "updated_at": 300})];
+
+        let (merged, _, report) = merge_items(&local, &[], &remote, &[], 100, 400).unwrap();
+        assert_eq!(find(&merged, "a")["text"], "远端");
+        assert_eq!(report.conflicts.len(), 1);
+        assert_eq!(report.conflicts[0].field, "text");
+        assert_eq!(report.conflicts[0].kept, "remote");
+
+        // 本地的修改早于上次同步，远端的修改不算冲突
+        let (_, _, report) = merge_items(&local, &[], &remote, &[], 250, 400).unwrap();
+        assert!(report.conflicts.is_empty());
+    }
+
+    #[test]
+    fn merge_breaks_ties_by_value() {
+        let local = vec![json!({"id": "a", "text": "a", "updated_at": 200})];
+        let remote = vec![json!({"id": "a", "text": "b", "updated_at": 200})];
+
+        let (forward, _, _) = merge_items(&local, &[], &remote, &[], 0, 400).unwrap();
+        let (backward, _, _) = merge_items(&remote, &[], &local, &[], 0, 400).unwrap();
+        assert_eq!(find(&forward, "a")["text"], "b");
+        assert_eq!(find(&backward, "a")["text"], "b");
+    }
+
+    #[test]
+    fn merge_applies_tombstones_and_keeps_new_items() {
+        let local = vec![
+            json!({"id": "deleted", "updated_at": 200}),
+            json!({"id": "edited", "updated_at": 300}),
+            json!({"id": "local-only", "updated_at": 100}),
+        ];
+        let remote = vec![json!({"id": "remote-only", "updated_at": 100})];
+        let remote_tombstones = vec![tombstone("deleted", 250), tombstone("edited", 250)];
+
+        let (merged, tombstones, report) = merge_items(&local, &[], &remote, &remote_tombstones, 0, 400).unwrap();
+        let ids: Vec<&str> = merged.iter().map(|item| item["id"].as_str().unwrap()).collect();
+        assert_eq!(ids, ["edited", "local-only", "remote-only"]);
+        assert_eq!(report.removed, 1);
+        assert_eq!(report.added, 1);
+        // 删除后又被修改的数据项保留，其删除记录被清理
+        assert_eq!(deleted(&tombstones), [("deleted", 250)]);
+    }
+
+    #[test]
+    fn merge_prunes_expired_tombstones() {
+        let now = TOMBSTONE_RETENTION_SECS + 1000;
+        let tombstones = vec![tombstone("old", 500), tombstone("recent", now - 10)];
+
+        let (_, pruned, _) = merge_items::<Value>(&[], &tombstones, &[], &[], 0, now).unwrap();
+        assert_eq!(deleted(&pruned), [("recent", now - 10)]);
+    }
+
+    #[test]
+    fn stamp_without_base_compares_with_disk() {
+        let previous = vec![
+            json!({"id": "a", "text": "旧", "done": false, "updated_at": 100}),
+            json!({"id": "b", "text": "删除", "updated_at": 100}),
+        ];
+        let incoming = vec![
+            json!({"id": "a", "text": "新", "done": false, "updated_at": 100}),
+            json!({"id": "c", "text": "新增", "updated_at": 0}),
+        ];
+
+        let (stamped, tombstones) = stamp_changes(&previous, &[], incoming, None, 500).unwrap();
+        let a = find(&stamped, "a");
+        assert_eq!(a["text"], "新");
+        assert_eq!(a["updated_at"], 500);
+        assert_eq!(a["field_updated_at"], json!({"done": 100}));
+        assert_eq!(find(&stamped, "c")["updated_at"], 500);
+        assert_eq!(stamped.len(), 2);
+        assert_eq!(deleted(&tombstones), [("b", 500)]);
+    }
+
+    #[test]
+    fn stamp_with_base_keeps_changes_the_saver_has_not_seen() {
+        // 磁盘上的数据包含同步进来的修改：a 的 done 字段、新任务 s，以及前端不认识的 color 字段
+        let previous = vec![
+            json!({"id": "a", "text": "旧", "done": true, "color": "red", "updated_at": 300}),
+            json!({"id": "b", "text": "删除", "updated_at": 100}),
+            json!({"id": "s", "text": "同步新增", "updated_at": 300}),
+        ];
+        let base = vec![
+            json!({"id": "a", "text": "旧", "done": false, "updated_at": 100}),
+            json!({"id": "b", "text": "删除", "updated_at": 100}),
+        ];
+        let incoming = vec![json!({"id": "a", "text": "新", "done": false, "updated_at": 100})];
+
+        let (stamped, tombstones) = stamp_changes(&previous, &[], incoming, Some(&base), 500).unwrap();
+        let a = find(&stamped, "a");
+        assert_eq!(a["text"], "新");
+        assert_eq!(a["done"], true);
+        assert_eq!(a["color"], "red");
+        assert_eq!(a["updated_at"], 500);
+        assert_eq!(a["field_updated_at"], json!({"color": 300, "done": 300}));
+        assert_eq!(find(&stamped, "s")["text"], "同步新增");
+        assert_eq!(deleted(&tombstones), [("b", 500)]);
+    }
+
+    #[test]
+    fn partial_group_save_keeps_unsent_fields() {
+        let stored = vec![TodoGroup {
+            id: "work".to_string(),
+            name: "工作".to_string(),
+            order: 1,
+            parent_id: Some("root".to_string()),
+            color: Some("#FF0000".to_string()),
+            icon: Some("💼".to_string()),
+            default_priority: 1,
+            default_deadline_offset: Some("+1d 18:00".to_string()),
+            updated_at: 100,
+            ..Default::default()
+        }];
+        // 旧版前端只回传这几个字段
+        let incoming = vec![json!({"id": "work", "name": "工作", "order": 2, "collapsed": true})];
+
+        let filled: Vec<TodoGroup> = fill_missing_fields(incoming, &stored).unwrap();
+        let (saved, tombstones) = stamp_changes(&stored, &[], filled, Some(&stored), 500).unwrap();
+        let group = &saved[0];
+        assert_eq!(group.order, 2);
+        assert!(group.collapsed);
+        assert_eq!(group.parent_id.as_deref(), Some("root"));
+        assert_eq!(group.color.as_deref(), Some("#FF0000"));
+        assert_eq!(group.icon.as_deref(), Some("💼"));
+        assert_eq!(group.default_priority, 1);
+        assert_eq!(group.default_deadline_offset.as_deref(), Some("+1d 18:00"));
+        assert_eq!(group.field_updated_at.get("color"), Some(&100));
+        assert_eq!(group.field_updated_at.get("order"), None);
+        assert_eq!(group.updated_at, 500);
+        assert!(tombstones.is_empty());
+    }
+
+    #[test]
+    fn fill_keeps_explicitly_cleared_fields() {
+        let stored = vec![TodoGroup { id: "work".to_string(), color: Some("#FF0000".to_string()), ..Default::default() }];
+        let incoming = vec![json!({"id": "work", "name": "", "order": 0, "collapsed": false, "color": null})];
+
+        let filled: Vec<TodoGroup> = fill_missing_fields(incoming, &stored).unwrap();
+        assert_eq!(filled[0].color, None);
+        assert!(fill_missing_fields::<TodoGroup>(vec![json!("work")], &stored).is_err());
+    }
+}
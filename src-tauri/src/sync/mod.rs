@@ -0,0 +1,6 @@
+pub mod merge;
+pub mod folder;
+pub mod webdav;
+pub mod scheduler;
+pub mod caldav;
+pub mod ical;
@@ -0,0 +1,133 @@
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::Emitter;
+
+use crate::data::storage::data_version;
+use crate::models::{AppSettings, MergeReport, SyncStatus};
+
+// 本地数据变化后等待多久再同步，避免连续编辑时频繁上传
+const CHANGE_DEBOUNCE_SECS: i64 = 10;
+
+// 后台同步线程的检查间隔
+const CHECK_INTERVAL: Duration = Duration::from_secs(5);
+
+// 一种同步方式（WebDAV、CalDAV）的运行状态，同一时间只允许一次同步
+pub struct SyncRunner {
+    name: &'static str, // 用于日志和事件名，例如 webdav 对应 webdav-sync-status
+    status: Mutex<Option<SyncStatus>>,
+    running: Mutex<()>,
+}
+
+impl SyncRunner {
+    pub const fn new(name: &'static str) -> Self {
+        Self {
+            name,
+            status: Mutex::new(None),
+            running: Mutex::new(()),
+        }
+    }
+
+    pub fn current_status(&self) -> SyncStatus {
+        self.status.lock()
+            .map(|status| status.clone().unwrap_or_default())
+            .unwrap_or_default()
+    }
+
+    fn update_status(&self, update: impl FnOnce(&mut SyncStatus)) -> SyncStatus {
+        let mut guard = self.status.lock().unwrap_or_else(|e| e.into_inner());
+        let status = guard.get_or_insert_with(SyncStatus::default);
+        update(status);
+        status.clone()
+    }
+
+    // 执行一次同步，并通知前端同步结果
+    // sync 返回合并结果和需要发送的重新加载事件
+    pub fn run(
+        &self,
+        app: &tauri::AppHandle,
+        sync: impl FnOnce() -> Result<(MergeReport, Vec<&'static str>), String>,
+    ) -> SyncStatus {
+        let _running = match self.running.try_lock() {
+            Ok(guard) => guard,
+            Err(_) => return self.current_status(),
+        };
+
+        let status_event = format!("{}-sync-status", self.name);
+        let _ = app.emit(&status_event, self.update_status(|status| status.syncing = true));
+
+        let started_at = chrono::Utc::now().timestamp();
+        let status = match sync() {
+            Ok((report, changed_events)) => {
+                for event in changed_events {
+                    let _ = app.emit(event, ());
+                }
+                if !report.conflicts.is_empty() {
+                    log::warn!("{}同步发现 {} 个冲突字段，已按最后修改时间处理", self.name, report.conflicts.len());
+                    let _ = app.emit(&format!("{}-sync-conflict", self.name), report.conflicts.clone());
+                }
+                self.update_status(|status| {
+                    status.syncing = false;
+                    status.last_sync = Some(started_at);
+                    status.last_error = None;
+                    status.last_report = report;
+                })
+            }
+            Err(e) => {
+                log::error!("{}同步失败: {}", self.name, e);
+                self.update_status(|status| {
+                    status.syncing = false;
+                    status.last_error = Some(e);
+                })
+            }
+        };
+
+        let _ = app.emit(&status_event, status.clone());
+        status
+    }
+}
+
+// 启动后台同步线程：按设定间隔同步，本地数据变化后也会触发同步
+// interval_minutes 返回 None 表示该同步方式未启用
+pub fn spawn_sync_loop(
+    app: tauri::AppHandle,
+    interval_minutes: fn(&AppSettings) -> Option<u32>,
+    run: fn(&tauri::AppHandle, &AppSettings) -> SyncStatus,
+) {
+    std::thread::spawn(move || {
+        let mut last_attempt: i64 = 0;
+        let mut synced_version = data_version();
+        let mut changed_at: Option<i64> = None;
+
+        loop {
+            std::thread::sleep(CHECK_INTERVAL);
+
+            let app_handle = app.clone();
+            let settings = match tauri::async_runtime::block_on(crate::data::load_app_settings(app_handle)) {
+                Ok(settings) => settings,
+                Err(_) => continue,
+            };
+            let interval = match interval_minutes(&settings) {
+                Some(minutes) => minutes.max(1) as i64 * 60,
+                None => continue,
+            };
+
+            let now = chrono::Utc::now().timestamp();
+            let version = data_version();
+            if version != synced_version && changed_at.is_none() {
+                changed_at = Some(now);
+            }
+
+            let interval_due = now - last_attempt >= interval;
+            let change_due = changed_at.is_some_and(|at| now - at >= CHANGE_DEBOUNCE_SECS);
+            if !interval_due && !change_due {
+                continue;
+            }
+
+            last_attempt = now;
+            run(&app, &settings);
+            // 同步本身写入的数据不再触发下一次同步
+            synced_version = data_version();
+            changed_at = None;
+        }
+    });
+}
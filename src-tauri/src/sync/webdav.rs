@@ -0,0 +1,517 @@
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use serde_json;
+
+use crate::data::archive::archived_todo_ids;
+use crate::data::encryption::{decrypt_contents, encrypt_for};
+use crate::data::storage::{lock_store, read_data_file, write_json_file};
+use crate::models::{AppSettings, GroupData, MergeReport, TodoDataWithGroups, SyncStatus};
+use crate::system::credentials::{resolve_password, WEBDAV_ACCOUNT};
+use super::merge::SyncStore;
+use super::scheduler::{spawn_sync_loop, SyncRunner};
+
+// 参与同步的数据文件（本地与远端使用相同的文件名）
+const TODO_FILE: &str = "todos_with_groups.json";
+const GROUP_FILE: &str = "groups.json";
+
+// 记录上次同步时间的状态文件
+const STATE_FILE: &str = "webdav_state.json";
+
+// 远端数据在读取和写入之间被修改时的最大重试次数
+const MAX_ATTEMPTS: usize = 3;
+
+// 远端文件
+pub struct RemoteFile {
+    pub contents: String,
+    pub etag: Option<String>,
+}
+
+// 写入远端文件的前提条件（乐观并发控制）
+pub enum WriteCondition {
+    Absent,           // 仅当远端文件不存在时写入
+    Matches(String),  // 仅当远端文件的ETag与读取时一致时写入
+    Any,              // 远端没有提供ETag，无条件写入
+}
+
+// 写入远端文件的结果
+pub enum PutOutcome {
+    Stored(Option<String>), // 写入成功，附带远端返回的新ETag
+    PreconditionFailed, // 远端文件已被其他设备修改
+}
+
+// 远端存储抽象，测试时可以替换为进程内的模拟实现
+pub trait RemoteStore {
+    // 读取远端文件，文件不存在时返回 None
+    fn get(&self, name: &str) -> Result<Option<RemoteFile>, String>;
+    // 按前提条件写入远端文件
+    fn put(&self, name: &str, contents: &str, condition: WriteCondition) -> Result<PutOutcome, String>;
+}
+
+// 生成HTTP基本认证请求头，未填写用户名时不认证
+pub(crate) fn basic_authorization(username: &str, password: &str) -> Option<String> {
+    if username.is_empty() {
+        return None;
+    }
+    let credentials = base64::engine::general_purpose::STANDARD
+        .encode(format!("{}:{}", username, password));
+    Some(format!("Basic {}", credentials))
+}
+
+// 判断地址是否指向本机，本机上的服务即使使用 http:// 也不会在网络上传输密码
+fn is_loopback(url: &str) -> bool {
+    url::Url::parse(url).ok()
+        .and_then(|url| url.host().map(|host| match host {
+            url::Host::Domain(domain) => domain.eq_ignore_ascii_case("localhost"),
+            url::Host::Ipv4(ip) => ip.is_loopback(),
+            url::Host::Ipv6(ip) => ip.is_loopback(),
+        }))
+        .unwrap_or(false)
+}
+
+// 基本认证只对密码做 Base64 编码，拒绝通过未加密的 http:// 连接发送到其他主机
+pub(crate) fn check_plain_http_auth(url: &str, username: &str) -> Result<(), String> {
+    if !username.is_empty() && url.starts_with("http://") && !is_loopback(url) {
+        return Err(format!("拒绝通过未加密的连接发送密码，请改用 https:// 地址: {}", url));
+    }
+    Ok(())
+}
+
+// 基于 WebDAV 的远端存储
+pub struct WebDavRemote {
+    base_url: String,
+    authorization: Option<String>,
+}
+
+impl WebDavRemote {
+    pub fn new(base_url: &str, username: &str, password: &str) -> Result<Self, String> {
+        let base_url = base_url.trim().trim_end_matches('/').to_string();
+        if !(base_url.starts_with("https://") || base_url.starts_with("http://")) {
+            return Err(format!("无效的WebDAV地址: {}", base_url));
+        }
+        check_plain_http_auth(&base_url, username)?;
+
+        Ok(Self { base_url, authorization: basic_authorization(username, password) })
+    }
+
+    fn request(&self, method: &str, url: &str) -> ureq::Request {
+        let request = ureq::request(method, url).timeout(Duration::from_secs(30));
+        match &self.authorization {
+            Some(authorization) => request.set("Authorization", authorization),
+            None => request,
+        }
+    }
+
+    fn file_url(&self, name: &str) -> String {
+        format!("{}/{}", self.base_url, name)
+    }
+
+    // 确保远端目录存在
+    pub fn ensure_collection(&self) -> Result<(), String> {
+        match self.request("MKCOL", &format!("{}/", self.base_url)).call() {
+            Ok(_) => Ok(()),
+            // 405：目录已存在
+            Err(ureq::Error::Status(405, _)) => Ok(()),
+            Err(e) => Err(format!("创建WebDAV目录失败: {}", e)),
+        }
+    }
+}
+
+impl RemoteStore for WebDavRemote {
+    fn get(&self, name: &str) -> Result<Option<RemoteFile>, String> {
+        match self.request("GET", &self.file_url(name)).call() {
+            Ok(response) => {
+                let etag = response.header("ETag").map(|etag| etag.to_string());
+                let contents = response.into_string()
+                    .map_err(|e| format!("读取WebDAV响应失败: {}", e))?;
+                Ok(Some(RemoteFile { contents, etag }))
+            }
+            Err(ureq::Error::Status(404, _)) => Ok(None),
+            Err(e) => Err(format!("下载 {} 失败: {}", name, e)),
+        }
+    }
+
+    fn put(&self, name: &str, contents: &str, condition: WriteCondition) -> Result<PutOutcome, String> {
+        let request = self.request("PUT", &self.file_url(name))
+            .set("Content-Type", "application/json; charset=utf-8");
+        let request = match &condition {
+            WriteCondition::Absent => request.set("If-None-Match", "*"),
+            WriteCondition::Matches(etag) => request.set("If-Match", etag),
+            WriteCondition::Any => request,
+        };
+
+        match request.send_string(contents) {
+            Ok(response) => Ok(PutOutcome::Stored(response.header("ETag").map(|etag| etag.to_string()))),
+            Err(ureq::Error::Status(412, _)) => Ok(PutOutcome::PreconditionFailed),
+            Err(e) => Err(format!("上传 {} 失败: {}", name, e)),
+        }
+    }
+}
+
+// 同步单个数据文件：下载远端版本，逐项合并后写回本地，再按ETag条件上传
+// 上传时远端已被其他设备修改则重新下载合并，返回合并结果和本地数据是否有变化
+// local_only：只在本机移除的数据项（已归档的任务），它们的删除记录不发送到远端，
+// 远端的副本保持原样，也不会合并回本地
+fn sync_store<S: SyncStore>(
+    remote: &dyn RemoteStore,
+    local_path: &Path,
+    name: &str,
+    since: i64,
+    local_only: &HashSet<String>,
+) -> Result<(MergeReport, bool), String> {
+    let as_value = |store: &S| serde_json::to_value(store).map_err(|e| format!("序列化数据失败: {}", e));
+    let mut local_changed = false;
+
+    for _ in 0..MAX_ATTEMPTS {
+        let remote_file = remote.get(name)?;
+        let remote_store: Option<S> = match &remote_file {
+            Some(file) => Some(serde_json::from_str(&decrypt_contents(&file.contents)?)
+                .map_err(|e| format!("解析远端 {} 失败: {}", name, e))?),
+            None => None,
+        };
+
+        // 合并并写回本地，期间持有数据文件锁，避免覆盖前端同时进行的保存
+        let (merged, report) = {
+            let _guard = lock_store();
+            let mut local: S = if local_path.exists() {
+                serde_json::from_str(&read_data_file(local_path)?)
+                    .map_err(|e| format!("解析本地 {} 失败: {}", name, e))?
+            } else {
+                S::default()
+            };
+            let local_value = as_value(&local)?;
+            let (local_only_tombstones, shared_tombstones) = std::mem::take(local.tombstones_mut())
+                .into_iter()
+                .partition(|tombstone| local_only.contains(&tombstone.id));
+            *local.tombstones_mut() = shared_tombstones;
+
+            let (merged, report) = match &remote_store {
+                Some(remote_store) => S::merge(&local, remote_store, since, chrono::Utc::now().timestamp())?,
+                None => (local, MergeReport::default()),
+            };
+
+            if !report.is_empty() {
+                let mut merged_local = serde_json::from_value::<S>(as_value(&merged)?)
+                    .map_err(|e| format!("解析合并结果失败: {}", e))?;
+                merged_local.remove_items(local_only);
+                merged_local.tombstones_mut().extend(local_only_tombstones);
+                if as_value(&merged_local)? != local_value {
+                    write_json_file(local_path, &merged_local)?;
+                    local_changed = true;
+                }
+            }
+            (merged, report)
+        };
+
+        // 远端已经是最新数据时无需上传
+        let condition = match (&remote_file, &remote_store) {
+            (Some(file), Some(remote_store)) => {
+                if as_value(remote_store)? == as_value(&merged)? {
+                    return Ok((report, local_changed));
+                }
+                match &file.etag {
+                    Some(etag) => WriteCondition::Matches(etag.clone()),
+                    None => WriteCondition::Any,
+                }
+            }
+            _ => WriteCondition::Absent,
+        };
+
+        // 启用数据加密时远端也只保存密文
+        let contents = serde_json::to_string_pretty(&merged)
+            .map_err(|e| format!("序列化数据失败: {}", e))
+            .and_then(|contents| encrypt_for(local_path, &contents))?;
+        match remote.put(name, &contents, condition)? {
+            PutOutcome::Stored(_) => return Ok((report, local_changed)),
+            PutOutcome::PreconditionFailed => {
+                log::info!("远端 {} 已被其他设备修改，重新合并", name);
+            }
+        }
+    }
+
+    Err(format!("远端 {} 频繁变化，本次同步放弃", name))
+}
+
+// 与远端同步所有数据文件
+// since：上次成功同步的时间，两端都在此之后修改的字段记为冲突
+pub fn sync_with_remote(
+    remote: &dyn RemoteStore,
+    data_dir: &Path,
+    since: i64,
+) -> Result<(MergeReport, Vec<&'static str>), String> {
+    let mut report = MergeReport::default();
+    let mut changed_events = Vec::new();
+
+    let (group_report, groups_changed) = sync_store::<GroupData>(remote, &data_dir.join(GROUP_FILE), GROUP_FILE, since, &HashSet::new())?;
+    report.absorb(group_report);
+    if groups_changed {
+        changed_events.push("group-data-changed");
+    }
+
+    // 归档文件不同步，归档留下的删除记录只在本机生效，避免其他设备上的任务被直接删除
+    let archived = archived_todo_ids(data_dir)?;
+    let (todo_report, todos_changed) = sync_store::<TodoDataWithGroups>(remote, &data_dir.join(TODO_FILE), TODO_FILE, since, &archived)?;
+    report.absorb(todo_report);
+    if todos_changed {
+        changed_events.push("todo-data-changed");
+    }
+
+    Ok((report, changed_events))
+}
+
+// 持久化的同步状态
+#[derive(Serialize, Deserialize, Default)]
+struct SyncState {
+    last_sync: i64,
+}
+
+fn load_state(data_dir: &Path) -> SyncState {
+    fs::read_to_string(data_dir.join(STATE_FILE))
+        .ok()
+        .and_then(|contents| serde_json::from_str(&contents).ok())
+        .unwrap_or_default()
+}
+
+fn save_state(data_dir: &Path, state: &SyncState) -> Result<(), String> {
+    let json_data = serde_json::to_string_pretty(state)
+        .map_err(|e| format!("序列化同步状态失败: {}", e))?;
+    fs::write(data_dir.join(STATE_FILE), json_data)
+        .map_err(|e| format!("写入同步状态失败: {}", e))
+}
+
+// WebDAV同步的运行状态
+static WEBDAV_SYNC: SyncRunner = SyncRunner::new("webdav");
+
+// 执行一次WebDAV同步，并通知前端同步结果
+fn run_sync(app: &tauri::AppHandle, settings: &AppSettings) -> SyncStatus {
+    WEBDAV_SYNC.run(app, || {
+        let data_dir = crate::data::todo_data::get_data_dir(app)?;
+        let password = resolve_password(WEBDAV_ACCOUNT, &settings.webdav_password)?;
+        let remote = WebDavRemote::new(&settings.webdav_url, &settings.webdav_username, &password)?;
+        remote.ensure_collection()?;
+
+        let mut state = load_state(&data_dir);
+        let started_at = chrono::Utc::now().timestamp();
+        let result = sync_with_remote(&remote, &data_dir, state.last_sync)?;
+
+        state.last_sync = started_at;
+        save_state(&data_dir, &state)?;
+        Ok(result)
+    })
+}
+
+// 启动后台同步：按设定间隔同步，本地数据变化后也会触发同步
+pub fn start_webdav_sync(app: tauri::AppHandle) {
+    spawn_sync_loop(
+        app,
+        |settings| (settings.webdav_enabled && !settings.webdav_url.is_empty())
+            .then_some(settings.webdav_sync_interval_minutes),
+        run_sync,
+    );
+}
+
+// Tauri 命令：立即执行WebDAV同步
+#[tauri::command]
+pub async fn sync_webdav_now(app: tauri::AppHandle) -> Result<SyncStatus, String> {
+    let settings = crate::data::load_app_settings(app.clone()).await?;
+    if settings.webdav_url.is_empty() {
+        return Err("尚未配置WebDAV地址".to_string());
+    }
+
+    tauri::async_runtime::spawn_blocking(move || run_sync(&app, &settings))
+        .await
+        .map_err(|e| format!("WebDAV同步任务失败: {}", e))
+}
+
+// Tauri 命令：获取WebDAV同步状态
+#[tauri::command]
+pub async fn get_webdav_sync_status() -> Result<SyncStatus, String> {
+    Ok(WEBDAV_SYNC.current_status())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::{Cell, RefCell};
+    use std::collections::HashMap;
+    use std::path::PathBuf;
+    use crate::models::{TodoGroup, Tombstone};
+
+    // 进程内的远端存储，每次写入生成新的ETag，可以模拟其他设备在读取和写入之间修改远端文件
+    #[derive(Default)]
+    struct MemoryRemote {
+        files: RefCell<HashMap<String, (String, u32)>>,
+        puts: Cell<usize>,
+        // 每次写入前由其他设备写入的内容，用完后不再干扰
+        interference: RefCell<Vec<String>>,
+    }
+
+    impl MemoryRemote {
+        fn store(&self, name: &str, contents: String) -> String {
+            let mut files = self.files.borrow_mut();
+            let version = files.get(name).map_or(1, |(_, version)| version + 1);
+            files.insert(name.to_string(), (contents, version));
+            format!("\"{}\"", version)
+        }
+
+        fn groups(&self, name: &str) -> GroupData {
+            let files = self.files.borrow();
+            serde_json::from_str(&files[name].0).unwrap()
+        }
+    }
+
+    impl RemoteStore for MemoryRemote {
+        fn get(&self, name: &str) -> Result<Option<RemoteFile>, String> {
+            Ok(self.files.borrow().get(name).map(|(contents, version)| RemoteFile {
+                contents: contents.clone(),
+                etag: Some(format!("\"{}\"", version)),
+            }))
+        }
+
+        fn put(&self, name: &str, contents: &str, condition: WriteCondition) -> Result<PutOutcome, String> {
+            self.puts.set(self.puts.get() + 1);
+            if let Some(other) = self.interference.borrow_mut().pop() {
+                self.store(name, other);
+            }
+
+            let current = self.files.borrow().get(name).map(|(_, version)| format!("\"{}\"", version));
+            let allowed = match (&condition, &current) {
+                (WriteCondition::Absent, current) => current.is_none(),
+                (WriteCondition::Matches(etag), Some(current)) => etag == current,
+                (WriteCondition::Matches(_), None) => false,
+                (WriteCondition::Any, _) => true,
+            };
+            if !allowed {
+                return Ok(PutOutcome::PreconditionFailed);
+            }
+            Ok(PutOutcome::Stored(Some(self.store(name, contents.to_string()))))
+        }
+    }
+
+    fn group(id: &str, name: &str, updated_at: i64) -> TodoGroup {
+        TodoGroup { id: id.to_string(), name: name.to_string(), updated_at, ..Default::default() }
+    }
+
+    fn groups_json(groups: Vec<TodoGroup>) -> String {
+        serde_json::to_string(&GroupData { groups, tombstones: Vec::new() }).unwrap()
+    }
+
+    fn temp_data_file(test: &str, groups: Vec<TodoGroup>) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("deskhive-webdav-{}-{}", test, uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(GROUP_FILE);
+        fs::write(&path, groups_json(groups)).unwrap();
+        path
+    }
+
+    fn names(data: &GroupData) -> Vec<(&str, &str)> {
+        let mut names: Vec<(&str, &str)> = data.groups.iter()
+            .map(|group| (group.id.as_str(), group.name.as_str()))
+            .collect();
+        names.sort();
+        names
+    }
+
+    fn read_local(path: &Path) -> GroupData {
+        serde_json::from_str(&fs::read_to_string(path).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn uploads_when_remote_is_missing() {
+        let remote = MemoryRemote::default();
+        let path = temp_data_file("missing", vec![group("a", "工作", 100)]);
+
+        let (report, local_changed) = sync_store::<GroupData>(&remote, &path, GROUP_FILE, 0, &HashSet::new()).unwrap();
+        assert!(report.is_empty());
+        assert!(!local_changed);
+        assert_eq!(remote.puts.get(), 1);
+        assert_eq!(names(&remote.groups(GROUP_FILE)), [("a", "工作")]);
+    }
+
+    #[test]
+    fn merges_remote_changes_and_uploads_with_etag() {
+        let remote = MemoryRemote::default();
+        remote.store(GROUP_FILE, groups_json(vec![group("a", "工作", 100), group("b", "生活", 100)]));
+        let path = temp_data_file("merge", vec![group("a", "学习", 200)]);
+
+        let (report, local_changed) = sync_store::<GroupData>(&remote, &path, GROUP_FILE, 150, &HashSet::new()).unwrap();
+        assert_eq!(report.added, 1);
+        assert!(local_changed);
+        assert_eq!(names(&read_local(&path)), [("a", "学习"), ("b", "生活")]);
+        assert_eq!(names(&remote.groups(GROUP_FILE)), [("a", "学习"), ("b", "生活")]);
+        assert_eq!(remote.files.borrow()[GROUP_FILE].1, 2);
+    }
+
+    #[test]
+    fn skips_upload_when_remote_is_up_to_date() {
+        let remote = MemoryRemote::default();
+        remote.store(GROUP_FILE, groups_json(vec![group("a", "工作", 100)]));
+        let path = temp_data_file("uptodate", vec![group("a", "工作", 100)]);
+
+        sync_store::<GroupData>(&remote, &path, GROUP_FILE, 0, &HashSet::new()).unwrap();
+        assert_eq!(remote.puts.get(), 0);
+    }
+
+    #[test]
+    fn remerges_after_precondition_failed() {
+        let remote = MemoryRemote::default();
+        remote.store(GROUP_FILE, groups_json(vec![group("a", "工作", 100)]));
+        // 上传前其他设备新增了分组 c
+        remote.interference.borrow_mut().push(groups_json(vec![group("a", "工作", 100), group("c", "其他设备", 300)]));
+        let path = temp_data_file("conflict", vec![group("a", "工作", 100), group("b", "本机", 200)]);
+
+        sync_store::<GroupData>(&remote, &path, GROUP_FILE, 0, &HashSet::new()).unwrap();
+        assert_eq!(remote.puts.get(), 2);
+        let expected = [("a", "工作"), ("b", "本机"), ("c", "其他设备")];
+        assert_eq!(names(&remote.groups(GROUP_FILE)), expected);
+        assert_eq!(names(&read_local(&path)), expected);
+    }
+
+    #[test]
+    fn gives_up_when_remote_keeps_changing() {
+        let remote = MemoryRemote::default();
+        remote.store(GROUP_FILE, groups_json(vec![group("a", "工作", 100)]));
+        for index in 0..MAX_ATTEMPTS {
+            remote.interference.borrow_mut().push(groups_json(vec![group("a", "工作", 100 + index as i64)]));
+        }
+        let path = temp_data_file("busy", vec![group("b", "本机", 200)]);
+
+        assert!(sync_store::<GroupData>(&remote, &path, GROUP_FILE, 0, &HashSet::new()).is_err());
+        assert_eq!(remote.puts.get(), MAX_ATTEMPTS);
+    }
+
+    #[test]
+    fn refuses_basic_auth_over_plain_http() {
+        assert!(WebDavRemote::new("http://example.com/dav", "user", "secret").is_err());
+        assert!(WebDavRemote::new("http://example.com/dav", "", "").is_ok());
+        assert!(WebDavRemote::new("http://localhost:8080/dav", "user", "secret").is_ok());
+        assert!(WebDavRemote::new("http://127.0.0.1/dav", "user", "secret").is_ok());
+        assert!(WebDavRemote::new("https://example.com/dav", "user", "secret").is_ok());
+    }
+
+    #[test]
+    fn keeps_local_only_deletions_out_of_the_remote() {
+        let remote = MemoryRemote::default();
+        remote.store(GROUP_FILE, groups_json(vec![group("a", "工作", 100), group("b", "生活", 100)]));
+        let path = temp_data_file("local-only", vec![group("a", "工作", 100)]);
+        let mut local = read_local(&path);
+        local.tombstones.push(Tombstone { id: "b".to_string(), deleted_at: 200 });
+        fs::write(&path, serde_json::to_string(&local).unwrap()).unwrap();
+        let local_only = HashSet::from(["b".to_string()]);
+
+        let (_, local_changed) = sync_store::<GroupData>(&remote, &path, GROUP_FILE, 150, &local_only).unwrap();
+        assert!(!local_changed);
+        assert_eq!(remote.puts.get(), 0);
+        assert_eq!(names(&remote.groups(GROUP_FILE)), [("a", "工作"), ("b", "生活")]);
+        let local = read_local(&path);
+        assert_eq!(names(&local), [("a", "工作")]);
+        assert_eq!(local.tombstones.len(), 1);
+
+        // 没有排除时删除记录会同步到远端
+        sync_store::<GroupData>(&remote, &path, GROUP_FILE, 150, &HashSet::new()).unwrap();
+        assert_eq!(names(&remote.groups(GROUP_FILE)), [("a", "工作")]);
+    }
+}
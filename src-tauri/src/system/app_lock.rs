@@ -0,0 +1,181 @@
+use std::sync::atomic::{AtomicBool, AtomicI64, AtomicU32, Ordering};
+use std::time::Duration;
+use argon2::password_hash::rand_core::OsRng;
+use argon2::password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString};
+use argon2::Argon2;
+use tauri::Emitter;
+
+use crate::data::app_settings::write_app_settings;
+use crate::models::AppLockStatus;
+
+// 空闲检查间隔
+const IDLE_CHECK_INTERVAL: Duration = Duration::from_secs(15);
+
+// 连续输错PIN达到该次数后，暂停一段时间才允许再次尝试
+const MAX_FAILED_ATTEMPTS: u32 = 5;
+const RETRY_DELAY_SECS: i64 = 30;
+
+static LOCKED: AtomicBool = AtomicBool::new(false);
+static LAST_ACTIVITY: AtomicI64 = AtomicI64::new(0);
+static FAILED_ATTEMPTS: AtomicU32 = AtomicU32::new(0);
+static RETRY_AFTER: AtomicI64 = AtomicI64::new(0);
+
+fn now() -> i64 {
+    chrono::Utc::now().timestamp()
+}
+
+// 记录用户活动，重新开始空闲计时
+fn touch() {
+    LAST_ACTIVITY.store(now(), Ordering::SeqCst);
+}
+
+// 检查应用是否已解锁，读取或修改任务数据的命令都需要先调用
+// 只做检查不重新计时：接口和命令行的调用不代表用户在电脑前，空闲计时只由前端报告的活动重置
+pub fn ensure_unlocked() -> Result<(), String> {
+    if LOCKED.load(Ordering::SeqCst) {
+        return Err("应用已锁定，请先输入PIN解锁".to_string());
+    }
+    Ok(())
+}
+
+// 应用是否处于锁定状态
+pub fn is_app_locked() -> bool {
+    LOCKED.load(Ordering::SeqCst)
+}
+
+// 锁定应用并通知前端
+fn lock(app: &tauri::AppHandle) {
+    if !LOCKED.swap(true, Ordering::SeqCst) {
+        log::info!("应用已锁定");
+        let _ = app.emit("app-locked", ());
+    }
+}
+
+fn hash_pin(pin: &str) -> Result<String, String> {
+    if pin.len() < 4 || pin.len() > 12 || !pin.chars().all(|c| c.is_ascii_digit()) {
+        return Err("PIN必须是4到12位数字".to_string());
+    }
+    let salt = SaltString::generate(&mut OsRng);
+    Argon2::default()
+        .hash_password(pin.as_bytes(), &salt)
+        .map(|hash| hash.to_string())
+        .map_err(|e| format!("生成PIN哈希失败: {}", e))
+}
+
+fn verify_pin(hash: &str, pin: &str) -> bool {
+    PasswordHash::new(hash)
+        .is_ok_and(|hash| Argon2::default().verify_password(pin.as_bytes(), &hash).is_ok())
+}
+
+// 校验PIN，连续输错多次后暂时拒绝尝试
+fn check_pin(hash: &str, pin: &str) -> Result<(), String> {
+    let retry_after = RETRY_AFTER.load(Ordering::SeqCst);
+    if now() < retry_after {
+        return Err(format!("PIN错误次数过多，请 {} 秒后再试", retry_after - now()));
+    }
+
+    if verify_pin(hash, pin) {
+        FAILED_ATTEMPTS.store(0, Ordering::SeqCst);
+        return Ok(());
+    }
+
+    if FAILED_ATTEMPTS.fetch_add(1, Ordering::SeqCst) + 1 >= MAX_FAILED_ATTEMPTS {
+        FAILED_ATTEMPTS.store(0, Ordering::SeqCst);
+        RETRY_AFTER.store(now() + RETRY_DELAY_SECS, Ordering::SeqCst);
+    }
+    Err("PIN错误".to_string())
+}
+
+// 初始化应用锁：设置了PIN时以锁定状态启动，并开始空闲检查
+pub fn init_app_lock(app: &tauri::AppHandle) {
+    let settings = tauri::async_runtime::block_on(crate::data::load_app_settings(app.clone()));
+    if settings.is_ok_and(|settings| !settings.app_lock_pin_hash.is_empty()) {
+        LOCKED.store(true, Ordering::SeqCst);
+    }
+    touch();
+
+    let app = app.clone();
+    std::thread::spawn(move || loop {
+        std::thread::sleep(IDLE_CHECK_INTERVAL);
+        if LOCKED.load(Ordering::SeqCst) {
+            continue;
+        }
+
+        let settings = match tauri::async_runtime::block_on(crate::data::load_app_settings(app.clone())) {
+            Ok(settings) => settings,
+            Err(_) => continue,
+        };
+        if settings.app_lock_pin_hash.is_empty() || settings.auto_lock_minutes == 0 {
+            continue;
+        }
+        if now() - LAST_ACTIVITY.load(Ordering::SeqCst) >= settings.auto_lock_minutes as i64 * 60 {
+            lock(&app);
+        }
+    });
+}
+
+// Tauri 命令：获取应用锁状态
+#[tauri::command]
+pub async fn get_app_lock_status(app: tauri::AppHandle) -> Result<AppLockStatus, String> {
+    let settings = crate::data::load_app_settings(app).await?;
+    Ok(AppLockStatus {
+        enabled: !settings.app_lock_pin_hash.is_empty(),
+        locked: LOCKED.load(Ordering::SeqCst),
+        auto_lock_minutes: settings.auto_lock_minutes,
+    })
+}
+
+// Tauri 命令：立即锁定应用
+#[tauri::command]
+pub async fn lock_app(app: tauri::AppHandle) -> Result<(), String> {
+    let settings = crate::data::load_app_settings(app.clone()).await?;
+    if settings.app_lock_pin_hash.is_empty() {
+        return Err("尚未设置PIN".to_string());
+    }
+    lock(&app);
+    Ok(())
+}
+
+// Tauri 命令：输入PIN解锁应用
+#[tauri::command]
+pub async fn unlock_app(app: tauri::AppHandle, pin: String) -> Result<(), String> {
+    let settings = crate::data::load_app_settings(app.clone()).await?;
+    if !settings.app_lock_pin_hash.is_empty() {
+        check_pin(&settings.app_lock_pin_hash, &pin)?;
+    }
+
+    LOCKED.store(false, Ordering::SeqCst);
+    touch();
+    let _ = app.emit("app-unlocked", ());
+    Ok(())
+}
+
+// Tauri 命令：设置、修改或移除PIN（new_pin 为空时移除），已设置PIN时需要提供当前PIN
+#[tauri::command]
+pub async fn set_app_lock_pin(
+    app: tauri::AppHandle,
+    current_pin: Option<String>,
+    new_pin: Option<String>,
+) -> Result<(), String> {
+    ensure_unlocked()?;
+    let mut settings = crate::data::load_app_settings(app.clone()).await?;
+    if !settings.app_lock_pin_hash.is_empty() {
+        check_pin(&settings.app_lock_pin_hash, current_pin.as_deref().unwrap_or_default())?;
+    }
+
+    settings.app_lock_pin_hash = match new_pin.as_deref() {
+        Some(pin) if !pin.is_empty() => hash_pin(pin)?,
+        _ => String::new(),
+    };
+    write_app_settings(&app, &settings)
+}
+
+// Tauri 命令：报告用户活动（前端在用户操作时调用），用于空闲自动锁定计时
+#[tauri::command]
+pub async fn report_user_activity() -> Result<(), String> {
+    if !LOCKED.load(Ordering::SeqCst) {
+        touch();
+    }
+    Ok(())
+}
+
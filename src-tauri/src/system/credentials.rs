@@ -0,0 +1,55 @@
+// 同步服务的密码保存在系统钥匙串中（Windows 凭据管理器、macOS 钥匙串、Linux Secret Service），
+// 设置文件里不保存明文密码
+
+// 钥匙串中的服务名
+const SERVICE: &str = "DeskHive";
+
+// 各同步服务在钥匙串中的账户名
+pub const WEBDAV_ACCOUNT: &str = "webdav";
+pub const CALDAV_ACCOUNT: &str = "caldav";
+
+fn entry(account: &str) -> Result<keyring::Entry, String> {
+    keyring::Entry::new(SERVICE, account)
+        .map_err(|e| format!("访问系统钥匙串失败: {}", e))
+}
+
+// 保存密码，密码为空时删除已保存的密码
+pub fn save_password(account: &str, password: &str) -> Result<(), String> {
+    let entry = entry(account)?;
+    if password.is_empty() {
+        return match entry.delete_credential() {
+            Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+            Err(e) => Err(format!("删除系统钥匙串中的密码失败: {}", e)),
+        };
+    }
+
+    entry.set_password(password)
+        .map_err(|e| format!("保存密码到系统钥匙串失败: {}", e))
+}
+
+// 读取密码，没有保存过时返回空字符串
+pub fn load_password(account: &str) -> Result<String, String> {
+    match entry(account)?.get_password() {
+        Ok(password) => Ok(password),
+        Err(keyring::Error::NoEntry) => Ok(String::new()),
+        Err(e) => Err(format!("读取系统钥匙串中的密码失败: {}", e)),
+    }
+}
+
+// 获取同步时使用的密码：旧版本设置文件中的明文密码尚未迁移时直接使用，否则从钥匙串读取
+pub fn resolve_password(account: &str, legacy_password: &str) -> Result<String, String> {
+    if !legacy_password.is_empty() {
+        return Ok(legacy_password.to_string());
+    }
+    load_password(account)
+}
+
+// 将设置中的明文密码移入钥匙串，并从设置中清除
+pub fn move_password_to_keyring(account: &str, password: &mut String) -> Result<(), String> {
+    if password.is_empty() {
+        return Ok(());
+    }
+    save_password(account, password)?;
+    password.clear();
+    Ok(())
+}
@@ -1,14 +1,24 @@
-use chrono::{Local, Datelike};
+use chrono::{Local, Datelike, NaiveDate};
 
-use crate::models::DateInfo;
+use crate::data::holidays::{day_kind, DayKind};
+use crate::models::{DateInfo, UpcomingDay};
+use super::festivals::{festivals_on, next_festival};
+use super::lunar::LunarDate;
+use super::solar_terms::solar_terms_of_year;
 
-// 获取当前日期信息（公历和农历）
-pub fn get_current_date_info() -> DateInfo {
-    let now = Local::now();
-    
+fn upcoming_day(from: NaiveDate, date: NaiveDate, name: String) -> UpcomingDay {
+    UpcomingDay {
+        name,
+        date: date.format("%Y-%m-%d").to_string(),
+        days_until: (date - from).num_days(),
+    }
+}
+
+// 获取某一天的日期信息（公历和农历）
+pub fn get_date_info_for(date: NaiveDate) -> DateInfo {
     // 公历信息
-    let solar_date = now.format("%Y年%m月%d日").to_string();
-    let weekday = match now.weekday() {
+    let solar_date = date.format("%Y年%m月%d日").to_string();
+    let weekday = match date.weekday() {
         chrono::Weekday::Mon => "星期一".to_string(),
         chrono::Weekday::Tue => "星期二".to_string(),
         chrono::Weekday::Wed => "星期三".to_string(),
@@ -17,11 +27,36 @@ pub fn get_current_date_info() -> DateInfo {
         chrono::Weekday::Sat => "星期六".to_string(),
         chrono::Weekday::Sun => "星期日".to_string(),
     };
-    
-    // 简化版的农历信息（作为示例）
-    // 在实际应用中，这里可以集成真正的农历计算库
-    let (lunar_year, lunar_month, lunar_day, lunar_date) = get_sample_lunar_info(&now);
-    
+
+    // 农历信息，超出农历数据范围（1900-2100年）时留空
+    let lunar = LunarDate::from_solar(date);
+    let lunar_year = lunar.map(|lunar| lunar.year_name()).unwrap_or_default();
+    let lunar_month = lunar.map(|lunar| lunar.month_name()).unwrap_or_default();
+    let lunar_day = lunar.map(|lunar| lunar.day_name()).unwrap_or_default();
+    let lunar_date = format!("{}{}{}", lunar_year, lunar_month, lunar_day);
+
+    // 节气：当天的节气和之后最近的节气（可能在下一年）
+    let mut solar_terms = solar_terms_of_year(date.year());
+    solar_terms.extend(solar_terms_of_year(date.year() + 1));
+    let solar_term = solar_terms.iter()
+        .find(|(term_date, _)| *term_date == date)
+        .map(|(_, name)| name.to_string());
+    let next_solar_term = solar_terms.iter()
+        .find(|(term_date, _)| *term_date > date)
+        .map(|(term_date, name)| upcoming_day(date, *term_date, name.to_string()));
+
+    // 节日
+    let festivals = festivals_on(date).into_iter().map(String::from).collect();
+    let next_festival = next_festival(date)
+        .map(|(festival_date, names)| upcoming_day(date, festival_date, names.join("、")));
+
+    // 法定节假日和调休
+    let kind = day_kind(date);
+    let holiday_name = match &kind {
+        DayKind::Holiday(name) | DayKind::AdjustedWorkday(name) => Some(name.clone()),
+        DayKind::Workday | DayKind::Weekend => None,
+    };
+
     DateInfo {
         solar_date,
         lunar_date,
@@ -29,56 +64,34 @@ pub fn get_current_date_info() -> DateInfo {
         lunar_year,
         lunar_month,
         lunar_day,
+        zodiac: lunar.map(|lunar| lunar.zodiac().to_string()).unwrap_or_default(),
+        is_leap_month: lunar.is_some_and(|lunar| lunar.is_leap),
+        solar_term,
+        next_solar_term,
+        festivals,
+        next_festival,
+        is_workday: kind.is_workday(),
+        is_holiday: matches!(kind, DayKind::Holiday(_)),
+        is_adjusted_workday: matches!(kind, DayKind::AdjustedWorkday(_)),
+        holiday_name,
     }
 }
 
-// 获取示例农历信息（简化版）
-fn get_sample_lunar_info(date: &chrono::DateTime<chrono::Local>) -> (String, String, String, String) {
-    // 这里使用一个简化的映射，实际应用中可以更换为真正的农历计算
-    let _year = 2024; // 使用下划线前缀来避免警告
-    let month = date.month();
-    let day = date.day();
-    
-    // 根据月份和日期生成一个简单的农历示例
-    let lunar_year_str = "甲辰年".to_string();
-    let lunar_month_str = match month {
-        1..=3 => "正月".to_string(),
-        4..=6 => "四月".to_string(),
-        7..=9 => "七月".to_string(),
-        _ => "十月".to_string(),
-    };
-    
-    let lunar_day_str = match day {
-        1..=10 => format!("初{}", get_chinese_number(day as i32)),
-        11..=20 => format!("十{}", if day == 10 { "".to_string() } else { get_chinese_number((day - 10) as i32) }),
-        21..=31 => format!("廿{}", get_chinese_number((day - 20) as i32)),
-        _ => format!("{}日", day),
-    };
-    
-    let full_lunar_date = format!("{}{}{}", lunar_year_str, lunar_month_str, lunar_day_str);
-    
-    (lunar_year_str, lunar_month_str, lunar_day_str, full_lunar_date)
-}
-
-// 获取中文数字
-fn get_chinese_number(num: i32) -> String {
-    match num {
-        1 => "一".to_string(),
-        2 => "二".to_string(),
-        3 => "三".to_string(),
-        4 => "四".to_string(),
-        5 => "五".to_string(),
-        6 => "六".to_string(),
-        7 => "七".to_string(),
-        8 => "八".to_string(),
-        9 => "九".to_string(),
-        10 => "十".to_string(),
-        _ => num.to_string(),
-    }
+// 获取当前日期信息（公历和农历）
+pub fn get_current_date_info() -> DateInfo {
+    get_date_info_for(Local::now().date_naive())
 }
 
 // Tauri 命令：获取当前日期信息
 #[tauri::command]
 pub async fn get_current_date() -> Result<DateInfo, String> {
     Ok(get_current_date_info())
-}
\ No newline at end of file
+}
+
+// Tauri 命令：获取指定日期（格式：2024-09-17）的日期信息
+#[tauri::command]
+pub async fn get_date_info(date: String) -> Result<DateInfo, String> {
+    let date = NaiveDate::parse_from_str(date.trim(), "%Y-%m-%d")
+        .map_err(|e| format!("无效的日期 {}，格式应为 YYYY-MM-DD: {}", date, e))?;
+    Ok(get_date_info_for(date))
+}
@@ -0,0 +1,205 @@
+use std::collections::HashMap;
+use std::sync::{Mutex, MutexGuard};
+use serde::Serialize;
+use tauri::Emitter;
+use tauri_plugin_deep_link::DeepLinkExt;
+use url::Url;
+
+use crate::data::groups::{parse_deadline, resolve_group};
+use crate::data::todo_data::{get_data_dir, read_group_store, read_todo_store};
+use crate::models::Todo;
+use crate::system::app_lock::ensure_unlocked;
+use crate::window::management::show_main_window;
+
+// 应用注册的链接协议
+pub const SCHEME: &str = "deskhive";
+
+// 链接中任务内容的最大长度（字符数）
+const MAX_TEXT_LEN: usize = 500;
+
+// 等待确认的新建请求的有效期（秒）
+const PENDING_ADD_TTL_SECS: i64 = 600;
+
+// 通过链接新建任务的请求，用户确认后才会新建
+#[derive(Serialize, Clone)]
+pub struct DeepLinkAddRequest {
+    pub request_id: String,
+    pub url: String,
+    pub text: String,
+    pub deadline: Option<i64>,
+    pub group_id: Option<String>,
+    pub group_name: Option<String>,
+    pub created_at: i64,
+}
+
+// 链接处理失败时通知前端
+#[derive(Serialize, Clone)]
+struct DeepLinkError {
+    url: String,
+    message: String,
+}
+
+// 链接对应的操作
+enum DeepLinkAction {
+    // deskhive://add?text=...&due=...&group=...
+    Add { text: String, due: Option<String>, group: Option<String> },
+    // deskhive://open?id=<uuid>
+    Open { todo_id: String },
+}
+
+static PENDING_ADDS: Mutex<Option<HashMap<String, DeepLinkAddRequest>>> = Mutex::new(None);
+
+// 取出等待确认的请求，同时清理过期的请求
+fn pending_adds() -> MutexGuard<'static, Option<HashMap<String, DeepLinkAddRequest>>> {
+    let mut pending = PENDING_ADDS.lock().unwrap_or_else(|e| e.into_inner());
+    let now = chrono::Utc::now().timestamp();
+    pending.get_or_insert_with(HashMap::new)
+        .retain(|_, request| now - request.created_at < PENDING_ADD_TTL_SECS);
+    pending
+}
+
+// 解析并校验链接，只接受已知的操作和参数
+fn parse_deep_link(url: &Url) -> Result<DeepLinkAction, String> {
+    if url.scheme() != SCHEME {
+        return Err(format!("不支持的链接协议: {}", url.scheme()));
+    }
+    if !matches!(url.path(), "" | "/") {
+        return Err(format!("无效的链接路径: {}", url.path()));
+    }
+
+    let mut params: HashMap<String, String> = HashMap::new();
+    for (key, value) in url.query_pairs() {
+        if params.insert(key.to_string(), value.to_string()).is_some() {
+            return Err(format!("链接参数 {} 重复", key));
+        }
+    }
+    let action = url.host_str().unwrap_or_default();
+    let allowed: &[&str] = match action {
+        "add" => &["text", "due", "group"],
+        "open" => &["id"],
+        _ => return Err(format!("未知的链接操作: {}", action)),
+    };
+    if let Some(key) = params.keys().find(|key| !allowed.contains(&key.as_str())) {
+        return Err(format!("未知的链接参数: {}", key));
+    }
+
+    match action {
+        "add" => {
+            let text = params.remove("text").unwrap_or_default().trim().to_string();
+            if text.is_empty() {
+                return Err("任务内容不能为空".to_string());
+            }
+            if text.chars().count() > MAX_TEXT_LEN {
+                return Err(format!("任务内容不能超过 {} 个字符", MAX_TEXT_LEN));
+            }
+            if text.chars().any(char::is_control) {
+                return Err("任务内容不能包含控制字符".to_string());
+            }
+            Ok(DeepLinkAction::Add {
+                text,
+                due: params.remove("due").filter(|due| !due.trim().is_empty()),
+                group: params.remove("group").filter(|group| !group.trim().is_empty()),
+            })
+        }
+        _ => {
+            let todo_id = params.remove("id").ok_or_else(|| "缺少任务ID".to_string())?;
+            uuid::Uuid::parse_str(&todo_id).map_err(|_| format!("无效的任务ID: {}", todo_id))?;
+            Ok(DeepLinkAction::Open { todo_id })
+        }
+    }
+}
+
+// 处理一个链接：新建任务先等待用户确认，打开任务时显示主窗口并定位到该任务
+fn handle_url(app: &tauri::AppHandle, url: &Url) -> Result<(), String> {
+    let action = parse_deep_link(url)?;
+    ensure_unlocked()?;
+    let data_dir = get_data_dir(app)?;
+
+    match action {
+        DeepLinkAction::Add { text, due, group } => {
+            let groups = read_group_store(&data_dir)?.groups;
+            let group_id = group.as_deref().map(|group| resolve_group(&groups, group)).transpose()?;
+            let group_name = group_id.as_ref()
+                .and_then(|id| groups.iter().find(|group| group.id == *id))
+                .map(|group| group.name.clone());
+            let deadline = due.as_deref()
+                .map(|due| parse_deadline(due, chrono::Local::now()))
+                .transpose()?;
+
+            let request = DeepLinkAddRequest {
+                request_id: uuid::Uuid::new_v4().to_string(),
+                url: url.to_string(),
+                text,
+                deadline,
+                group_id,
+                group_name,
+                created_at: chrono::Utc::now().timestamp(),
+            };
+            pending_adds().get_or_insert_with(HashMap::new)
+                .insert(request.request_id.clone(), request.clone());
+
+            tauri::async_runtime::spawn(show_main_window(app.clone()));
+            let _ = app.emit("deep-link-add-request", request);
+        }
+        DeepLinkAction::Open { todo_id } => {
+            if !read_todo_store(&data_dir)?.todos.iter().any(|todo| todo.id == todo_id) {
+                return Err(format!("未找到指定的todo项: id='{}'", todo_id));
+            }
+            tauri::async_runtime::spawn(show_main_window(app.clone()));
+            let _ = app.emit("focus-todo", todo_id);
+        }
+    }
+    Ok(())
+}
+
+fn handle_urls(app: &tauri::AppHandle, urls: Vec<Url>) {
+    for url in urls {
+        log::info!("打开链接: {}", url);
+        if let Err(e) = handle_url(app, &url) {
+            log::error!("处理链接 {} 失败: {}", url, e);
+            let _ = app.emit("deep-link-error", DeepLinkError { url: url.to_string(), message: e });
+        }
+    }
+}
+
+// 注册链接协议并处理启动时和运行期间打开的链接
+pub fn init_deep_link(app: &tauri::AppHandle) {
+    // 安装包会注册协议，这里确保未安装运行（例如开发时或 AppImage）时也能打开链接
+    #[cfg(any(target_os = "windows", target_os = "linux"))]
+    if let Err(e) = app.deep_link().register_all() {
+        log::error!("注册链接协议失败: {}", e);
+    }
+
+    let handle = app.clone();
+    app.deep_link().on_open_url(move |event| handle_urls(&handle, event.urls()));
+
+    match app.deep_link().get_current() {
+        Ok(Some(urls)) => handle_urls(app, urls),
+        Ok(None) => {}
+        Err(e) => log::error!("读取启动链接失败: {}", e),
+    }
+}
+
+// Tauri 命令：获取等待确认的链接新建请求，前端启动时调用，以免错过启动前打开的链接
+#[tauri::command]
+pub async fn get_pending_deep_link_adds() -> Result<Vec<DeepLinkAddRequest>, String> {
+    let pending = pending_adds();
+    let mut requests: Vec<DeepLinkAddRequest> = pending.iter().flat_map(|pending| pending.values().cloned()).collect();
+    requests.sort_by_key(|request| request.created_at);
+    Ok(requests)
+}
+
+// Tauri 命令：确认或拒绝通过链接新建任务，确认时返回新建的任务
+#[tauri::command]
+pub async fn confirm_deep_link_add(app: tauri::AppHandle, request_id: String, confirmed: bool) -> Result<Option<Todo>, String> {
+    let request = pending_adds().as_mut()
+        .and_then(|pending| pending.remove(&request_id))
+        .ok_or_else(|| "未找到待确认的新建请求，可能已过期".to_string())?;
+    if !confirmed {
+        return Ok(None);
+    }
+
+    crate::data::add_todo(app, request.text, request.group_id, None, request.deadline, None)
+        .await
+        .map(Some)
+}
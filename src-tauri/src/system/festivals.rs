@@ -0,0 +1,124 @@
+use chrono::{Datelike, Duration, NaiveDate, Weekday};
+
+use super::lunar::LunarDate;
+use super::solar_terms::solar_term_date;
+
+// 公历节日：月、日、名称
+const SOLAR_FESTIVALS: [(u32, u32, &str); 14] = [
+    (1, 1, "元旦"),
+    (2, 14, "情人节"),
+    (3, 8, "妇女节"),
+    (3, 12, "植树节"),
+    (4, 1, "愚人节"),
+    (5, 1, "劳动节"),
+    (5, 4, "青年节"),
+    (6, 1, "儿童节"),
+    (7, 1, "建党节"),
+    (8, 1, "建军节"),
+    (9, 10, "教师节"),
+    (10, 1, "国庆节"),
+    (12, 24, "平安夜"),
+    (12, 25, "圣诞节"),
+];
+
+// 按星期确定的公历节日：月、星期、当月第几个、名称
+const WEEKDAY_FESTIVALS: [(u32, Weekday, u8, &str); 2] = [
+    (5, Weekday::Sun, 2, "母亲节"),
+    (6, Weekday::Sun, 3, "父亲节"),
+];
+
+// 农历节日：月、日、名称（闰月不算）
+const LUNAR_FESTIVALS: [(u32, u32, &str); 10] = [
+    (1, 1, "春节"),
+    (1, 15, "元宵节"),
+    (2, 2, "龙抬头"),
+    (5, 5, "端午节"),
+    (7, 7, "七夕节"),
+    (7, 15, "中元节"),
+    (8, 15, "中秋节"),
+    (9, 9, "重阳节"),
+    (12, 8, "腊八节"),
+    (12, 23, "小年"),
+];
+
+// 清明节是节气，在节气表中的序号
+const QINGMING_INDEX: usize = 6;
+
+// 查找下一个节日时最多向后查找的天数（农历闰年最长384天）
+const MAX_SEARCH_DAYS: i64 = 400;
+
+// 某一天的全部节日，农历节日在前
+pub(crate) fn festivals_on(date: NaiveDate) -> Vec<&'static str> {
+    let mut festivals = Vec::new();
+
+    if let Some(lunar) = LunarDate::from_solar(date).filter(|lunar| !lunar.is_leap) {
+        festivals.extend(LUNAR_FESTIVALS.iter()
+            .filter(|(month, day, _)| *month == lunar.month && *day == lunar.day)
+            .map(|(_, _, name)| *name));
+    }
+    // 除夕是农历年的最后一天，腊月可能只有29天
+    let tomorrow = date.succ_opt().and_then(LunarDate::from_solar);
+    if tomorrow.is_some_and(|lunar| lunar.month == 1 && lunar.day == 1 && !lunar.is_leap) {
+        festivals.push("除夕");
+    }
+    if solar_term_date(date.year(), QINGMING_INDEX) == Some(date) {
+        festivals.push("清明节");
+    }
+
+    festivals.extend(SOLAR_FESTIVALS.iter()
+        .filter(|(month, day, _)| *month == date.month() && *day == date.day())
+        .map(|(_, _, name)| *name));
+    festivals.extend(WEEKDAY_FESTIVALS.iter()
+        .filter(|(month, weekday, nth, _)| {
+            *month == date.month() && *weekday == date.weekday() && (date.day() - 1) / 7 + 1 == *nth as u32
+        })
+        .map(|(_, _, _, name)| *name));
+
+    festivals
+}
+
+// 某一天之后（不含当天）最近的有节日的日期
+pub(crate) fn next_festival(date: NaiveDate) -> Option<(NaiveDate, Vec<&'static str>)> {
+    (1..=MAX_SEARCH_DAYS)
+        .filter_map(|days| date.checked_add_signed(Duration::days(days)))
+        .map(|date| (date, festivals_on(date)))
+        .find(|(_, festivals)| !festivals.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::system::lunar::month_days;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    #[test]
+    fn finds_lunar_and_solar_festivals() {
+        assert_eq!(festivals_on(date(2024, 2, 10)), ["春节"]);
+        assert_eq!(festivals_on(date(2024, 9, 17)), ["中秋节"]);
+        assert_eq!(festivals_on(date(2024, 4, 4)), ["清明节"]);
+        assert_eq!(festivals_on(date(2024, 10, 1)), ["国庆节"]);
+        assert_eq!(festivals_on(date(2024, 5, 12)), ["母亲节"]);
+        assert_eq!(festivals_on(date(2024, 6, 16)), ["父亲节"]);
+        assert!(festivals_on(date(2024, 6, 17)).is_empty());
+    }
+
+    #[test]
+    fn new_years_eve_is_the_last_day_of_a_short_twelfth_month() {
+        // 2024年腊月只有29天，除夕是腊月廿九
+        assert_eq!(month_days(2024, 12), 29);
+        assert_eq!(festivals_on(date(2025, 1, 28)), ["除夕"]);
+        assert_eq!(festivals_on(date(2025, 1, 29)), ["春节"]);
+        // 腊月有30天时除夕是腊月三十
+        assert_eq!(festivals_on(date(2024, 2, 9)), ["除夕"]);
+        assert!(festivals_on(date(2024, 2, 8)).is_empty());
+    }
+
+    #[test]
+    fn finds_the_next_festival() {
+        assert_eq!(next_festival(date(2025, 1, 27)), Some((date(2025, 1, 28), vec!["除夕"])));
+        assert_eq!(next_festival(date(2024, 12, 25)), Some((date(2025, 1, 1), vec!["元旦"])));
+    }
+}
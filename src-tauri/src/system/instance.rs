@@ -0,0 +1,48 @@
+use serde::Serialize;
+use tauri::Emitter;
+use tauri_plugin_deep_link::DeepLinkExt;
+
+use super::deep_link::SCHEME;
+use crate::cli;
+use crate::window::management;
+
+// 再次启动应用时转发给已运行实例的参数
+#[derive(Serialize, Clone)]
+pub struct SecondInstanceArgs {
+    pub args: Vec<String>,  // 启动参数（不含程序路径）
+    pub cwd: String,        // 启动时的工作目录
+}
+
+// 处理再次启动时转发的参数：执行其中的命令行命令或窗口操作，没有可执行的参数时显示主窗口
+// 例如 "deskhive add 买牛奶" 添加任务，"deskhive --settings" 打开设置窗口，"deskhive --toggle" 切换主窗口显示
+pub fn handle_second_instance(app: &tauri::AppHandle, argv: Vec<String>, cwd: String) {
+    // 打开 deskhive:// 链接时系统以链接为参数启动应用，交给链接处理
+    app.deep_link().handle_cli_arguments(argv.iter());
+
+    let args: Vec<String> = argv.into_iter().skip(1).collect();
+    log::info!("再次启动应用，参数: {:?}，工作目录: {}", args, cwd);
+    let _ = app.emit("second-instance", SecondInstanceArgs { args: args.clone(), cwd: cwd.clone() });
+    if args.first().is_some_and(|arg| arg.starts_with(&format!("{}:", SCHEME))) {
+        return;
+    }
+
+    // 未启用本地控制接口时命令行把命令转发过来，按命令行的方式执行
+    if cli::is_command(&args) {
+        let app = app.clone();
+        tauri::async_runtime::spawn_blocking(move || cli::run_forwarded(&app, &args, &cwd));
+        return;
+    }
+
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let result = match args.first().map(String::as_str) {
+            Some("--settings") => management::open_settings_window(app).await,
+            Some("--toggle") => management::toggle_main_window(app).await,
+            Some("--hide") => management::minimize_to_tray(app).await,
+            _ => management::show_main_window(app).await,
+        };
+        if let Err(e) = result {
+            log::error!("处理再次启动的参数失败: {}", e);
+        }
+    });
+}
@@ -0,0 +1,194 @@
+use chrono::NaiveDate;
+
+// 农历数据表起始年份（1900年1月31日为农历1900年正月初一）
+pub(crate) const FIRST_YEAR: i32 = 1900;
+pub(crate) const LAST_YEAR: i32 = 2100;
+
+// 1900-2100年的农历数据，每年一项：
+// 第0-3位：闰月月份，0表示当年没有闰月
+// 第4-15位：正月到十二月的大小，对应位为1表示大月（30天），0表示小月（29天），最高位为正月
+// 第16位：闰月的大小，1表示大月
+const LUNAR_INFO: [u32; 201] = [
+    0x04bd8, 0x04ae0, 0x0a570, 0x054d5, 0x0d260, 0x0d950, 0x16554, 0x056a0, 0x09ad0, 0x055d2, // 1900-1909
+    0x04ae0, 0x0a5b6, 0x0a4d0, 0x0d250, 0x1d255, 0x0b540, 0x0d6a0, 0x0ada2, 0x095b0, 0x14977, // 1910-1919
+    0x04970, 0x0a4b0, 0x0b4b5, 0x06a50, 0x06d40, 0x1ab54, 0x02b60, 0x09570, 0x052f2, 0x04970, // 1920-1929
+    0x06566, 0x0d4a0, 0x0ea50, 0x16a95, 0x05ad0, 0x02b60, 0x186e3, 0x092e0, 0x1c8d7, 0x0c950, // 1930-1939
+    0x0d4a0, 0x1d8a6, 0x0b550, 0x056a0, 0x1a5b4, 0x025d0, 0x092d0, 0x0d2b2, 0x0a950, 0x0b557, // 1940-1949
+    0x06ca0, 0x0b550, 0x15355, 0x04da0, 0x0a5b0, 0x14573, 0x052b0, 0x0a9a8, 0x0e950, 0x06aa0, // 1950-1959
+    0x0aea6, 0x0ab50, 0x04b60, 0x0aae4, 0x0a570, 0x05260, 0x0f263, 0x0d950, 0x05b57, 0x056a0, // 1960-1969
+    0x096d0, 0x04dd5, 0x04ad0, 0x0a4d0, 0x0d4d4, 0x0d250, 0x0d558, 0x0b540, 0x0b6a0, 0x195a6, // 1970-1979
+    0x095b0, 0x049b0, 0x0a974, 0x0a4b0, 0x0b27a, 0x06a50, 0x06d40, 0x0af46, 0x0ab60, 0x09570, // 1980-1989
+    0x04af5, 0x04970, 0x064b0, 0x074a3, 0x0ea50, 0x06b58, 0x05ac0, 0x0ab60, 0x096d5, 0x092e0, // 1990-1999
+    0x0c960, 0x0d954, 0x0d4a0, 0x0da50, 0x07552, 0x056a0, 0x0abb7, 0x025d0, 0x092d0, 0x0cab5, // 2000-2009
+    0x0a950, 0x0b4a0, 0x0baa4, 0x0ad50, 0x055d9, 0x04ba0, 0x0a5b0, 0x15176, 0x052b0, 0x0a930, // 2010-2019
+    0x07954, 0x06aa0, 0x0ad50, 0x05b52, 0x04b60, 0x0a6e6, 0x0a4e0, 0x0d260, 0x0ea65, 0x0d530, // 2020-2029
+    0x05aa0, 0x076a3, 0x096d0, 0x04afb, 0x04ad0, 0x0a4d0, 0x1d0b6, 0x0d250, 0x0d520, 0x0dd45, // 2030-2039
+    0x0b5a0, 0x056d0, 0x055b2, 0x049b0, 0x0a577, 0x0a4b0, 0x0aa50, 0x1b255, 0x06d20, 0x0ada0, // 2040-2049
+    0x14b63, 0x09370, 0x049f8, 0x04970, 0x064b0, 0x168a6, 0x0ea50, 0x06b20, 0x1a6c4, 0x0aae0, // 2050-2059
+    0x092e0, 0x0d2e3, 0x0c960, 0x0d557, 0x0d4a0, 0x0da50, 0x05d55, 0x056a0, 0x0a6d0, 0x055d4, // 2060-2069
+    0x052d0, 0x0a9b8, 0x0a950, 0x0b4a0, 0x0b6a6, 0x0ad50, 0x055a0, 0x0aba4, 0x0a5b0, 0x052b0, // 2070-2079
+    0x0b273, 0x06930, 0x07337, 0x06aa0, 0x0ad50, 0x14b55, 0x04b60, 0x0a570, 0x054e4, 0x0d160, // 2080-2089
+    0x0e968, 0x0d520, 0x0daa0, 0x16aa6, 0x056d0, 0x04ae0, 0x0a9d4, 0x0a2d0, 0x0d150, 0x0f252, // 2090-2099
+    0x0d520, // 2100
+];
+
+const HEAVENLY_STEMS: [&str; 10] = ["甲", "乙", "丙", "丁", "戊", "己", "庚", "辛", "壬", "癸"];
+const EARTHLY_BRANCHES: [&str; 12] = ["子", "丑", "寅", "卯", "辰", "巳", "午", "未", "申", "酉", "戌", "亥"];
+const ZODIAC_ANIMALS: [&str; 12] = ["鼠", "牛", "虎", "兔", "龙", "蛇", "马", "羊", "猴", "鸡", "狗", "猪"];
+const MONTH_NAMES: [&str; 12] = ["正", "二", "三", "四", "五", "六", "七", "八", "九", "十", "冬", "腊"];
+const DIGITS: [&str; 10] = ["", "一", "二", "三", "四", "五", "六", "七", "八", "九"];
+
+// 农历数据表的第一天
+fn first_day() -> NaiveDate {
+    NaiveDate::from_ymd_opt(FIRST_YEAR, 1, 31).expect("农历起始日期无效")
+}
+
+fn year_info(year: i32) -> u32 {
+    LUNAR_INFO[(year - FIRST_YEAR) as usize]
+}
+
+// 闰月月份，没有闰月时返回 0
+pub(crate) fn leap_month(year: i32) -> u32 {
+    year_info(year) & 0xf
+}
+
+// 闰月的天数，没有闰月时返回 0
+fn leap_month_days(year: i32) -> i64 {
+    match leap_month(year) {
+        0 => 0,
+        _ if year_info(year) & 0x10000 != 0 => 30,
+        _ => 29,
+    }
+}
+
+// 农历某月（非闰月）的天数
+pub(crate) fn month_days(year: i32, month: u32) -> i64 {
+    if year_info(year) & (0x10000 >> month) != 0 { 30 } else { 29 }
+}
+
+// 农历某年的总天数
+fn year_days(year: i32) -> i64 {
+    (1..=12).map(|month| month_days(year, month)).sum::<i64>() + leap_month_days(year)
+}
+
+// 农历日期
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct LunarDate {
+    pub year: i32,      // 农历年份（以正月初一为界）
+    pub month: u32,     // 月份，1-12
+    pub day: u32,       // 日，1-30
+    pub is_leap: bool,  // 是否为闰月
+}
+
+impl LunarDate {
+    // 公历转农历，超出 1900-2100 年范围时返回 None
+    pub fn from_solar(date: NaiveDate) -> Option<LunarDate> {
+        let mut offset = (date - first_day()).num_days();
+        if offset < 0 {
+            return None;
+        }
+
+        let mut year = FIRST_YEAR;
+        while offset >= year_days(year) {
+            offset -= year_days(year);
+            year += 1;
+            if year > LAST_YEAR {
+                return None;
+            }
+        }
+
+        // 闰月排在同名月份之后
+        let leap = leap_month(year);
+        for month in 1..=12 {
+            let days = month_days(year, month);
+            if offset < days {
+                return Some(LunarDate { year, month, day: offset as u32 + 1, is_leap: false });
+            }
+            offset -= days;
+
+            if month == leap {
+                let days = leap_month_days(year);
+                if offset < days {
+                    return Some(LunarDate { year, month, day: offset as u32 + 1, is_leap: true });
+                }
+                offset -= days;
+            }
+        }
+        None
+    }
+
+    // 干支纪年，如：甲辰年
+    pub fn year_name(&self) -> String {
+        let index = (self.year - 4).rem_euclid(60) as usize;
+        format!("{}{}年", HEAVENLY_STEMS[index % 10], EARTHLY_BRANCHES[index % 12])
+    }
+
+    // 生肖，如：龙
+    pub fn zodiac(&self) -> &'static str {
+        ZODIAC_ANIMALS[(self.year - 4).rem_euclid(12) as usize]
+    }
+
+    // 月份名称，如：正月、闰四月、冬月、腊月
+    pub fn month_name(&self) -> String {
+        let leap = if self.is_leap { "闰" } else { "" };
+        format!("{}{}月", leap, MONTH_NAMES[self.month as usize - 1])
+    }
+
+    // 日期名称，如：初一、初十、十五、二十、廿五、三十
+    pub fn day_name(&self) -> String {
+        match self.day {
+            10 => "初十".to_string(),
+            20 => "二十".to_string(),
+            30 => "三十".to_string(),
+            day => {
+                let prefix = match day / 10 {
+                    0 => "初",
+                    1 => "十",
+                    _ => "廿",
+                };
+                format!("{}{}", prefix, DIGITS[day as usize % 10])
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lunar(year: i32, month: u32, day: u32) -> Option<LunarDate> {
+        LunarDate::from_solar(NaiveDate::from_ymd_opt(year, month, day).unwrap())
+    }
+
+    // 干支年、月份和日期名称，如：甲辰年正月初一
+    fn name(year: i32, month: u32, day: u32) -> String {
+        let date = lunar(year, month, day).unwrap();
+        format!("{}{}{}", date.year_name(), date.month_name(), date.day_name())
+    }
+
+    #[test]
+    fn converts_known_dates() {
+        assert_eq!(name(2024, 2, 10), "甲辰年正月初一");
+        assert_eq!(lunar(2024, 2, 10).unwrap().zodiac(), "龙");
+        assert_eq!(name(2024, 2, 9), "癸卯年腊月三十");
+        assert_eq!(name(1900, 1, 31), "庚子年正月初一");
+    }
+
+    #[test]
+    fn converts_leap_months() {
+        assert_eq!(name(2023, 3, 22), "癸卯年闰二月初一");
+        assert_eq!(name(2023, 3, 21), "癸卯年二月三十");
+        assert_eq!(name(2020, 5, 23), "庚子年闰四月初一");
+        assert_eq!(name(2020, 6, 21), "庚子年五月初一");
+        assert_eq!(name(2033, 12, 22), "癸丑年闰冬月初一");
+        assert_eq!(lunar(2033, 12, 22), Some(LunarDate { year: 2033, month: 11, day: 1, is_leap: true }));
+    }
+
+    #[test]
+    fn dates_outside_the_table_have_no_lunar_date() {
+        assert_eq!(lunar(1900, 1, 30), None);
+        assert_eq!(name(2101, 1, 28), "庚申年腊月廿九");
+        assert_eq!(lunar(2101, 1, 29), None);
+        assert_eq!(lunar(2200, 1, 1), None);
+    }
+}
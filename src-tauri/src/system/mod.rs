@@ -1,6 +1,13 @@
 pub mod auto_start;
 pub mod tray;
 pub mod date_info;
+pub mod lunar;
+pub mod solar_terms;
+pub mod festivals;
+pub mod app_lock;
+pub mod instance;
+pub mod deep_link;
+pub mod credentials;
 
 // 移除未使用的导入，只保留实际使用的
 // pub use auto_start::*;
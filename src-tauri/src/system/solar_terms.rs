@@ -0,0 +1,94 @@
+use chrono::{Duration, NaiveDate};
+
+// 二十四节气，按公历一年中的先后顺序，从小寒（太阳黄经285°）开始，每个节气相差15°
+pub(crate) const SOLAR_TERM_NAMES: [&str; 24] = [
+    "小寒", "大寒", "立春", "雨水", "惊蛰", "春分",
+    "清明", "谷雨", "立夏", "小满", "芒种", "夏至",
+    "小暑", "大暑", "立秋", "处暑", "白露", "秋分",
+    "寒露", "霜降", "立冬", "小雪", "大雪", "冬至",
+];
+
+// 2000年1月1日0时的儒略日
+const J2000_MIDNIGHT: f64 = 2451544.5;
+
+// 回归年的天数
+const TROPICAL_YEAR: f64 = 365.2422;
+
+fn epoch() -> NaiveDate {
+    NaiveDate::from_ymd_opt(2000, 1, 1).expect("日期无效")
+}
+
+// 太阳视黄经（度），使用低精度公式，误差约0.01°，对应十几分钟
+fn sun_longitude(julian_day: f64) -> f64 {
+    let t = (julian_day - 2451545.0) / 36525.0;
+    let mean_longitude = 280.46646 + 36000.76983 * t + 0.0003032 * t * t;
+    let mean_anomaly = (357.52911 + 35999.05029 * t - 0.0001537 * t * t).to_radians();
+    let center = (1.914602 - 0.004817 * t - 0.000014 * t * t) * mean_anomaly.sin()
+        + (0.019993 - 0.000101 * t) * (2.0 * mean_anomaly).sin()
+        + 0.000289 * (3.0 * mean_anomaly).sin();
+    let omega = (125.04 - 1934.136 * t).to_radians();
+    (mean_longitude + center - 0.00569 - 0.00478 * omega.sin()).rem_euclid(360.0)
+}
+
+// 计算某年第 index 个节气的日期（北京时间）
+pub(crate) fn solar_term_date(year: i32, index: usize) -> Option<NaiveDate> {
+    let target = (285.0 + 15.0 * index as f64).rem_euclid(360.0);
+
+    // 从节气的大致日期（每月6日或21日前后）开始逼近
+    let approx = NaiveDate::from_ymd_opt(year, index as u32 / 2 + 1, if index % 2 == 0 { 6 } else { 21 })?;
+    let mut julian_day = (approx - epoch()).num_days() as f64 + J2000_MIDNIGHT;
+    for _ in 0..6 {
+        let diff = (target - sun_longitude(julian_day) + 540.0).rem_euclid(360.0) - 180.0;
+        julian_day += diff * TROPICAL_YEAR / 360.0;
+    }
+
+    // 换算为北京时间（UTC+8）所在的日期
+    let days = julian_day - J2000_MIDNIGHT + 8.0 / 24.0;
+    epoch().checked_add_signed(Duration::days(days.floor() as i64))
+}
+
+// 某年全部节气的日期和名称，按日期排列
+pub(crate) fn solar_terms_of_year(year: i32) -> Vec<(NaiveDate, &'static str)> {
+    SOLAR_TERM_NAMES.iter().enumerate()
+        .filter_map(|(index, name)| solar_term_date(year, index).map(|date| (date, *name)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Datelike;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    fn term_date(year: i32, name: &str) -> Option<NaiveDate> {
+        solar_terms_of_year(year).into_iter().find(|(_, term)| *term == name).map(|(date, _)| date)
+    }
+
+    #[test]
+    fn computes_known_solar_terms() {
+        assert_eq!(term_date(2024, "小寒"), Some(date(2024, 1, 6)));
+        assert_eq!(term_date(2024, "立春"), Some(date(2024, 2, 4)));
+        assert_eq!(term_date(2024, "清明"), Some(date(2024, 4, 4)));
+        assert_eq!(term_date(2024, "夏至"), Some(date(2024, 6, 21)));
+        assert_eq!(term_date(2024, "冬至"), Some(date(2024, 12, 21)));
+    }
+
+    #[test]
+    fn uses_the_beijing_date_near_midnight() {
+        // 2025年立春在北京时间2月3日22时10分，UTC 为同一天14时
+        assert_eq!(term_date(2025, "立春"), Some(date(2025, 2, 3)));
+        // 2023年冬至在北京时间12月22日11时27分，UTC 为同一天3时
+        assert_eq!(term_date(2023, "冬至"), Some(date(2023, 12, 22)));
+    }
+
+    #[test]
+    fn lists_all_terms_in_date_order() {
+        let terms = solar_terms_of_year(2024);
+        assert_eq!(terms.len(), 24);
+        assert!(terms.windows(2).all(|pair| pair[0].0 < pair[1].0));
+        assert!(terms.iter().all(|(date, _)| date.year() == 2024));
+    }
+}
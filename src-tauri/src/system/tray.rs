@@ -1,19 +1,99 @@
-use tauri::{menu::{MenuBuilder, MenuItemBuilder}, tray::{TrayIconBuilder, TrayIconEvent}};
+use tauri::{
+    image::Image,
+    menu::{IconMenuItemBuilder, Menu, MenuBuilder, MenuItemBuilder, Submenu, SubmenuBuilder},
+    tray::{TrayIconBuilder, TrayIconEvent},
+    Emitter, Listener,
+};
 
+use crate::data::groups::parse_color;
+use crate::data::todo_data::{get_data_dir, read_group_store};
+use crate::models::TodoGroup;
 use crate::window::management::{open_settings_window, show_main_window};
 use crate::quit_app;
+use super::app_lock::{is_app_locked, lock_app};
 
-// 创建系统托盘菜单和事件处理
-pub fn create_tray(app: &tauri::App) -> Result<(), Box<dyn std::error::Error>> {
-    // 创建系统托盘菜单
+// 分组菜单项ID的前缀
+const GROUP_ITEM_PREFIX: &str = "group:";
+
+// 分组颜色色块的边长（像素）
+const SWATCH_SIZE: u32 = 16;
+
+// 生成分组颜色的色块图标
+fn color_swatch((r, g, b): (u8, u8, u8)) -> Image<'static> {
+    let rgba = [r, g, b, 255].repeat((SWATCH_SIZE * SWATCH_SIZE) as usize);
+    Image::new_owned(rgba, SWATCH_SIZE, SWATCH_SIZE)
+}
+
+// 按层级顺序排列分组，返回 (分组, 层级深度)
+fn ordered_groups(groups: &[TodoGroup]) -> Vec<(&TodoGroup, usize)> {
+    fn push_children<'a>(groups: &'a [TodoGroup], parent_id: Option<&str>, depth: usize, result: &mut Vec<(&'a TodoGroup, usize)>) {
+        let mut children: Vec<&TodoGroup> = groups.iter()
+            .filter(|group| group.parent_id.as_deref() == parent_id)
+            .collect();
+        children.sort_by_key(|group| group.order);
+        for child in children {
+            result.push((child, depth));
+            push_children(groups, Some(&child.id), depth + 1, result);
+        }
+    }
+
+    let mut result = Vec::new();
+    push_children(groups, None, 0, &mut result);
+    result
+}
+
+// 分组子菜单，显示分组颜色和图标；应用锁定或数据无法读取时不显示
+fn group_submenu(app: &tauri::AppHandle) -> tauri::Result<Option<Submenu<tauri::Wry>>> {
+    if is_app_locked() {
+        return Ok(None);
+    }
+    let groups = match get_data_dir(app).and_then(|data_dir| read_group_store(&data_dir)) {
+        Ok(group_data) => group_data.groups,
+        Err(_) => return Ok(None),
+    };
+
+    let mut submenu = SubmenuBuilder::new(app, "分组");
+    for (group, depth) in ordered_groups(&groups) {
+        let id = format!("{}{}", GROUP_ITEM_PREFIX, group.id);
+        let icon = group.icon.as_ref().map(|icon| format!("{} ", icon)).unwrap_or_default();
+        let text = format!("{}{}{}", "    ".repeat(depth), icon, group.name);
+        submenu = match group.color.as_deref().and_then(parse_color) {
+            Some(rgb) => submenu.item(&IconMenuItemBuilder::with_id(id, text).icon(color_swatch(rgb)).build(app)?),
+            None => submenu.item(&MenuItemBuilder::with_id(id, text).build(app)?),
+        };
+    }
+    Ok(Some(submenu.build()?))
+}
+
+// 构建托盘菜单
+fn build_menu(app: &tauri::AppHandle) -> tauri::Result<Menu<tauri::Wry>> {
     let show = MenuItemBuilder::with_id("show", "显示").build(app)?;
     let reset_position = MenuItemBuilder::with_id("reset_position", "重置窗口位置").build(app)?;
     let settings = MenuItemBuilder::with_id("settings", "设置").build(app)?;
+    let lock = MenuItemBuilder::with_id("lock", "锁定").build(app)?;
     let quit = MenuItemBuilder::with_id("quit", "退出").build(app)?;
-    
-    let menu = MenuBuilder::new(app)
-        .items(&[&show, &reset_position, &settings, &quit])
-        .build()?;
+
+    let mut menu = MenuBuilder::new(app).item(&show);
+    if let Some(groups) = group_submenu(app)? {
+        menu = menu.item(&groups);
+    }
+    menu.items(&[&reset_position, &settings, &lock, &quit]).build()
+}
+
+// 重新构建托盘菜单，分组变化或应用锁定、解锁后调用
+pub fn refresh_tray_menu(app: &tauri::AppHandle) {
+    let Some(tray) = app.tray_by_id("main") else {
+        return;
+    };
+    if let Err(e) = build_menu(app).and_then(|menu| tray.set_menu(Some(menu))) {
+        log::error!("更新托盘菜单失败: {}", e);
+    }
+}
+
+// 创建系统托盘菜单和事件处理
+pub fn create_tray(app: &tauri::App) -> Result<(), Box<dyn std::error::Error>> {
+    // 创建系统托盘菜单
+    let menu = build_menu(app.handle())?;
 
     // 创建系统托盘图标
     let _tray = TrayIconBuilder::with_id("main")
@@ -21,15 +101,12 @@ pub fn create_tray(app: &tauri::App) -> Result<(), Box<dyn std::error::Error>> {
         .tooltip("Todo 桌面助手")
         .icon(app.default_window_icon().unwrap().clone())
         .on_tray_icon_event(|tray, event| {
-            match event {
-                TrayIconEvent::Click { button: tauri::tray::MouseButton::Left, .. } => {
-                    // 左键单击只显示窗口
-                    let app = tray.app_handle().clone();
-                    tauri::async_runtime::spawn(async move {
-                        let _ = show_main_window(app).await;
-                    });
-                }
-                _ => {}
+            if let TrayIconEvent::Click { button: tauri::tray::MouseButton::Left, .. } = event {
+                // 左键单击只显示窗口
+                let app = tray.app_handle().clone();
+                tauri::async_runtime::spawn(async move {
+                    let _ = show_main_window(app).await;
+                });
             }
         })
         .on_menu_event(|app, event| {
@@ -53,16 +130,39 @@ pub fn create_tray(app: &tauri::App) -> Result<(), Box<dyn std::error::Error>> {
                         let _ = open_settings_window(app_handle).await;
                     });
                 }
+                "lock" => {
+                    let app_handle = app.clone();
+                    tauri::async_runtime::spawn(async move {
+                        if let Err(e) = lock_app(app_handle).await {
+                            log::error!("锁定应用失败: {}", e);
+                        }
+                    });
+                }
                 "quit" => {
                     let app_handle = app.clone();
                     tauri::async_runtime::spawn(async move {
                         let _ = quit_app(app_handle).await;
                     });
                 }
+                id if id.starts_with(GROUP_ITEM_PREFIX) => {
+                    // 显示主窗口并定位到所选分组
+                    let group_id = id[GROUP_ITEM_PREFIX.len()..].to_string();
+                    let app_handle = app.clone();
+                    tauri::async_runtime::spawn(async move {
+                        let _ = show_main_window(app_handle.clone()).await;
+                        let _ = app_handle.emit("focus-group", group_id);
+                    });
+                }
                 _ => {}
             }
         })
         .build(app)?;
 
+    // 分组变化或应用锁定、解锁后更新分组菜单
+    for event in ["group-data-changed", "data-unlocked", "app-locked", "app-unlocked"] {
+        let app_handle = app.handle().clone();
+        app.listen(event, move |_| refresh_tray_menu(&app_handle));
+    }
+
     Ok(())
 }
\ No newline at end of file
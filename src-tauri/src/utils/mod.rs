@@ -1 +1,2 @@
-// 工具模块
\ No newline at end of file
+// 工具模块
+pub mod task_parser;
@@ -0,0 +1,428 @@
+use std::sync::OnceLock;
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDate, NaiveTime, TimeDelta, TimeZone};
+use regex::{Captures, Regex};
+
+use crate::data::groups::resolve_group;
+use crate::models::TodoGroup;
+
+// 从任务文字中识别出的信息，识别出的部分已从 text 中去掉
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct ParsedTask {
+    pub text: String,
+    pub deadline: Option<i64>,
+    pub priority: Option<i32>,
+    pub group_id: Option<String>,
+    pub tags: Vec<String>,
+}
+
+// 中文日期：今天、明天、后天、周五、下周一、9月1日、15号、3天后、2小时后
+const DATE_CN: &str = r"(?P<rel>大后天|后天|明天|明日|今天|今日|今晚|明晚)|(?P<week>下下|下|本|这)?(?:周|星期|礼拜)(?P<wd>[一二三四五六日天1-7])|(?P<month>\d{1,2}|[一二三四五六七八九十]{1,3})月(?P<mday>\d{1,2}|[一二三四五六七八九十]{1,3})[日号]|(?P<day>\d{1,2})[日号]|(?P<n>\d+|[一二两三四五六七八九十]{1,3})(?P<unit>天|周|个星期|星期|个小时|小时|分钟)(?:后|以后|之后)";
+
+// 英文日期：today、tomorrow、fri、next monday、2024-09-01、9/1、in 3 days、in 2 hours
+const DATE_EN: &str = r"(?i)\b(?:(?P<prefix>due|by|on)\s+)?(?:(?P<rel>today|tonight|tomorrow|tmrw|tmr)|(?:(?P<week>next|this)\s+)?(?P<wd>monday|mon|tuesday|tues|tue|wednesday|wed|thursday|thurs|thur|thu|friday|fri|saturday|sat|sunday|sun)|(?P<y>\d{4})-(?P<ym>\d{1,2})-(?P<yd>\d{1,2})|(?P<md_m>\d{1,2})/(?P<md_d>\d{1,2})|in\s+(?P<n>\d+)\s*(?P<unit>days?|d|weeks?|w|hours?|hrs?|h|minutes?|mins?|m))\b";
+
+// 同时是常见单词的星期缩写（如 "sun cream"、"sat exam"），前面没有 on、next 等词时不作为日期
+const AMBIGUOUS_WEEKDAYS: [&str; 3] = ["sun", "sat", "wed"];
+
+// 中文时间：下午三点、早上8点半、晚上九点一刻、10点30分
+const TIME_CN: &str = r"(?P<period>上午|早上|早晨|中午|下午|晚上|傍晚|凌晨)?(?P<hour>\d{1,2}|[零一二两三四五六七八九十]{1,3})[点點时](?:(?P<half>半)|(?P<quarter>[一三])刻|(?P<minute>\d{1,2}|[零一二三四五六七八九十]{1,3})分?)?";
+
+// 数字时间：17:00、下午3:30、5:30pm
+const TIME_COLON: &str = r"(?i)(?:\bat\s+)?(?P<period>上午|早上|早晨|中午|下午|晚上|傍晚|凌晨)?(?P<hour>\d{1,2})[:：](?P<minute>\d{2})(?:\s*(?P<ampm>am|pm)\b)?";
+
+// 英文时间：5pm、11am、at 9am、noon、eod
+const TIME_EN: &str = r"(?i)(?:\bat\s+)?\b(?:(?P<hour>\d{1,2})(?::(?P<minute>\d{2}))?\s*(?P<ampm>am|pm)|(?P<word>noon|midnight|eod))\b";
+
+// 标签（#分组名 或 #标签）和优先级（! !! !!! 或 p0~p4），需要单独成词
+const TAG: &str = r"(?:^|\s)[#＃](?P<tag>[^\s#＃]+)";
+const PRIORITY: &str = r"(?i)(?:^|\s)(?:(?P<bang>[!！]{1,3})|p(?P<level>[0-4]))(?:\s|$)";
+
+fn regex(cell: &'static OnceLock<Regex>, pattern: &str) -> &'static Regex {
+    cell.get_or_init(|| Regex::new(pattern).expect("任务解析的正则表达式无效"))
+}
+
+macro_rules! pattern {
+    ($pattern:expr) => {{
+        static CELL: OnceLock<Regex> = OnceLock::new();
+        regex(&CELL, $pattern)
+    }};
+}
+
+// 中文或阿拉伯数字，支持 0~99（如 "十"、"十二"、"二十三"、"两"）
+fn parse_number(text: &str) -> Option<u32> {
+    if let Ok(number) = text.parse() {
+        return Some(number);
+    }
+    let digit = |c: char| "零一二三四五六七八九".chars().position(|d| d == c).map(|d| d as u32)
+        .or_else(|| (c == '两').then_some(2));
+    let chars: Vec<char> = text.chars().collect();
+    match chars.as_slice() {
+        [c] if *c == '十' => Some(10),
+        [c] => digit(*c),
+        ['十', ones] => Some(10 + digit(*ones)?),
+        [tens, '十'] => Some(digit(*tens)? * 10),
+        [tens, '十', ones] => Some(digit(*tens)? * 10 + digit(*ones)?),
+        _ => None,
+    }
+}
+
+// 一周中的第几天（周一为 0）
+fn weekday_index(name: &str) -> Option<u32> {
+    let name = name.to_lowercase();
+    let index = match name.as_str() {
+        "一" | "1" => 0,
+        "二" | "2" => 1,
+        "三" | "3" => 2,
+        "四" | "4" => 3,
+        "五" | "5" => 4,
+        "六" | "6" => 5,
+        "日" | "天" | "7" => 6,
+        _ => ["mon", "tue", "wed", "thu", "fri", "sat", "sun"].iter().position(|day| name.starts_with(day))? as u32,
+    };
+    Some(index)
+}
+
+// 星期几对应的日期：未指定哪一周时取今天或之后最近的一天，"下周" 按周一开始的下一周计算
+fn weekday_date(today: NaiveDate, weekday: u32, week: Option<&str>) -> NaiveDate {
+    let current = today.weekday().num_days_from_monday();
+    let monday = today - Duration::days(current as i64);
+    let weeks = match week.map(str::to_lowercase).as_deref() {
+        Some("下" | "next") => 1,
+        Some("下下") => 2,
+        Some("本" | "这" | "this") => 0,
+        _ if weekday >= current => 0,
+        _ => 1,
+    };
+    monday + Duration::days(weeks * 7 + weekday as i64)
+}
+
+// 只有月日的日期：今年的这一天已过时取明年
+fn month_day_date(today: NaiveDate, month: u32, day: u32) -> Option<NaiveDate> {
+    let date = NaiveDate::from_ymd_opt(today.year(), month, day)?;
+    if date < today {
+        return NaiveDate::from_ymd_opt(today.year() + 1, month, day);
+    }
+    Some(date)
+}
+
+// 只有日的日期：本月的这一天已过时取下个月
+fn month_date(today: NaiveDate, day: u32) -> Option<NaiveDate> {
+    let date = NaiveDate::from_ymd_opt(today.year(), today.month(), day);
+    if date.is_some_and(|date| date >= today) {
+        return date;
+    }
+    let (year, month) = if today.month() == 12 { (today.year() + 1, 1) } else { (today.year(), today.month() + 1) };
+    NaiveDate::from_ymd_opt(year, month, day)
+}
+
+// 识别出的日期
+enum DateMatch {
+    // 某一天，evening 表示 "今晚"、"tonight" 这类默认在晚上的说法
+    Day { date: NaiveDate, evening: bool },
+    // 相对现在的时间，如 "2小时后"，已经包含具体时间
+    Instant(DateTime<Local>),
+}
+
+fn relative_date(n: u32, unit: &str, now: DateTime<Local>) -> Option<DateMatch> {
+    // 超出可表示范围（如 "99999999天后"）时不作为日期
+    let n = n as i64;
+    let unit = unit.to_lowercase();
+    let date = |days: Option<i64>| {
+        let date = now.date_naive().checked_add_signed(TimeDelta::try_days(days?)?)?;
+        Some(DateMatch::Day { date, evening: false })
+    };
+    let instant = |delta: Option<TimeDelta>| now.checked_add_signed(delta?).map(DateMatch::Instant);
+    match unit.as_str() {
+        "天" | "d" | "day" | "days" => date(Some(n)),
+        "周" | "星期" | "个星期" | "w" | "week" | "weeks" => date(n.checked_mul(7)),
+        "小时" | "个小时" | "h" | "hr" | "hrs" | "hour" | "hours" => instant(TimeDelta::try_hours(n)),
+        "分钟" | "m" | "min" | "mins" | "minute" | "minutes" => instant(TimeDelta::try_minutes(n)),
+        _ => None,
+    }
+}
+
+fn date_from_captures(caps: &Captures, now: DateTime<Local>) -> Option<DateMatch> {
+    let today = now.date_naive();
+    let day = |days: i64, evening: bool| Some(DateMatch::Day { date: today + Duration::days(days), evening });
+
+    if let Some(rel) = caps.name("rel") {
+        return match rel.as_str().to_lowercase().as_str() {
+            "今天" | "今日" | "today" => day(0, false),
+            "今晚" | "tonight" => day(0, true),
+            "明天" | "明日" | "tomorrow" | "tmrw" | "tmr" => day(1, false),
+            "明晚" => day(1, true),
+            "后天" => day(2, false),
+            "大后天" => day(3, false),
+            _ => None,
+        };
+    }
+    if let Some(weekday) = caps.name("wd") {
+        let name = weekday.as_str().to_lowercase();
+        if AMBIGUOUS_WEEKDAYS.contains(&name.as_str()) && caps.name("week").is_none() && caps.name("prefix").is_none() {
+            return None;
+        }
+        let weekday = weekday_index(&name)?;
+        let date = weekday_date(today, weekday, caps.name("week").map(|week| week.as_str()));
+        return Some(DateMatch::Day { date, evening: false });
+    }
+    if let (Some(month), Some(mday)) = (caps.name("month"), caps.name("mday")) {
+        let date = month_day_date(today, parse_number(month.as_str())?, parse_number(mday.as_str())?)?;
+        return Some(DateMatch::Day { date, evening: false });
+    }
+    if let (Some(month), Some(mday)) = (caps.name("md_m"), caps.name("md_d")) {
+        let date = month_day_date(today, month.as_str().parse().ok()?, mday.as_str().parse().ok()?)?;
+        return Some(DateMatch::Day { date, evening: false });
+    }
+    if let (Some(year), Some(month), Some(mday)) = (caps.name("y"), caps.name("ym"), caps.name("yd")) {
+        let date = NaiveDate::from_ymd_opt(year.as_str().parse().ok()?, month.as_str().parse().ok()?, mday.as_str().parse().ok()?)?;
+        return Some(DateMatch::Day { date, evening: false });
+    }
+    if let Some(mday) = caps.name("day") {
+        let date = month_date(today, mday.as_str().parse().ok()?)?;
+        return Some(DateMatch::Day { date, evening: false });
+    }
+    if let (Some(n), Some(unit)) = (caps.name("n"), caps.name("unit")) {
+        return relative_date(parse_number(n.as_str())?, unit.as_str(), now);
+    }
+    None
+}
+
+// 按时段（下午、晚上、pm 等）换算成 24 小时制
+fn adjust_hour(hour: u32, period: Option<&str>) -> Option<u32> {
+    let hour = match period.map(str::to_lowercase).as_deref() {
+        Some("am") if (1..=12).contains(&hour) => hour % 12,
+        Some("pm") if (1..=12).contains(&hour) => hour % 12 + 12,
+        Some("am" | "pm") => return None,
+        Some("下午" | "晚上" | "傍晚") if hour < 12 => hour + 12,
+        Some("中午") if hour < 11 => hour + 12,
+        Some("上午" | "早上" | "早晨" | "凌晨") if hour == 12 => 0,
+        _ => hour,
+    };
+    (hour <= 23).then_some(hour)
+}
+
+// 识别出的时间（24 小时制），period_given 表示是否指明了上下午，未指明时 "今晚" 等说法按晚上计算
+struct TimeMatch {
+    hour: u32,
+    minute: u32,
+    period_given: bool,
+}
+
+impl TimeMatch {
+    fn time(&self, evening: bool) -> Option<NaiveTime> {
+        let hour = if evening && !self.period_given && self.hour < 12 { self.hour + 12 } else { self.hour };
+        NaiveTime::from_hms_opt(hour, self.minute, 0)
+    }
+}
+
+fn time_from_captures(caps: &Captures) -> Option<TimeMatch> {
+    if let Some(word) = caps.name("word") {
+        let (hour, minute) = match word.as_str().to_lowercase().as_str() {
+            "noon" => (12, 0),
+            _ => (23, 59),
+        };
+        return Some(TimeMatch { hour, minute, period_given: true });
+    }
+
+    let hour = parse_number(caps.name("hour")?.as_str())?;
+    let minute = if caps.name("half").is_some() {
+        30
+    } else if let Some(quarter) = caps.name("quarter") {
+        if quarter.as_str() == "一" { 15 } else { 45 }
+    } else {
+        caps.name("minute").map_or(Some(0), |minute| parse_number(minute.as_str()))?
+    };
+    if minute > 59 {
+        return None;
+    }
+    let period = caps.name("period").or_else(|| caps.name("ampm")).map(|period| period.as_str());
+    Some(TimeMatch {
+        hour: adjust_hour(hour, period)?,
+        minute,
+        period_given: period.is_some(),
+    })
+}
+
+// 在文字中查找第一个有效的匹配，把匹配到的部分替换为空格
+fn take_first<T>(text: &mut String, pattern: &Regex, parse: impl Fn(&Captures) -> Option<T>) -> Option<T> {
+    let (range, value) = pattern.captures_iter(text)
+        .filter(|caps| !caps[0].trim().is_empty())
+        .find_map(|caps| Some((caps.get(0)?.range(), parse(&caps)?)))?;
+    text.replace_range(range, " ");
+    Some(value)
+}
+
+fn local_timestamp(date: NaiveDate, time: NaiveTime) -> Option<i64> {
+    Local.from_local_datetime(&date.and_time(time)).earliest().map(|datetime| datetime.timestamp())
+}
+
+// 根据识别出的日期和时间计算截止时间：
+// - 只有日期时截止到当天结束
+// - 只有时间时为今天的这个时间，已经过了则为明天
+// - "今晚" 加上没有指明上下午的时间（如 "今晚8点"）按晚上计算
+fn resolve_deadline(date: Option<DateMatch>, time: Option<TimeMatch>, now: DateTime<Local>) -> Option<i64> {
+    let end_of_day = NaiveTime::from_hms_opt(23, 59, 0)?;
+    match (date, time) {
+        (Some(DateMatch::Instant(instant)), _) => Some(instant.timestamp()),
+        (Some(DateMatch::Day { date, evening }), Some(time)) => local_timestamp(date, time.time(evening)?),
+        (Some(DateMatch::Day { date, .. }), None) => local_timestamp(date, end_of_day),
+        (None, Some(time)) => {
+            let time = time.time(false)?;
+            let today = local_timestamp(now.date_naive(), time)?;
+            if today > now.timestamp() {
+                Some(today)
+            } else {
+                local_timestamp(now.date_naive() + Duration::days(1), time)
+            }
+        }
+        (None, None) => None,
+    }
+}
+
+// 解析任务文字中的截止时间、优先级、分组和标签，支持中文和英文：
+// "明天下午三点 交报告 !! #工作"、"report to boss fri 5pm p1"
+// "#名称" 与已有分组的名称或ID相同时作为分组（只取第一个），否则作为标签
+pub fn parse_task(input: &str, now: DateTime<Local>, groups: &[TodoGroup]) -> ParsedTask {
+    let mut text = input.to_string();
+    let mut parsed = ParsedTask::default();
+
+    // 标签和分组
+    let tags: Vec<String> = pattern!(TAG).captures_iter(&text)
+        .map(|caps| caps["tag"].to_string())
+        .collect();
+    text = pattern!(TAG).replace_all(&text, " ").to_string();
+    for tag in tags {
+        match resolve_group(groups, &tag) {
+            Ok(group_id) if parsed.group_id.is_none() => parsed.group_id = Some(group_id),
+            _ if !parsed.tags.contains(&tag) => parsed.tags.push(tag),
+            _ => {}
+        }
+    }
+
+    // 优先级：! 或 p0、p1 表示重要
+    while let Some(priority) = take_first(&mut text, pattern!(PRIORITY), |caps| {
+        // p0、p1 为重要，p2 及以下为普通
+        let level: u32 = caps.name("level").map_or(Some(0), |level| level.as_str().parse().ok())?;
+        Some(if level <= 1 { 1 } else { 0 })
+    }) {
+        parsed.priority = Some(parsed.priority.unwrap_or(0).max(priority));
+    }
+
+    // 日期和时间：中文优先，各取第一个
+    let date = take_first(&mut text, pattern!(DATE_CN), |caps| date_from_captures(caps, now))
+        .or_else(|| take_first(&mut text, pattern!(DATE_EN), |caps| date_from_captures(caps, now)));
+    let time = take_first(&mut text, pattern!(TIME_COLON), time_from_captures)
+        .or_else(|| take_first(&mut text, pattern!(TIME_CN), time_from_captures))
+        .or_else(|| take_first(&mut text, pattern!(TIME_EN), time_from_captures));
+    parsed.deadline = resolve_deadline(date, time, now);
+
+    parsed.text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    if parsed.text.is_empty() {
+        // 全部是日期、标签等信息时保留原文，避免得到空任务
+        parsed.text = input.trim().to_string();
+    }
+    parsed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 2024-09-04 是星期三
+    fn now() -> DateTime<Local> {
+        Local.with_ymd_and_hms(2024, 9, 4, 10, 0, 0).unwrap()
+    }
+
+    fn at(month: u32, day: u32, hour: u32, minute: u32) -> Option<i64> {
+        Some(Local.with_ymd_and_hms(2024, month, day, hour, minute, 0).unwrap().timestamp())
+    }
+
+    fn groups() -> Vec<TodoGroup> {
+        vec![TodoGroup { id: "work".to_string(), name: "工作".to_string(), ..Default::default() }]
+    }
+
+    fn parse(input: &str) -> ParsedTask {
+        parse_task(input, now(), &groups())
+    }
+
+    #[test]
+    fn parses_request_examples() {
+        let parsed = parse("明天下午三点 交报告 !! #工作");
+        assert_eq!(parsed.text, "交报告");
+        assert_eq!(parsed.deadline, at(9, 5, 15, 0));
+        assert_eq!(parsed.priority, Some(1));
+        assert_eq!(parsed.group_id.as_deref(), Some("work"));
+        assert!(parsed.tags.is_empty());
+
+        let parsed = parse("report to boss fri 5pm p1");
+        assert_eq!(parsed.text, "report to boss");
+        assert_eq!(parsed.deadline, at(9, 6, 17, 0));
+        assert_eq!(parsed.priority, Some(1));
+    }
+
+    #[test]
+    fn weekdays_wrap_to_the_next_week() {
+        assert_eq!(parse("周三 开会").deadline, at(9, 4, 23, 59));
+        assert_eq!(parse("周五 开会").deadline, at(9, 6, 23, 59));
+        assert_eq!(parse("周一 开会").deadline, at(9, 9, 23, 59));
+        assert_eq!(parse("星期日 开会").deadline, at(9, 8, 23, 59));
+        assert_eq!(parse("下周一 开会").deadline, at(9, 9, 23, 59));
+        assert_eq!(parse("下周三 开会").deadline, at(9, 11, 23, 59));
+        assert_eq!(parse("本周一 开会").deadline, at(9, 2, 23, 59));
+        assert_eq!(parse("meeting mon").deadline, at(9, 9, 23, 59));
+        assert_eq!(parse("meeting next fri").deadline, at(9, 13, 23, 59));
+    }
+
+    #[test]
+    fn parses_times_and_priorities() {
+        let parsed = parse("今晚8点 看电影");
+        assert_eq!(parsed.text, "看电影");
+        assert_eq!(parsed.deadline, at(9, 4, 20, 0));
+
+        let parsed = parse("call mom 5pm");
+        assert_eq!(parsed.text, "call mom");
+        assert_eq!(parsed.deadline, at(9, 4, 17, 0));
+
+        // 已经过了的时间为明天
+        assert_eq!(parse("早上8点 跑步").deadline, at(9, 5, 8, 0));
+
+        assert_eq!(parse("fix bug p1").priority, Some(1));
+        assert_eq!(parse("fix bug !!").priority, Some(1));
+        assert_eq!(parse("fix bug p3").priority, Some(0));
+        assert_eq!(parse("fix bug").priority, None);
+    }
+
+    #[test]
+    fn known_groups_and_other_tags() {
+        let parsed = parse("写周报 #工作 #urgent");
+        assert_eq!(parsed.text, "写周报");
+        assert_eq!(parsed.group_id.as_deref(), Some("work"));
+        assert_eq!(parsed.tags, vec!["urgent".to_string()]);
+
+        let parsed = parse("写周报 #生活");
+        assert_eq!(parsed.group_id, None);
+        assert_eq!(parsed.tags, vec!["生活".to_string()]);
+    }
+
+    #[test]
+    fn ambiguous_weekday_words_are_not_dates() {
+        for input in ["buy sun cream", "sat exam", "wed dress fitting"] {
+            let parsed = parse(input);
+            assert_eq!(parsed.text, input);
+            assert_eq!(parsed.deadline, None, "{}", input);
+        }
+        assert_eq!(parse("exam on sat").deadline, at(9, 7, 23, 59));
+        assert_eq!(parse("picnic next sun").deadline, at(9, 15, 23, 59));
+    }
+
+    #[test]
+    fn out_of_range_offsets_are_ignored() {
+        for input in ["99999999天后 交报告", "交报告 in 99999999 days", "交报告 in 99999999 weeks"] {
+            let parsed = parse(input);
+            assert_eq!(parsed.deadline, None, "{}", input);
+            assert_eq!(parsed.text, input);
+        }
+        assert_eq!(parse("3天后 交报告").deadline, at(9, 7, 23, 59));
+        assert_eq!(parse("交报告 in 2 hours").deadline, at(9, 4, 12, 0));
+    }
+}
@@ -80,7 +80,8 @@ pub async fn show_main_window(app: tauri::AppHandle) -> Result<(), String> {
     if let Some(window) = app.get_webview_window("main") {
         let _ = window.show();
         
-        // 加载设置
+        // 加载设置（只在 Windows 上用于调整窗口层级）
+        #[cfg(target_os = "windows")]
         let settings = load_app_settings(app.clone()).await.unwrap_or_default();
         
         // 使用 Windows API 强制获取焦点
@@ -275,13 +276,9 @@ pub async fn reset_window_position(app: tauri::AppHandle) -> Result<(), String>
         // 保存新位置
         save_window_position(app.clone(), x, y).await?;
         
-        // 加载当前设置并关闭"禁止拖动窗口"
-        if let Ok(mut settings) = load_app_settings(app.clone()).await {
-            settings.disable_drag = false;
-            // 保存更新后的设置
-            save_app_settings(app.clone(), settings).await?;
-            println!("已关闭禁止拖动窗口设置");
-        }
+        // 关闭"禁止拖动窗口"，其他设置保持不变
+        save_app_settings(app.clone(), serde_json::json!({ "disable_drag": false })).await?;
+        println!("已关闭禁止拖动窗口设置");
         
         // 显示并聚焦窗口
         let _ = window.show();
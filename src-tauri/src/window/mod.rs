@@ -1,6 +1,7 @@
 pub mod opacity;
 pub mod position;
 pub mod management;
+pub mod quick_add;
 
 // 移除未使用的导入，只保留实际使用的
 // pub use opacity::*;
@@ -0,0 +1,211 @@
+use std::str::FromStr;
+use tauri::{Emitter, Manager};
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, Modifiers, Shortcut, ShortcutState};
+
+use chrono::Local;
+
+use crate::data::todo_data::{get_data_dir, read_group_store};
+use crate::models::{Todo, TodoGroup};
+use crate::system::app_lock::ensure_unlocked;
+use crate::utils::task_parser::parse_task;
+
+// 快速添加窗口的标签
+const QUICK_ADD_LABEL: &str = "quick-add";
+
+// 快速添加窗口的大小（逻辑像素）
+const QUICK_ADD_WIDTH: f64 = 380.0;
+const QUICK_ADD_HEIGHT: f64 = 96.0;
+
+// 窗口相对鼠标位置的偏移（逻辑像素）
+const CURSOR_OFFSET: f64 = 12.0;
+
+// 系统或常用程序使用的快捷键，不能用作快速添加快捷键
+const RESERVED_SHORTCUTS: [&str; 14] = [
+    "CommandOrControl+A",
+    "CommandOrControl+C",
+    "CommandOrControl+V",
+    "CommandOrControl+X",
+    "CommandOrControl+Z",
+    "CommandOrControl+Y",
+    "CommandOrControl+S",
+    "CommandOrControl+W",
+    "CommandOrControl+Q",
+    "Alt+F4",
+    "Alt+Tab",
+    "Super+D",
+    "Super+L",
+    "Super+Tab",
+];
+
+// 解析并检查快捷键：必须包含修饰键（功能键除外），不能与系统常用快捷键冲突
+pub(crate) fn validate_shortcut(shortcut: &str) -> Result<Shortcut, String> {
+    let parsed = Shortcut::from_str(shortcut)
+        .map_err(|e| format!("无效的快捷键 {}: {}", shortcut, e))?;
+
+    let key = format!("{:?}", parsed.key);
+    let is_function_key = key.len() > 1 && key.starts_with('F') && key[1..].chars().all(|c| c.is_ascii_digit());
+    if parsed.mods.is_empty() && !is_function_key {
+        return Err("快捷键需要包含 Ctrl、Alt、Shift 或 Win/Cmd 修饰键".to_string());
+    }
+    if parsed.mods == Modifiers::SHIFT && !is_function_key {
+        return Err("快捷键不能只使用 Shift 修饰键，会影响正常输入".to_string());
+    }
+    if let Some(reserved) = RESERVED_SHORTCUTS.iter()
+        .find(|reserved| Shortcut::from_str(reserved).is_ok_and(|reserved| reserved == parsed))
+    {
+        return Err(format!("快捷键与系统常用快捷键 {} 冲突", reserved));
+    }
+    Ok(parsed)
+}
+
+// 注册快速添加快捷键
+fn register_shortcut(app: &tauri::AppHandle, shortcut: Shortcut) -> Result<(), String> {
+    app.global_shortcut()
+        .on_shortcut(shortcut, |app, _, event| {
+            if event.state == ShortcutState::Pressed {
+                tauri::async_runtime::spawn(open_quick_add_window(app.clone()));
+            }
+        })
+        .map_err(|e| format!("注册快捷键失败，可能已被其他程序占用: {}", e))
+}
+
+// 更换快速添加快捷键，新快捷键无法注册时恢复原来的快捷键
+pub(crate) fn apply_quick_add_shortcut(app: &tauri::AppHandle, old: &str, new: &str) -> Result<(), String> {
+    let new_shortcut = match new.trim() {
+        "" => None,
+        new => Some(validate_shortcut(new)?),
+    };
+    let old_shortcut = Shortcut::from_str(old).ok()
+        .filter(|old| app.global_shortcut().is_registered(*old));
+
+    if let Some(old_shortcut) = old_shortcut {
+        let _ = app.global_shortcut().unregister(old_shortcut);
+    }
+    if let Some(new_shortcut) = new_shortcut {
+        if let Err(e) = register_shortcut(app, new_shortcut) {
+            if let Some(old_shortcut) = old_shortcut {
+                let _ = register_shortcut(app, old_shortcut);
+            }
+            return Err(e);
+        }
+    }
+    Ok(())
+}
+
+// 应用启动时注册设置中的快速添加快捷键
+pub fn init_quick_add_shortcut(app: &tauri::AppHandle) {
+    let shortcut = match tauri::async_runtime::block_on(crate::data::load_app_settings(app.clone())) {
+        Ok(settings) => settings.quick_add_shortcut,
+        Err(e) => {
+            log::error!("读取快速添加快捷键失败: {}", e);
+            return;
+        }
+    };
+    if shortcut.trim().is_empty() {
+        return;
+    }
+    if let Err(e) = validate_shortcut(&shortcut).and_then(|shortcut| register_shortcut(app, shortcut)) {
+        log::error!("注册快速添加快捷键 {} 失败: {}", shortcut, e);
+    }
+}
+
+// 计算窗口位置：鼠标右下方，超出屏幕工作区时移回屏幕内
+fn position_near_cursor(app: &tauri::AppHandle) -> Option<tauri::PhysicalPosition<i32>> {
+    let cursor = app.cursor_position().ok()?;
+    let monitor = app.monitor_from_point(cursor.x, cursor.y).ok()??;
+    let scale = monitor.scale_factor();
+    let area = monitor.work_area();
+
+    let width = (QUICK_ADD_WIDTH * scale) as i32;
+    let height = (QUICK_ADD_HEIGHT * scale) as i32;
+    let offset = (CURSOR_OFFSET * scale) as i32;
+    let right = area.position.x + area.size.width as i32;
+    let bottom = area.position.y + area.size.height as i32;
+
+    let x = (cursor.x as i32 + offset).min(right - width).max(area.position.x);
+    let y = (cursor.y as i32 + offset).min(bottom - height).max(area.position.y);
+    Some(tauri::PhysicalPosition { x, y })
+}
+
+// Tauri 命令：在鼠标附近打开快速添加窗口
+#[tauri::command]
+pub async fn open_quick_add_window(app: tauri::AppHandle) -> Result<(), String> {
+    ensure_unlocked()?;
+    let position = position_near_cursor(&app);
+
+    let window = match app.get_webview_window(QUICK_ADD_LABEL) {
+        Some(window) => window,
+        None => {
+            let window = tauri::WebviewWindowBuilder::new(
+                &app,
+                QUICK_ADD_LABEL,
+                tauri::WebviewUrl::App("quick-add.html".into()),
+            )
+            .title("快速添加")
+            .inner_size(QUICK_ADD_WIDTH, QUICK_ADD_HEIGHT)
+            .resizable(false)
+            .decorations(false)
+            .always_on_top(true)
+            .skip_taskbar(true)
+            .visible(false)
+            .build()
+            .map_err(|e| format!("创建快速添加窗口失败: {}", e))?;
+
+            // 失去焦点时关闭，和系统的快速输入框一样
+            let handle = window.clone();
+            window.on_window_event(move |event| {
+                if let tauri::WindowEvent::Focused(false) = event {
+                    let _ = handle.close();
+                }
+            });
+            window
+        }
+    };
+
+    if let Some(position) = position {
+        let _ = window.set_position(tauri::Position::Physical(position));
+    }
+    let _ = window.show();
+    let _ = window.set_focus();
+    let _ = window.emit("quick-add-opened", ());
+    Ok(())
+}
+
+// Tauri 命令：关闭快速添加窗口
+#[tauri::command]
+pub async fn close_quick_add_window(app: tauri::AppHandle) -> Result<(), String> {
+    if let Some(window) = app.get_webview_window(QUICK_ADD_LABEL) {
+        window.close().map_err(|e| format!("关闭快速添加窗口失败: {}", e))?;
+    }
+    Ok(())
+}
+
+// Tauri 命令：获取快速添加窗口可选的分组
+// 直接读取数据文件，不改变主窗口保存数据时使用的基准
+#[tauri::command]
+pub async fn get_quick_add_groups(app: tauri::AppHandle) -> Result<Vec<TodoGroup>, String> {
+    ensure_unlocked()?;
+    let mut groups = read_group_store(&get_data_dir(&app)?)?.groups;
+    groups.sort_by_key(|group| group.order);
+    Ok(groups)
+}
+
+// Tauri 命令：提交快速添加的任务，新建后关闭窗口
+// 输入中的时间、"#分组名"、"#标签" 和 "!" 会被识别，"#分组名" 优先于窗口中选择的分组
+#[tauri::command]
+pub async fn submit_quick_add(app: tauri::AppHandle, text: String, group_id: Option<String>) -> Result<Todo, String> {
+    ensure_unlocked()?;
+    let groups = read_group_store(&get_data_dir(&app)?)?.groups;
+    let parsed = parse_task(&text, Local::now(), &groups);
+
+    let todo = crate::data::add_todo(
+        app.clone(),
+        parsed.text,
+        parsed.group_id.or(group_id),
+        parsed.priority,
+        parsed.deadline,
+        Some(parsed.tags),
+    ).await?;
+    close_quick_add_window(app).await?;
+    Ok(todo)
+}
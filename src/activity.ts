@@ -0,0 +1,32 @@
+import { invoke } from '@tauri-apps/api/core';
+
+// 用户操作时向后端报告活动，用于空闲自动锁定计时
+// 连续操作时最多每30秒报告一次，避免频繁调用后端
+const REPORT_INTERVAL = 30000;
+const ACTIVITY_EVENTS = ['mousedown', 'keydown', 'wheel', 'touchstart'];
+
+let lastReportedAt = 0;
+
+function reportActivity() {
+  const now = Date.now();
+  if (now - lastReportedAt < REPORT_INTERVAL) {
+    return;
+  }
+  lastReportedAt = now;
+  invoke('report_user_activity').catch(error => {
+    console.error('报告用户活动失败:', error);
+  });
+}
+
+export function startActivityReporting() {
+  for (const event of ACTIVITY_EVENTS) {
+    document.addEventListener(event, reportActivity, { passive: true });
+  }
+}
+
+export function stopActivityReporting() {
+  for (const event of ACTIVITY_EVENTS) {
+    document.removeEventListener(event, reportActivity);
+  }
+}
+
@@ -0,0 +1,5 @@
+import { createApp } from 'vue'
+import QuickAdd from './QuickAdd.vue'
+
+const app = createApp(QuickAdd)
+app.mount('#app')
@@ -8,6 +8,7 @@ export interface Todo {
   order: number; // 在分组内的排序
   groupId: string; // 所属分组ID
   priority: number; // 优先级：0=普通，1=重要
+  tags?: string[]; // 标签
   color?: string; // 小圆点颜色，可选（已弃用，由优先级决定）
 }
 
@@ -16,6 +17,11 @@ export interface TodoGroup {
   name: string;
   order: number; // 分组排序
   collapsed: boolean; // 是否折叠
+  parentId?: string; // 上级分组
+  color?: string; // 分组颜色
+  icon?: string; // 分组图标
+  defaultPriority?: number; // 新建任务的默认优先级
+  defaultDeadlineOffset?: string; // 新建任务的默认截止时间偏移
 }
 
 export interface DateInfo {
@@ -25,4 +31,87 @@ export interface DateInfo {
   lunar_year: string;    // 农历年份
   lunar_month: string;   // 农历月份
   lunar_day: string;     // 农历日期
-}
\ No newline at end of file
+  zodiac: string;        // 生肖
+  is_leap_month: boolean; // 是否为农历闰月
+  solar_term: string | null;            // 当天的节气
+  next_solar_term: UpcomingDay | null;  // 下一个节气
+  festivals: string[];                  // 当天的节日
+  next_festival: UpcomingDay | null;    // 下一个节日
+  is_workday: boolean;                  // 是否为工作日
+  is_holiday: boolean;                  // 是否为法定节假日放假
+  is_adjusted_workday: boolean;         // 是否为调休上班
+  holiday_name: string | null;          // 放假或调休对应的节日名称
+}
+
+// 即将到来的节日或节气
+export interface UpcomingDay {
+  name: string;        // 名称
+  date: string;        // 日期，格式：2024-09-17
+  days_until: number;  // 距离当天的天数
+}
+
+// 后端返回的任务数据（字段名与数据文件一致）
+export interface TodoRecord {
+  id: string;
+  text: string;
+  completed: boolean;
+  created_at: number;
+  completed_at?: number | null;
+  deadline?: number | null;
+  order: number;
+  group_id: string;
+  priority: number;
+  tags: string[];
+}
+
+// 日历中的一天
+export interface CalendarDay {
+  date: string;                  // 日期，格式：2024-09-17
+  day: number;                   // 公历日
+  weekday: number;               // 星期，0=星期一 … 6=星期日
+  is_today: boolean;             // 是否为今天
+  lunar_month: string;           // 农历月份
+  lunar_day: string;             // 农历日期
+  lunar_label: string;           // 日历格子中显示的农历文字
+  festivals: string[];           // 当天的节日
+  solar_term: string | null;     // 当天的节气
+  is_workday: boolean;           // 是否为工作日
+  is_holiday: boolean;           // 是否为法定节假日放假
+  is_adjusted_workday: boolean;  // 是否为调休上班
+  holiday_name: string | null;   // 放假或调休对应的节日名称
+  due: TodoRecord[];             // 当天截止的未完成任务
+  scheduled: TodoRecord[];       // 按创建时间排在当天的未完成任务
+  completed: TodoRecord[];       // 当天完成的任务
+}
+
+// 日历中的一个月
+export interface CalendarMonth {
+  year: number;
+  month: number;
+  deadline_priority: boolean;    // 是否按截止时间排列未完成的任务
+  days: CalendarDay[];
+}
+
+// 时间轴中的一项
+export interface TimelineItem {
+  todo: TodoRecord;
+  display_time: number;                          // 用于排序和显示的时间，Unix时间戳（秒）
+  status: 'pending' | 'overdue' | 'completed';
+}
+
+// 时间轴中的一个分组：已逾期、某一天或已完成
+export interface TimelineBucket {
+  key: string;                // "overdue"、"completed" 或日期
+  label: string;              // 显示的名称：已逾期、今天、明天、9月17日
+  weekday: string | null;     // 日期分组的星期
+  items: TimelineItem[];
+}
+
+// 时间轴
+export interface Timeline {
+  deadline_priority: boolean;
+  buckets: TimelineBucket[];
+  pending_count: number;
+  overdue_count: number;
+  completed_count: number;
+}
@@ -14,7 +14,8 @@ export default defineConfig({
     rollupOptions: {
       input: {
         main: resolve(__dirname, 'index.html'),
-        settings: resolve(__dirname, 'settings.html')
+        settings: resolve(__dirname, 'settings.html'),
+        quickAdd: resolve(__dirname, 'quick-add.html')
       },
       output: {
         entryFileNames: 'assets/[name]-[hash].js',